@@ -0,0 +1,244 @@
+//! Benchmarks of every indicator on synthetic 1-minute bars and on
+//! `data/AUDNZD1.csv`.
+//!
+//!     cargo bench --bench indicators
+//!     POLARS_TA_BENCH_ROWS=10000000 cargo bench --bench indicators -- synthetic
+//!
+//! The synthetic series has one million bars unless `POLARS_TA_BENCH_ROWS`
+//! says otherwise; it is generated from a fixed seed so runs compare.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkGroup, Criterion, Throughput};
+use criterion::measurement::WallTime;
+use polars::prelude::*;
+use polars_ta::candles::cdl_doji::{cdl_doji, DojiOptions};
+use polars_ta::candles::cdl_inside::cdl_inside;
+use polars_ta::candles::cdl_pattern::cdl_pattern;
+use polars_ta::candles::cdl_z::{cdl_z, CdlZOptions};
+use polars_ta::candles::ha::ha;
+use polars_ta::momentum::rsi::{rsi, RsiOptions};
+use polars_ta::momentum::slope::slope;
+use polars_ta::overlap::dema::{dema, DemaOptions};
+use polars_ta::overlap::ema::{ema, EmaOptions};
+use polars_ta::overlap::linreg::{linreg, LinregOptions};
+use polars_ta::overlap::rma::{rma, RmaOptions};
+use polars_ta::overlap::sma::{sma, SmaOptions};
+use polars_ta::performance::drawdown::drawdown;
+use polars_ta::performance::log_return::log_return;
+use polars_ta::performance::percent_return::percent_return;
+use polars_ta::statistics::entropy::entropy;
+use polars_ta::statistics::kurtosis::kurtosis;
+use polars_ta::statistics::mad::mad;
+use polars_ta::statistics::median::median;
+use polars_ta::statistics::quantile::quantile;
+use polars_ta::statistics::skew::skew;
+use polars_ta::statistics::stdev::stdev;
+use polars_ta::statistics::variance::variance;
+use polars_ta::statistics::zscore::zscore;
+use polars_ta::streaming::{AtrState, DemaState, EmaState, RsiState, TrueRangeState};
+use polars_ta::utils::core::non_zero_range;
+use polars_ta::utils::data_loader::{csv_to_dataframe, set_column_names};
+use polars_ta::volatility::atr::{atr, AtrOptions};
+use polars_ta::volatility::true_range::true_range;
+use polars_ta::volume::aobv::{aobv, AobvOptions};
+use polars_ta::volume::nvi::nvi;
+use polars_ta::volume::obv::obv;
+use polars_ta::volume::pvi::pvi;
+use polars_ta::volume::vp::{vp, VpOptions};
+
+
+struct Bars {
+    open: Series,
+    high: Series,
+    low: Series,
+    close: Series,
+    volume: Series,
+}
+
+
+impl Bars {
+    fn len(&self) -> usize {
+        self.close.len()
+    }
+
+    fn values(series: &Series) -> Vec<f64> {
+        series.f64().unwrap().into_no_null_iter().collect()
+    }
+}
+
+
+/// A random walk around 1.0 with the tick size and volume of a currency pair,
+/// from a xorshift generator with a fixed seed.
+fn synthetic(rows: usize) -> Bars {
+    let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+    let mut uniform = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        (state >> 11) as f64 / (1u64 << 53) as f64
+    };
+    let (mut open, mut high, mut low, mut close, mut volume) =
+        (Vec::with_capacity(rows), Vec::with_capacity(rows), Vec::with_capacity(rows), Vec::with_capacity(rows), Vec::with_capacity(rows));
+    let mut price = 1.0;
+    for _ in 0..rows {
+        let o = price;
+        let c = o + (uniform() - 0.5) * 4e-4;
+        open.push(o);
+        close.push(c);
+        high.push(o.max(c) + uniform() * 2e-4);
+        low.push(o.min(c) - uniform() * 2e-4);
+        volume.push((uniform() * 200.0).floor());
+        price = c;
+    }
+    Bars {
+        open: Series::new("open".into(), open),
+        high: Series::new("high".into(), high),
+        low: Series::new("low".into(), low),
+        close: Series::new("close".into(), close),
+        volume: Series::new("volume".into(), volume),
+    }
+}
+
+
+fn audnzd() -> Bars {
+    let mut df = csv_to_dataframe("data/AUDNZD1.csv", false).unwrap();
+    set_column_names(&mut df, vec!["date", "time", "open", "high", "low", "close", "volume"]).unwrap();
+    let column = |name: &str| {
+        df.column(name).unwrap().as_materialized_series().cast(&DataType::Float64).unwrap()
+    };
+    Bars {
+        open: column("open"),
+        high: column("high"),
+        low: column("low"),
+        close: column("close"),
+        volume: column("volume"),
+    }
+}
+
+
+fn rows() -> usize {
+    std::env::var("POLARS_TA_BENCH_ROWS")
+        .ok()
+        .and_then(|rows| rows.parse().ok())
+        .unwrap_or(1_000_000)
+}
+
+
+macro_rules! bench {
+    ($group:expr, $name:literal, $indicator:expr) => {
+        $group.bench_function($name, |b| b.iter(|| black_box($indicator.unwrap())));
+    };
+}
+
+
+fn overlap(group: &mut BenchmarkGroup<WallTime>, bars: &Bars) {
+    let close = &bars.close;
+    bench!(group, "sma", sma(close, SmaOptions::default()));
+    bench!(group, "ema", ema(close, EmaOptions::default()));
+    bench!(group, "ema_ewm", ema(close, EmaOptions { mamode: Some("ema".to_string()), ..Default::default() }));
+    bench!(group, "dema", dema(close, DemaOptions::default()));
+    bench!(group, "rma", rma(close, RmaOptions::default()));
+    bench!(group, "linreg", linreg(close, LinregOptions::default()));
+    bench!(group, "slope", slope(close, None, false, false, None, None));
+    bench!(group, "rsi", rsi(close, RsiOptions::default()));
+}
+
+
+fn volatility(group: &mut BenchmarkGroup<WallTime>, bars: &Bars) {
+    let (high, low, close) = (&bars.high, &bars.low, &bars.close);
+    bench!(group, "non_zero_range", non_zero_range(high, low));
+    bench!(group, "true_range", true_range(high, low, close, None, None, None));
+    bench!(group, "atr", atr(high, low, close, AtrOptions::default()));
+}
+
+
+fn volume(group: &mut BenchmarkGroup<WallTime>, bars: &Bars) {
+    let (close, volume) = (&bars.close, &bars.volume);
+    bench!(group, "obv", obv(close, volume, None, None));
+    bench!(group, "nvi", nvi(close, volume, None, None, None, None));
+    bench!(group, "pvi", pvi(close, volume, None, None, None, None));
+    bench!(group, "aobv", aobv(close, volume, AobvOptions::default()));
+    bench!(group, "vp", vp(close, volume, VpOptions::default()));
+}
+
+
+fn statistics(group: &mut BenchmarkGroup<WallTime>, bars: &Bars) {
+    let close = &bars.close;
+    bench!(group, "zscore", zscore(close, None, None, None, None));
+    bench!(group, "stdev", stdev(close, None, None, None, None));
+    bench!(group, "variance", variance(close, None, None, None, None));
+    bench!(group, "skew", skew(close, None, None, None));
+    bench!(group, "kurtosis", kurtosis(close, None, None, None));
+    bench!(group, "quantile", quantile(close, None, None, None, None));
+    bench!(group, "median", median(close, None, None, None));
+    bench!(group, "mad", mad(close, None, None, None));
+    bench!(group, "entropy", entropy(close, None, None, None, None));
+}
+
+
+fn performance(group: &mut BenchmarkGroup<WallTime>, bars: &Bars) {
+    let close = &bars.close;
+    bench!(group, "log_return", log_return(close, None, false, None, None));
+    bench!(group, "percent_return", percent_return(close, None, false, None, None));
+    bench!(group, "drawdown", drawdown(close, None, None));
+}
+
+
+fn candles(group: &mut BenchmarkGroup<WallTime>, bars: &Bars) {
+    let (open, high, low, close) = (&bars.open, &bars.high, &bars.low, &bars.close);
+    bench!(group, "ha", ha(open, high, low, close, None, None));
+    bench!(group, "cdl_doji", cdl_doji(open, high, low, close, DojiOptions::default()));
+    bench!(group, "cdl_inside", cdl_inside(open, high, low, close, false, None, None));
+    bench!(group, "cdl_z", cdl_z(open, high, low, close, CdlZOptions::default()));
+    bench!(group, "cdl_pattern_all", cdl_pattern(open, high, low, close, &[]));
+}
+
+
+/// The streaming states fed every bar, one update at a time.
+fn streaming(group: &mut BenchmarkGroup<WallTime>, bars: &Bars) {
+    let (high, low, close) = (Bars::values(&bars.high), Bars::values(&bars.low), Bars::values(&bars.close));
+    group.bench_function("streaming_ema", |b| b.iter(|| {
+        let mut state = EmaState::new(EmaOptions::default()).unwrap();
+        close.iter().fold(None, |_, &close| state.update(close))
+    }));
+    group.bench_function("streaming_dema", |b| b.iter(|| {
+        let mut state = DemaState::new(DemaOptions::default()).unwrap();
+        close.iter().fold(None, |_, &close| state.update(close))
+    }));
+    group.bench_function("streaming_rsi", |b| b.iter(|| {
+        let mut state = RsiState::new(RsiOptions::default()).unwrap();
+        close.iter().fold(None, |_, &close| state.update(close))
+    }));
+    group.bench_function("streaming_true_range", |b| b.iter(|| {
+        let mut state = TrueRangeState::new(None).unwrap();
+        (0..close.len()).fold(None, |_, i| state.update(high[i], low[i], close[i]))
+    }));
+    group.bench_function("streaming_atr", |b| b.iter(|| {
+        let mut state = AtrState::new(AtrOptions::default()).unwrap();
+        (0..close.len()).fold(None, |_, i| state.update(high[i], low[i], close[i]))
+    }));
+}
+
+
+fn run(c: &mut Criterion, name: &str, bars: &Bars) {
+    let mut group = c.benchmark_group(name);
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(bars.len() as u64));
+    overlap(&mut group, bars);
+    volatility(&mut group, bars);
+    volume(&mut group, bars);
+    statistics(&mut group, bars);
+    performance(&mut group, bars);
+    candles(&mut group, bars);
+    streaming(&mut group, bars);
+    group.finish();
+}
+
+
+fn benches(c: &mut Criterion) {
+    run(c, "audnzd", &audnzd());
+    run(c, "synthetic", &synthetic(rows()));
+}
+
+
+criterion_group!(indicators, benches);
+criterion_main!(indicators);
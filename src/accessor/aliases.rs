@@ -0,0 +1,117 @@
+use crate::utils::error::{CommandError, CommandResult};
+use polars::prelude::*;
+
+
+/// The price and volume columns an indicator can take as input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Input {
+    Open,
+    High,
+    Low,
+    Close,
+    Volume,
+}
+
+
+impl Input {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Input::Open => "open",
+            Input::High => "high",
+            Input::Low => "low",
+            Input::Close => "close",
+            Input::Volume => "volume",
+        }
+    }
+}
+
+
+/// Column names tried, in order and ignoring case, for each input.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct ColumnAliases {
+    pub open: Vec<String>,
+    pub high: Vec<String>,
+    pub low: Vec<String>,
+    pub close: Vec<String>,
+    pub volume: Vec<String>,
+}
+
+
+impl Default for ColumnAliases {
+    fn default() -> Self {
+        let aliases = |names: &[&str]| names.iter().map(|name| name.to_string()).collect();
+        ColumnAliases {
+            open: aliases(&["open", "o"]),
+            high: aliases(&["high", "h"]),
+            low: aliases(&["low", "l"]),
+            close: aliases(&["close", "c", "adj close", "adj_close"]),
+            volume: aliases(&["volume", "vol", "v"]),
+        }
+    }
+}
+
+
+impl ColumnAliases {
+    pub fn get(&self, input: Input) -> &[String] {
+        match input {
+            Input::Open => &self.open,
+            Input::High => &self.high,
+            Input::Low => &self.low,
+            Input::Close => &self.close,
+            Input::Volume => &self.volume,
+        }
+    }
+
+    /// Puts `alias` first for `input`, e.g. `("close", "mid")`.
+    pub fn set(&mut self, input: Input, alias: &str) {
+        let aliases = match input {
+            Input::Open => &mut self.open,
+            Input::High => &mut self.high,
+            Input::Low => &mut self.low,
+            Input::Close => &mut self.close,
+            Input::Volume => &mut self.volume,
+        };
+        aliases.insert(0, alias.to_string());
+    }
+
+    /// The name of the column of `df` used as `input`.
+    pub fn resolve(&self, df: &DataFrame, input: Input) -> CommandResult<PlSmallStr> {
+        for alias in self.get(input) {
+            let found = df
+                .get_column_names()
+                .into_iter()
+                .find(|name| name.eq_ignore_ascii_case(alias));
+            if let Some(name) = found {
+                return Ok(name.clone());
+            }
+        }
+        Err(CommandError::MissingColumn(input.name().to_string()))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        let df = df!(
+            "Date" => [1, 2],
+            "Adj Close" => [1.0, 2.0],
+            "PX_LAST" => [1.0, 2.0]
+        ).unwrap();
+        let mut aliases = ColumnAliases::default();
+        assert_eq!(aliases.resolve(&df, Input::Close).unwrap().as_str(), "Adj Close");
+        assert!(matches!(
+            aliases.resolve(&df, Input::Volume),
+            Err(CommandError::MissingColumn(name)) if name == "volume"
+        ));
+        aliases.set(Input::Close, "px_last");
+        assert_eq!(aliases.resolve(&df, Input::Close).unwrap().as_str(), "PX_LAST");
+    }
+}
@@ -0,0 +1,807 @@
+pub mod aliases;
+
+use crate::accessor::aliases::{ColumnAliases, Input};
+use crate::batch::{evaluate, required_inputs, Bars, IndicatorSpec, Strategy};
+use crate::candles::cdl_doji::{cdl_doji, DojiOptions};
+use crate::candles::cdl_inside::cdl_inside;
+use crate::candles::cdl_pattern::{cdl_pattern_with, CandlePattern, CdlPatternOptions};
+use crate::candles::cdl_z::{cdl_z, CdlZOptions};
+use crate::candles::ha::ha;
+use crate::momentum::rsi::{rsi, RsiOptions};
+use crate::momentum::slope::slope;
+use crate::overlap::dema::{dema, DemaOptions};
+use crate::overlap::ema::{ema, EmaOptions};
+use crate::overlap::linreg::{linreg, LinregOptions};
+use crate::overlap::rma::{rma, RmaOptions};
+use crate::overlap::sma::{sma, SmaOptions};
+use crate::performance::drawdown::drawdown;
+use crate::performance::log_return::log_return;
+use crate::performance::percent_return::percent_return;
+use crate::statistics::entropy::entropy;
+use crate::statistics::kurtosis::kurtosis;
+use crate::statistics::mad::mad;
+use crate::statistics::median::median;
+use crate::statistics::quantile::quantile;
+use crate::statistics::skew::skew;
+use crate::statistics::stdev::stdev;
+use crate::statistics::variance::variance;
+use crate::statistics::zscore::zscore;
+use crate::utils::core::FillPolicy;
+use crate::utils::error::{CommandError, CommandResult};
+use crate::utils::validate::with_short_inputs;
+use crate::volatility::atr::{atr, AtrOptions};
+use crate::volatility::true_range::true_range;
+use crate::volume::aobv::{aobv, AobvOptions};
+use crate::volume::nvi::nvi;
+use crate::volume::obv::obv;
+use crate::volume::pvi::pvi;
+use crate::volume::vp::{vp, VpOptions};
+use polars::prelude::*;
+
+
+const OHLC: [Input; 4] = [Input::Open, Input::High, Input::Low, Input::Close];
+const HLC: [Input; 3] = [Input::High, Input::Low, Input::Close];
+
+
+/// Indicators on the columns of a `DataFrame`, like pandas-ta's `df.ta`.
+///
+/// ```ignore
+/// let ema = df.ta().append(true).ema(EmaOptions::default())?; // adds EMA_10
+/// ```
+pub trait TaExt {
+    fn ta(&mut self) -> Ta<'_>;
+}
+
+
+impl TaExt for DataFrame {
+    fn ta(&mut self) -> Ta<'_> {
+        Ta::new(self)
+    }
+}
+
+
+/// Resolves the input columns of an indicator through `aliases`, names the
+/// result the way pandas-ta does and, with `append`, adds it to the frame,
+/// replacing a column of the same name. With `group_by` the indicator runs
+/// separately on the rows of each group, so warm-ups restart at every symbol,
+/// and the result is put back in the original row order. A group with fewer
+/// rows than the indicator needs gets warm-up rows only; the call fails on
+/// insufficient data only when no group has enough rows.
+pub struct Ta<'a> {
+    df: &'a mut DataFrame,
+    aliases: ColumnAliases,
+    append: bool,
+    group: Option<String>,
+}
+
+
+/// Row indices of each group of `column`, in order of first appearance.
+fn group_indices(df: &DataFrame, column: &str) -> CommandResult<Vec<Vec<IdxSize>>> {
+    let groups = df.group_by_stable([column])?.take_groups();
+    let indices = match groups {
+        GroupsProxy::Idx(groups) => groups
+            .into_iter()
+            .map(|(_, indices)| indices.to_vec())
+            .collect(),
+        GroupsProxy::Slice { groups, .. } => groups
+            .into_iter()
+            .map(|[first, len]| (first..first + len).collect())
+            .collect(),
+    };
+    Ok(indices)
+}
+
+
+/// The permutation that moves rows concatenated group by group back to the
+/// positions in `rows`.
+fn original_order(rows: &[IdxSize]) -> IdxCa {
+    let mut order = vec![0 as IdxSize; rows.len()];
+    for (position, row) in rows.iter().enumerate() {
+        order[*row as usize] = position as IdxSize;
+    }
+    IdxCa::from_vec("order".into(), order)
+}
+
+
+impl<'a> Ta<'a> {
+    pub fn new(df: &'a mut DataFrame) -> Self {
+        Ta {
+            df,
+            aliases: ColumnAliases::default(),
+            append: false,
+            group: None,
+        }
+    }
+
+    pub fn aliases(mut self, aliases: ColumnAliases) -> Self {
+        self.aliases = aliases;
+        self
+    }
+
+    /// Uses the column `name` for `input`, e.g. `.alias(Input::Close, "mid")`.
+    pub fn alias(mut self, input: Input, name: &str) -> Self {
+        self.aliases.set(input, name);
+        self
+    }
+
+    pub fn append(mut self, append: bool) -> Self {
+        self.append = append;
+        self
+    }
+
+    /// Computes every indicator per value of `column`, e.g. "symbol".
+    pub fn group_by(mut self, column: &str) -> Self {
+        self.group = Some(column.to_string());
+        self
+    }
+
+    fn input(&self, input: Input) -> CommandResult<Series> {
+        let name = self.aliases.resolve(self.df, input)?;
+        Ok(self.df.column(&name)?.as_materialized_series().clone())
+    }
+
+    fn inputs(&self, inputs: &[Input]) -> CommandResult<Vec<Series>> {
+        inputs.iter().map(|input| self.input(*input)).collect()
+    }
+
+    /// The rows of `inputs` belonging to each group, with their indices.
+    fn groups(
+        &self,
+        group: &str,
+        inputs: &[Series]
+    ) -> CommandResult<Vec<(Vec<IdxSize>, Vec<Series>)>> {
+        let mut groups = Vec::new();
+        for indices in group_indices(self.df, group)? {
+            let take = IdxCa::from_vec("take".into(), indices.clone());
+            let parts = inputs
+                .iter()
+                .map(|input| input.take(&take))
+                .collect::<PolarsResult<Vec<Series>>>()?;
+            groups.push((indices, parts));
+        }
+        Ok(groups)
+    }
+
+    /// `f` on the rows of each group. Under strict validation a group too
+    /// short for the indicator is evaluated again with short inputs allowed,
+    /// which gives warm-up rows, unless every group is too short. Its
+    /// parameters are still validated strictly.
+    fn each_group<T, F>(
+        &self,
+        group: &str,
+        inputs: &[Series],
+        f: F
+    ) -> CommandResult<Vec<(Vec<IdxSize>, T)>>
+    where
+        F: Fn(&[Series]) -> CommandResult<T>,
+    {
+        let mut results = Vec::new();
+        let mut short = None;
+        let mut complete = false;
+        for (indices, parts) in self.groups(group, inputs)? {
+            let result = match f(&parts) {
+                Err(error @ CommandError::InsufficientData { .. }) => {
+                    short.get_or_insert(error);
+                    with_short_inputs(|| f(&parts))?
+                },
+                result => {
+                    complete = true;
+                    result?
+                },
+            };
+            results.push((indices, result));
+        }
+        match short {
+            Some(error) if !complete => Err(error),
+            _ => Ok(results),
+        }
+    }
+
+    fn apply<F>(&self, inputs: &[Input], f: F) -> CommandResult<Series>
+    where
+        F: Fn(&[Series]) -> CommandResult<Series>,
+    {
+        let inputs = self.inputs(inputs)?;
+        let Some(group) = &self.group else {
+            return f(&inputs);
+        };
+        let mut rows = Vec::with_capacity(self.df.height());
+        let mut result: Option<Series> = None;
+        for (indices, part) in self.each_group(group, &inputs, &f)? {
+            match result.as_mut() {
+                Some(result) => {
+                    result.append(&part)?;
+                },
+                None => result = Some(part),
+            }
+            rows.extend(indices);
+        }
+        match result {
+            Some(result) => Ok(result.take(&original_order(&rows))?),
+            None => f(&inputs),
+        }
+    }
+
+    fn apply_frame<F>(&self, inputs: &[Input], f: F) -> CommandResult<DataFrame>
+    where
+        F: Fn(&[Series]) -> CommandResult<DataFrame>,
+    {
+        let inputs = self.inputs(inputs)?;
+        let Some(group) = &self.group else {
+            return f(&inputs);
+        };
+        let mut rows = Vec::with_capacity(self.df.height());
+        let mut result: Option<DataFrame> = None;
+        for (indices, part) in self.each_group(group, &inputs, &f)? {
+            match result.as_mut() {
+                Some(result) => {
+                    result.vstack_mut(&part)?;
+                },
+                None => result = Some(part),
+            }
+            rows.extend(indices);
+        }
+        match result {
+            Some(result) => Ok(result.take(&original_order(&rows))?),
+            None => f(&inputs),
+        }
+    }
+
+    fn series(&mut self, series: Series) -> CommandResult<Series> {
+        if self.append {
+            self.df.with_column(series.clone())?;
+        }
+        Ok(series)
+    }
+
+    fn frame(&mut self, frame: DataFrame) -> CommandResult<DataFrame> {
+        if self.append {
+            for column in frame.get_columns() {
+                self.df.with_column(column.clone())?;
+            }
+        }
+        Ok(frame)
+    }
+
+    pub fn ema(&mut self, options: EmaOptions) -> CommandResult<Series> {
+        let ema = self.apply(&[Input::Close], |s| ema(&s[0], options.clone()))?;
+        self.series(ema)
+    }
+
+    pub fn dema(&mut self, options: DemaOptions) -> CommandResult<Series> {
+        let dema = self.apply(&[Input::Close], |s| dema(&s[0], options.clone()))?;
+        self.series(dema)
+    }
+
+    pub fn sma(&mut self, options: SmaOptions) -> CommandResult<Series> {
+        let sma = self.apply(&[Input::Close], |s| sma(&s[0], options.clone()))?;
+        self.series(sma)
+    }
+
+    pub fn rma(&mut self, options: RmaOptions) -> CommandResult<Series> {
+        let rma = self.apply(&[Input::Close], |s| rma(&s[0], options.clone()))?;
+        self.series(rma)
+    }
+
+    pub fn linreg(&mut self, options: LinregOptions) -> CommandResult<Series> {
+        let linreg = self.apply(&[Input::Close], |s| linreg(&s[0], options.clone()))?;
+        self.series(linreg)
+    }
+
+    pub fn slope(
+        &mut self,
+        length: Option<i32>,
+        as_angle: bool,
+        to_degrees: bool,
+        offset: Option<i32>,
+        fill: Option<FillPolicy>
+    ) -> CommandResult<Series> {
+        let slope = self.apply(&[Input::Close], |s| {
+            slope(&s[0], length, as_angle, to_degrees, offset, fill)
+        })?;
+        self.series(slope)
+    }
+
+    pub fn rsi(&mut self, options: RsiOptions) -> CommandResult<Series> {
+        let rsi = self.apply(&[Input::Close], |s| rsi(&s[0], options.clone()))?;
+        self.series(rsi)
+    }
+
+    pub fn true_range(
+        &mut self,
+        drift: Option<i32>,
+        offset: Option<i32>,
+        fill: Option<FillPolicy>
+    ) -> CommandResult<Series> {
+        let true_range = self.apply(&HLC, |s| true_range(&s[0], &s[1], &s[2], drift, offset, fill))?;
+        self.series(true_range)
+    }
+
+    pub fn atr(&mut self, options: AtrOptions) -> CommandResult<Series> {
+        let atr = self.apply(&HLC, |s| atr(&s[0], &s[1], &s[2], options.clone()))?;
+        self.series(atr)
+    }
+
+    pub fn obv(
+        &mut self,
+        offset: Option<i32>,
+        fill: Option<FillPolicy>
+    ) -> CommandResult<Series> {
+        let obv = self.apply(&[Input::Close, Input::Volume], |s| obv(&s[0], &s[1], offset, fill))?;
+        self.series(obv)
+    }
+
+    pub fn nvi(
+        &mut self,
+        length: Option<i32>,
+        initial: Option<f64>,
+        offset: Option<i32>,
+        fill: Option<FillPolicy>
+    ) -> CommandResult<Series> {
+        let nvi = self.apply(&[Input::Close, Input::Volume], |s| {
+            nvi(&s[0], &s[1], length, initial, offset, fill)
+        })?;
+        self.series(nvi)
+    }
+
+    pub fn pvi(
+        &mut self,
+        length: Option<i32>,
+        initial: Option<f64>,
+        offset: Option<i32>,
+        fill: Option<FillPolicy>
+    ) -> CommandResult<Series> {
+        let pvi = self.apply(&[Input::Close, Input::Volume], |s| {
+            pvi(&s[0], &s[1], length, initial, offset, fill)
+        })?;
+        self.series(pvi)
+    }
+
+    pub fn aobv(&mut self, options: AobvOptions) -> CommandResult<DataFrame> {
+        let aobv = self.apply_frame(&[Input::Close, Input::Volume], |s| {
+            aobv(&s[0], &s[1], options.clone())
+        })?;
+        self.frame(aobv)
+    }
+
+    /// The volume profile has one row per bin, so it is never appended. Per
+    /// group the profiles are stacked with the group key as first column.
+    pub fn vp(&mut self, options: VpOptions) -> CommandResult<DataFrame> {
+        let inputs = self.inputs(&[Input::Close, Input::Volume])?;
+        let Some(group) = &self.group else {
+            return vp(&inputs[0], &inputs[1], options);
+        };
+        let keys = self.df.column(group)?.as_materialized_series();
+        let mut result: Option<DataFrame> = None;
+        for (indices, parts) in self.groups(group, &inputs)? {
+            let mut part = vp(&parts[0], &parts[1], options.clone())?;
+            let key = keys.new_from_index(indices[0] as usize, part.height());
+            part.insert_column(0, key)?;
+            match result.as_mut() {
+                Some(result) => {
+                    result.vstack_mut(&part)?;
+                },
+                None => result = Some(part),
+            }
+        }
+        match result {
+            Some(result) => Ok(result),
+            None => vp(&inputs[0], &inputs[1], options),
+        }
+    }
+
+    pub fn zscore(
+        &mut self,
+        length: Option<i32>,
+        std: Option<f64>,
+        offset: Option<i32>,
+        fill: Option<FillPolicy>
+    ) -> CommandResult<Series> {
+        let zscore = self.apply(&[Input::Close], |s| zscore(&s[0], length, std, offset, fill))?;
+        self.series(zscore)
+    }
+
+    pub fn stdev(
+        &mut self,
+        length: Option<i32>,
+        ddof: Option<i32>,
+        offset: Option<i32>,
+        fill: Option<FillPolicy>
+    ) -> CommandResult<Series> {
+        let stdev = self.apply(&[Input::Close], |s| stdev(&s[0], length, ddof, offset, fill))?;
+        self.series(stdev)
+    }
+
+    pub fn variance(
+        &mut self,
+        length: Option<i32>,
+        ddof: Option<i32>,
+        offset: Option<i32>,
+        fill: Option<FillPolicy>
+    ) -> CommandResult<Series> {
+        let variance = self.apply(&[Input::Close], |s| variance(&s[0], length, ddof, offset, fill))?;
+        self.series(variance)
+    }
+
+    pub fn skew(
+        &mut self,
+        length: Option<i32>,
+        offset: Option<i32>,
+        fill: Option<FillPolicy>
+    ) -> CommandResult<Series> {
+        let skew = self.apply(&[Input::Close], |s| skew(&s[0], length, offset, fill))?;
+        self.series(skew)
+    }
+
+    pub fn kurtosis(
+        &mut self,
+        length: Option<i32>,
+        offset: Option<i32>,
+        fill: Option<FillPolicy>
+    ) -> CommandResult<Series> {
+        let kurtosis = self.apply(&[Input::Close], |s| kurtosis(&s[0], length, offset, fill))?;
+        self.series(kurtosis)
+    }
+
+    pub fn quantile(
+        &mut self,
+        length: Option<i32>,
+        q: Option<f64>,
+        offset: Option<i32>,
+        fill: Option<FillPolicy>
+    ) -> CommandResult<Series> {
+        let quantile = self.apply(&[Input::Close], |s| quantile(&s[0], length, q, offset, fill))?;
+        self.series(quantile)
+    }
+
+    pub fn median(
+        &mut self,
+        length: Option<i32>,
+        offset: Option<i32>,
+        fill: Option<FillPolicy>
+    ) -> CommandResult<Series> {
+        let median = self.apply(&[Input::Close], |s| median(&s[0], length, offset, fill))?;
+        self.series(median)
+    }
+
+    pub fn mad(
+        &mut self,
+        length: Option<i32>,
+        offset: Option<i32>,
+        fill: Option<FillPolicy>
+    ) -> CommandResult<Series> {
+        let mad = self.apply(&[Input::Close], |s| mad(&s[0], length, offset, fill))?;
+        self.series(mad)
+    }
+
+    pub fn entropy(
+        &mut self,
+        length: Option<i32>,
+        base: Option<f64>,
+        offset: Option<i32>,
+        fill: Option<FillPolicy>
+    ) -> CommandResult<Series> {
+        let entropy = self.apply(&[Input::Close], |s| entropy(&s[0], length, base, offset, fill))?;
+        self.series(entropy)
+    }
+
+    pub fn log_return(
+        &mut self,
+        length: Option<i32>,
+        cumulative: bool,
+        offset: Option<i32>,
+        fill: Option<FillPolicy>
+    ) -> CommandResult<Series> {
+        let log_return = self.apply(&[Input::Close], |s| {
+            log_return(&s[0], length, cumulative, offset, fill)
+        })?;
+        self.series(log_return)
+    }
+
+    pub fn percent_return(
+        &mut self,
+        length: Option<i32>,
+        cumulative: bool,
+        offset: Option<i32>,
+        fill: Option<FillPolicy>
+    ) -> CommandResult<Series> {
+        let percent_return = self.apply(&[Input::Close], |s| {
+            percent_return(&s[0], length, cumulative, offset, fill)
+        })?;
+        self.series(percent_return)
+    }
+
+    pub fn drawdown(
+        &mut self,
+        offset: Option<i32>,
+        fill: Option<FillPolicy>
+    ) -> CommandResult<DataFrame> {
+        let drawdown = self.apply_frame(&[Input::Close], |s| drawdown(&s[0], offset, fill))?;
+        self.frame(drawdown)
+    }
+
+    pub fn ha(
+        &mut self,
+        offset: Option<i32>,
+        fill: Option<FillPolicy>
+    ) -> CommandResult<DataFrame> {
+        let ha = self.apply_frame(&OHLC, |s| ha(&s[0], &s[1], &s[2], &s[3], offset, fill))?;
+        self.frame(ha)
+    }
+
+    pub fn cdl_doji(&mut self, options: DojiOptions) -> CommandResult<Series> {
+        let doji = self.apply(&OHLC, |s| cdl_doji(&s[0], &s[1], &s[2], &s[3], options.clone()))?;
+        self.series(doji)
+    }
+
+    pub fn cdl_inside(
+        &mut self,
+        asbool: bool,
+        offset: Option<i32>,
+        fill: Option<FillPolicy>
+    ) -> CommandResult<Series> {
+        let inside = self.apply(&OHLC, |s| cdl_inside(&s[0], &s[1], &s[2], &s[3], asbool, offset, fill))?;
+        self.series(inside)
+    }
+
+    pub fn cdl_z(&mut self, options: CdlZOptions) -> CommandResult<DataFrame> {
+        let z = self.apply_frame(&OHLC, |s| cdl_z(&s[0], &s[1], &s[2], &s[3], options.clone()))?;
+        self.frame(z)
+    }
+
+    pub fn cdl_pattern(
+        &mut self,
+        names: &[CandlePattern],
+        options: CdlPatternOptions
+    ) -> CommandResult<DataFrame> {
+        let patterns = self.apply_frame(&OHLC, |s| {
+            cdl_pattern_with(&s[0], &s[1], &s[2], &s[3], names, options.clone())
+        })?;
+        self.frame(patterns)
+    }
+
+    /// Every indicator of `specs` in one frame, sharing the intermediates
+    /// they have in common. See `batch::evaluate`.
+    pub fn batch(&mut self, specs: &[IndicatorSpec]) -> CommandResult<DataFrame> {
+        if specs.is_empty() {
+            return Ok(DataFrame::empty());
+        }
+        let inputs = required_inputs(specs);
+        let batch = self.apply_frame(&inputs, |s| {
+            let bars = Bars::new(inputs.iter().copied().zip(s.iter().cloned()));
+            evaluate(&bars, specs)
+        })?;
+        self.frame(batch)
+    }
+
+    /// The indicators of `strategy`, as one batch.
+    pub fn strategy(&mut self, strategy: &Strategy) -> CommandResult<DataFrame> {
+        self.batch(&strategy.ta)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::utils::data_loader::{
+        csv_to_dataframe,
+        set_column_names,
+        combine_date_time,
+        convert_to_naive_datetime
+    };
+
+    use super::*;
+
+    fn load() -> DataFrame {
+        let mut df = csv_to_dataframe(
+            "data/AUDNZD1.csv",
+            false
+        ).unwrap();
+        let _ = set_column_names(
+            &mut df,
+            vec!["date", "time", "open", "high", "low", "close", "volume"]
+        );
+        let df = combine_date_time(&df).unwrap();
+        convert_to_naive_datetime(
+            &df,
+            "%Y.%m.%d %H:%M"
+        ).unwrap()
+    }
+
+    #[test]
+    fn test_append() {
+        let mut df = load();
+        let width = df.width();
+
+        let ema = df.ta().ema(EmaOptions::default()).unwrap();
+        assert_eq!(ema.name().as_str(), "EMA_10");
+        assert_eq!(df.width(), width);
+
+        let _ = df.ta().append(true).ema(EmaOptions::default()).unwrap();
+        let _ = df.ta().append(true).atr(AtrOptions::default()).unwrap();
+        let _ = df.ta().append(true).drawdown(None, None).unwrap();
+        assert!(df.column("EMA_10").is_ok());
+        assert!(df.column("ATRr_14").is_ok());
+        assert!(df.column("DD_PCT").is_ok());
+
+        // appending again replaces the column
+        let _ = df.ta().append(true).ema(EmaOptions::default()).unwrap();
+        assert_eq!(df.width(), width + 5);
+
+        let expected = ema.f64().unwrap();
+        let appended = df.column("EMA_10").unwrap().f64().unwrap();
+        assert_eq!(expected.get(20), appended.get(20));
+    }
+
+    #[test]
+    fn test_aliases() {
+        let mut df = load();
+        df.rename("close", "Mid".into()).unwrap();
+        assert!(df.ta().sma(SmaOptions::default()).is_err());
+
+        let sma = df.ta().alias(Input::Close, "mid").sma(SmaOptions::default()).unwrap();
+        assert_eq!(sma.name().as_str(), "SMA_10");
+        assert!(sma.f64().unwrap().get(9).is_some());
+    }
+
+    #[test]
+    fn test_group_by() {
+        let df = load().slice(0, 200);
+        let a = df.slice(0, 100);
+        let b = df.slice(100, 100);
+
+        // interleave the two symbols row by row
+        let mut long = df.clone();
+        let symbols: Vec<&str> = (0..200).map(|i| if i < 100 { "A" } else { "B" }).collect();
+        long.with_column(Series::new("symbol".into(), symbols)).unwrap();
+        let interleaved: Vec<IdxSize> = (0..100).flat_map(|i| [i, i + 100]).collect();
+        let mut long = long.take(&IdxCa::from_vec("take".into(), interleaved)).unwrap();
+
+        let _ = long.ta().group_by("symbol").append(true).ema(EmaOptions::default()).unwrap();
+        let _ = long.ta().group_by("symbol").append(true).true_range(None, None, None).unwrap();
+        let _ = long.ta().group_by("symbol").append(true).drawdown(None, None).unwrap();
+
+        for (symbol, single) in [("A", a), ("B", b)] {
+            let mask = long.column("symbol").unwrap().str().unwrap().equal(symbol);
+            let rows = long.filter(&mask).unwrap();
+            let close = single.column("close").unwrap().as_series().unwrap();
+            let high = single.column("high").unwrap().as_series().unwrap();
+            let low = single.column("low").unwrap().as_series().unwrap();
+
+            let expected = ema(close, EmaOptions::default()).unwrap();
+            let grouped = rows.column("EMA_10").unwrap().as_materialized_series();
+            assert!(grouped.equals_missing(&expected.with_name("EMA_10".into())));
+
+            // the warm-up restarts at each symbol
+            let grouped = rows.column("TRUERANGE_1").unwrap().f64().unwrap();
+            assert_eq!(grouped.get(0), None);
+            let expected = true_range(high, low, close, None, None, None).unwrap();
+            assert_eq!(grouped.get(50), expected.f64().unwrap().get(50));
+
+            let expected = drawdown(close, None, None).unwrap();
+            let grouped = rows.column("DD").unwrap().as_materialized_series();
+            assert!(grouped.equals_missing(expected.column("DD").unwrap().as_materialized_series()));
+        }
+
+        let profile = long.ta().group_by("symbol").vp(VpOptions::default()).unwrap();
+        assert_eq!(profile.get_column_names()[0].as_str(), "symbol");
+        assert_eq!(profile.height(), 20);
+    }
+
+    #[test]
+    fn test_group_by_short() {
+        use crate::utils::error::CommandError;
+
+        // "B" has 2 rows, fewer than the 3 an EMA of length 3 needs
+        let mut df = load().slice(0, 12);
+        let symbols: Vec<&str> = (0..12).map(|i| if i < 10 { "A" } else { "B" }).collect();
+        df.with_column(Series::new("symbol".into(), symbols)).unwrap();
+        let options = EmaOptions { length: Some(3), ..Default::default() };
+
+        let grouped = df.ta().group_by("symbol").ema(options.clone()).unwrap();
+        let a = df.slice(0, 10);
+        let expected = ema(a.column("close").unwrap().as_series().unwrap(), options.clone()).unwrap();
+        assert!(grouped.slice(0, 10).equals_missing(&expected));
+        assert_eq!(grouped.slice(10, 2).null_count(), 2);
+
+        let frame = df.ta().group_by("symbol").aobv(AobvOptions { fast: Some(2), slow: Some(3), ..Default::default() }).unwrap();
+        assert_eq!(frame.height(), 12);
+        assert_eq!(frame.column("OBVe_3").unwrap().slice(10, 2).null_count(), 2);
+
+        // a short group does not hide parameters checked after its rows
+        let invalid = AobvOptions { fast: Some(2), slow: Some(3), max_lookback: Some(0), ..Default::default() };
+        assert!(matches!(
+            df.ta().group_by("symbol").aobv(invalid),
+            Err(CommandError::InvalidParameter { .. })
+        ));
+
+        // without any group long enough the call fails like an ungrouped one
+        let mut short = df.slice(10, 2);
+        assert!(matches!(
+            short.ta().group_by("symbol").ema(options),
+            Err(CommandError::InsufficientData { needed: 3, got: 2 })
+        ));
+        // but parameters checked after the rows are still validated strictly
+        let invalid = AobvOptions { fast: Some(2), slow: Some(3), run_length: Some(-1), ..Default::default() };
+        assert!(matches!(
+            short.ta().group_by("symbol").aobv(invalid),
+            Err(CommandError::InvalidParameter { .. })
+        ));
+    }
+
+    #[test]
+    fn test_batch() {
+        let mut df = load().slice(0, 200);
+        let specs = [
+            IndicatorSpec::Ema(EmaOptions::default()),
+            IndicatorSpec::Dema(DemaOptions { length: Some(10), ..Default::default() }),
+            IndicatorSpec::Atr(AtrOptions::default()),
+            IndicatorSpec::Aobv(AobvOptions::default()),
+        ];
+        let width = df.width();
+        let batch = df.ta().append(true).batch(&specs).unwrap();
+        assert_eq!(df.width(), width + batch.width());
+        let expected = df.ta().dema(DemaOptions { length: Some(10), ..Default::default() }).unwrap();
+        assert!(batch.column("DEMA_10").unwrap().as_materialized_series().equals_missing(&expected));
+
+        // per symbol the batch matches the indicators computed one by one
+        let symbols: Vec<&str> = (0..200).map(|i| if i % 2 == 0 { "A" } else { "B" }).collect();
+        df.with_column(Series::new("symbol".into(), symbols)).unwrap();
+        let batch = df.ta().group_by("symbol").batch(&specs).unwrap();
+        let expected = df.ta().group_by("symbol").atr(AtrOptions::default()).unwrap();
+        assert!(batch.column("ATRr_14").unwrap().as_materialized_series().equals_missing(&expected));
+        assert!(df.ta().batch(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_validation() {
+        use crate::utils::error::CommandError;
+        use crate::utils::validate::{with_validation, Validation};
+
+        let mut df = load().slice(0, 3);
+        assert!(matches!(
+            df.ta().ema(EmaOptions::default()),
+            Err(CommandError::InsufficientData { needed: 10, got: 3 })
+        ));
+        assert!(matches!(
+            df.ta().sma(SmaOptions { length: Some(0), ..Default::default() }),
+            Err(CommandError::InvalidParameter { .. })
+        ));
+
+        // pandas-ta substitutes defaults and returns warm-up rows only
+        with_validation(Validation::Lenient, || {
+            let mut ta = df.ta();
+            let series = [
+                ta.ema(EmaOptions { length: Some(0), ..Default::default() }).unwrap(),
+                ta.dema(DemaOptions::default()).unwrap(),
+                ta.sma(SmaOptions::default()).unwrap(),
+                ta.rma(RmaOptions::default()).unwrap(),
+                ta.linreg(LinregOptions::default()).unwrap(),
+                ta.slope(None, false, false, None, None).unwrap(),
+                ta.rsi(RsiOptions::default()).unwrap(),
+                ta.true_range(Some(5), None, None).unwrap(),
+                ta.atr(AtrOptions::default()).unwrap(),
+                ta.zscore(None, Some(-1.0), None, None).unwrap(),
+                ta.stdev(None, None, None, None).unwrap(),
+                ta.variance(None, Some(50), None, None).unwrap(),
+                ta.skew(None, None, None).unwrap(),
+                ta.kurtosis(None, None, None).unwrap(),
+                ta.quantile(None, Some(2.0), None, None).unwrap(),
+                ta.median(None, None, None).unwrap(),
+                ta.mad(None, None, None).unwrap(),
+                ta.entropy(None, None, None, None).unwrap(),
+                ta.log_return(Some(5), false, None, None).unwrap(),
+                ta.percent_return(Some(5), false, None, None).unwrap(),
+            ];
+            for series in series {
+                assert_eq!(series.len(), 3);
+                assert_eq!(series.null_count(), 3, "{} has values", series.name());
+            }
+            assert_eq!(ta.cdl_doji(DojiOptions::default()).unwrap().len(), 3);
+            assert_eq!(ta.aobv(AobvOptions::default()).unwrap().height(), 3);
+            assert_eq!(ta.cdl_z(CdlZOptions::default()).unwrap().height(), 3);
+            assert_eq!(ta.cdl_pattern(&[], CdlPatternOptions::default()).unwrap().height(), 3);
+        });
+    }
+}
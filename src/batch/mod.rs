@@ -0,0 +1,415 @@
+//! Many indicators on one frame in a single call.
+//!
+//! A batch first collects the intermediates its indicators have in common,
+//! like the EMA inside DEMA or the true range inside ATR, computes each of
+//! them once, then computes the indicators from them. Both steps run on the
+//! rayon thread pool, with the missing value policy and validation mode in
+//! effect on the calling thread.
+
+pub mod spec;
+pub mod strategy;
+
+pub use spec::IndicatorSpec;
+pub use strategy::Strategy;
+
+use crate::accessor::aliases::Input;
+use crate::overlap::ema::{ema, EmaOptions};
+use crate::utils::error::{CommandError, CommandResult};
+use crate::utils::missing::{missing_policy, with_missing_policy};
+use crate::utils::validate::{validation, with_validation};
+use crate::volatility::true_range::true_range;
+use polars::prelude::*;
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+
+/// The input columns of a batch.
+#[derive(Clone, Default)]
+pub struct Bars {
+    columns: HashMap<Input, Series>,
+}
+
+
+impl Bars {
+    pub fn new(columns: impl IntoIterator<Item = (Input, Series)>) -> Self {
+        Bars { columns: columns.into_iter().collect() }
+    }
+
+    pub fn get(&self, input: Input) -> CommandResult<&Series> {
+        self.columns
+            .get(&input)
+            .ok_or_else(|| CommandError::MissingColumn(input.name().to_string()))
+    }
+}
+
+
+/// A result several indicators are computed from, without offset or fill.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum Intermediate {
+    /// The EMA of close, seeded with an SMA for `sma`.
+    Ema { length: Option<i32>, adjust: bool, sma: bool },
+    TrueRange { drift: Option<i32> },
+}
+
+
+impl Intermediate {
+    fn compute(&self, bars: &Bars) -> CommandResult<Series> {
+        match self {
+            Intermediate::Ema { length, adjust, sma } => ema(bars.get(Input::Close)?, EmaOptions {
+                length: *length,
+                adjust: *adjust,
+                mamode: Some(if *sma { "sma" } else { "ema" }.to_string()),
+                offset: None,
+                fill: None,
+            }),
+            Intermediate::TrueRange { drift } => {
+                let [high, low, close] = [Input::High, Input::Low, Input::Close].map(|input| bars.get(input));
+                true_range(high?, low?, close?, *drift, None, None)
+            },
+        }
+    }
+}
+
+
+/// The inputs read by any of `specs`, in the order of `Input`.
+pub fn required_inputs(specs: &[IndicatorSpec]) -> Vec<Input> {
+    [Input::Open, Input::High, Input::Low, Input::Close, Input::Volume]
+        .into_iter()
+        .filter(|input| specs.iter().any(|spec| spec.inputs().contains(input)))
+        .collect()
+}
+
+
+/// The distinct intermediates of `specs`, in order of first use.
+pub(crate) fn intermediates(specs: &[IndicatorSpec]) -> Vec<Intermediate> {
+    let mut intermediates: Vec<Intermediate> = Vec::new();
+    for intermediate in specs.iter().flat_map(|spec| spec.intermediates()) {
+        if !intermediates.contains(&intermediate) {
+            intermediates.push(intermediate);
+        }
+    }
+    intermediates
+}
+
+
+/// Computes every indicator of `specs` on `bars` and returns their columns
+/// in order. A column named like an earlier one replaces it, as it does when
+/// appending through `df.ta()`.
+pub fn evaluate(bars: &Bars, specs: &[IndicatorSpec]) -> CommandResult<DataFrame> {
+    let (policy, validation) = (missing_policy(), validation());
+
+    let shared = intermediates(specs)
+        .into_par_iter()
+        .map(|intermediate| {
+            let series = with_missing_policy(policy, || {
+                with_validation(validation, || intermediate.compute(bars))
+            })?;
+            Ok((intermediate, series))
+        })
+        .collect::<CommandResult<HashMap<Intermediate, Series>>>()?;
+
+    let columns = specs
+        .par_iter()
+        .map(|spec| {
+            with_missing_policy(policy, || {
+                with_validation(validation, || spec.compute(bars, &shared))
+            })
+        })
+        .collect::<CommandResult<Vec<Vec<Column>>>>()?;
+
+    let mut df = DataFrame::empty();
+    for column in columns.into_iter().flatten() {
+        df.with_column(column)?;
+    }
+    Ok(df)
+}
+
+
+/// The rows before every indicator of `specs` has a converged value, the
+/// largest of their lookbacks.
+pub fn lookback(specs: &[IndicatorSpec]) -> usize {
+    specs.iter().map(IndicatorSpec::lookback).max().unwrap_or(0)
+}
+
+
+/// The rows at the end without a value in some indicator of `specs` shifted
+/// backward, the largest of their negative offsets.
+pub fn trailing(specs: &[IndicatorSpec]) -> usize {
+    specs.iter().map(IndicatorSpec::trailing).max().unwrap_or(0)
+}
+
+
+/// The leading and trailing rows of a `height` rows result to leave out, the
+/// trailing ones fewer when the leading ones already cover them.
+fn unconverged(height: usize, specs: &[IndicatorSpec]) -> (usize, usize) {
+    let leading = lookback(specs).min(height);
+    (leading, trailing(specs).min(height - leading))
+}
+
+
+/// `df`, the result of `specs` on a single series of bars, from its first row
+/// with every indicator converged to its last row with every indicator
+/// having a value. With `Ta::group_by` the warm-ups restart for every group,
+/// trim each group instead.
+pub fn trim(df: &DataFrame, specs: &[IndicatorSpec]) -> DataFrame {
+    let (leading, trailing) = unconverged(df.height(), specs);
+    df.slice(leading as i64, df.height() - leading - trailing)
+}
+
+
+/// `df` with every column missing before the first row with every indicator
+/// of `specs` converged and after the last with every indicator having a
+/// value, keeping the rows aligned with the bars.
+pub fn mask(df: &DataFrame, specs: &[IndicatorSpec]) -> CommandResult<DataFrame> {
+    let (leading, trailing) = unconverged(df.height(), specs);
+    let kept = df.height() - leading - trailing;
+    let columns = df
+        .get_columns()
+        .iter()
+        .map(|column| {
+            let series = column.as_materialized_series();
+            let mut masked = Series::full_null(series.name().clone(), leading, series.dtype());
+            masked.append(&series.slice(leading as i64, kept))?;
+            masked.append(&Series::full_null(series.name().clone(), trailing, series.dtype()))?;
+            Ok(masked.into_column())
+        })
+        .collect::<CommandResult<Vec<Column>>>()?;
+    Ok(DataFrame::new(columns)?)
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::utils::data_loader::{
+        csv_to_dataframe,
+        set_column_names,
+        combine_date_time,
+        convert_to_naive_datetime
+    };
+    use crate::candles::cdl_z::CdlZOptions;
+    use crate::momentum::rsi::RsiOptions;
+    use crate::overlap::dema::{dema, DemaOptions};
+    use crate::overlap::ema::{ema, EmaOptions};
+    use crate::overlap::sma::SmaOptions;
+    use crate::utils::missing::{InteriorNulls, MissingPolicy, WarmUpValue};
+    use crate::volatility::atr::{atr, AtrOptions};
+    use crate::volatility::true_range::true_range;
+    use super::*;
+
+    fn load() -> Bars {
+        let mut df = csv_to_dataframe(
+            "data/AUDNZD1.csv",
+            false
+        ).unwrap();
+        let _ = set_column_names(
+            &mut df,
+            vec!["date", "time", "open", "high", "low", "close", "volume"]
+        );
+        let df = combine_date_time(&df).unwrap();
+        let df = convert_to_naive_datetime(
+            &df,
+            "%Y.%m.%d %H:%M"
+        ).unwrap();
+        Bars::new([Input::Open, Input::High, Input::Low, Input::Close, Input::Volume].map(|input| {
+            (input, df.column(input.name()).unwrap().as_materialized_series().clone())
+        }))
+    }
+
+    #[test]
+    fn test_intermediates() {
+        let specs = [
+            IndicatorSpec::Ema(EmaOptions::default()),
+            IndicatorSpec::Dema(DemaOptions { length: Some(10), ..Default::default() }),
+            IndicatorSpec::Ema(EmaOptions { length: Some(20), ..Default::default() }),
+            IndicatorSpec::TrueRange { drift: None, offset: Some(1), fill: None },
+            IndicatorSpec::Atr(AtrOptions::default()),
+            IndicatorSpec::Atr(AtrOptions { percent: true, ..Default::default() }),
+        ];
+        assert_eq!(intermediates(&specs), vec![
+            Intermediate::Ema { length: Some(10), adjust: false, sma: true },
+            Intermediate::Ema { length: Some(20), adjust: false, sma: true },
+            Intermediate::TrueRange { drift: None },
+        ]);
+        assert_eq!(required_inputs(&specs), vec![Input::High, Input::Low, Input::Close]);
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let bars = load();
+        let close = bars.get(Input::Close).unwrap();
+        let (high, low) = (bars.get(Input::High).unwrap(), bars.get(Input::Low).unwrap());
+        let specs = [
+            IndicatorSpec::Ema(EmaOptions { offset: Some(2), ..Default::default() }),
+            IndicatorSpec::Dema(DemaOptions { length: Some(10), ..Default::default() }),
+            IndicatorSpec::TrueRange { drift: None, offset: None, fill: None },
+            IndicatorSpec::Atr(AtrOptions { percent: true, ..Default::default() }),
+            IndicatorSpec::Skew { length: None, offset: None, fill: None },
+        ];
+        let df = evaluate(&bars, &specs).unwrap();
+        assert_eq!(df.height(), close.len());
+
+        let expected = [
+            ema(close, EmaOptions { offset: Some(2), ..Default::default() }).unwrap(),
+            dema(close, DemaOptions { length: Some(10), ..Default::default() }).unwrap(),
+            true_range(high, low, close, None, None, None).unwrap(),
+            atr(high, low, close, AtrOptions { percent: true, ..Default::default() }).unwrap(),
+            crate::statistics::skew::skew(close, None, None, None).unwrap(),
+        ];
+        assert_eq!(df.width(), expected.len());
+        for expected in expected {
+            let column = df.column(expected.name()).unwrap().as_materialized_series();
+            assert!(column.equals_missing(&expected), "{} differs", expected.name());
+        }
+    }
+
+    #[test]
+    fn test_evaluate_policy() {
+        let close = Series::new("close".into(), (0..40).map(|i| {
+            if i == 20 { None } else { Some(1.0 + (i as f64 * 0.7).sin() / 100.0) }
+        }).collect::<Vec<_>>());
+        let bars = Bars::new([(Input::Close, close.clone())]);
+        let specs = [IndicatorSpec::Dema(DemaOptions { length: Some(5), ..Default::default() })];
+
+        // the policy of the calling thread applies on the pool
+        let policy = MissingPolicy { interior: InteriorNulls::Skip, ..Default::default() };
+        with_missing_policy(policy, || {
+            let df = evaluate(&bars, &specs).unwrap();
+            let expected = dema(&close, DemaOptions { length: Some(5), ..Default::default() }).unwrap();
+            assert!(df.column("DEMA_5").unwrap().as_materialized_series().equals_missing(&expected));
+            assert!(df.column("DEMA_5").unwrap().f64().unwrap().get(21).is_some());
+        });
+
+        let policy = MissingPolicy { interior: InteriorNulls::Error, ..Default::default() };
+        assert!(with_missing_policy(policy, || evaluate(&bars, &specs)).is_err());
+        assert!(matches!(
+            evaluate(&bars, &[IndicatorSpec::Obv { offset: None, fill: None }]),
+            Err(CommandError::MissingColumn(name)) if name == "volume"
+        ));
+    }
+
+    #[test]
+    fn test_evaluate_shifted_nan_policy() {
+        let bars = load();
+        let (high, low, close) = (bars.get(Input::High).unwrap(), bars.get(Input::Low).unwrap(), bars.get(Input::Close).unwrap());
+        let specs = [
+            IndicatorSpec::Ema(EmaOptions { length: Some(3), offset: Some(2), ..Default::default() }),
+            IndicatorSpec::Dema(DemaOptions { length: Some(3), offset: Some(-2), ..Default::default() }),
+            IndicatorSpec::TrueRange { drift: None, offset: Some(2), fill: None },
+            IndicatorSpec::Atr(AtrOptions { length: Some(3), offset: Some(-2), ..Default::default() }),
+        ];
+        // the shared intermediates are shifted like the indicators shift
+        // theirs, with the policy's missing value in the emptied rows
+        let policy = MissingPolicy { warm_up: WarmUpValue::NaN, ..Default::default() };
+        with_missing_policy(policy, || {
+            let df = evaluate(&bars, &specs).unwrap();
+            let expected = [
+                ema(close, EmaOptions { length: Some(3), offset: Some(2), ..Default::default() }).unwrap(),
+                dema(close, DemaOptions { length: Some(3), offset: Some(-2), ..Default::default() }).unwrap(),
+                true_range(high, low, close, None, Some(2), None).unwrap(),
+                atr(high, low, close, AtrOptions { length: Some(3), offset: Some(-2), ..Default::default() }).unwrap(),
+            ];
+            let bits = |series: &Series| -> Vec<Option<u64>> {
+                series.f64().unwrap().into_iter().map(|value| value.map(f64::to_bits)).collect()
+            };
+            for expected in expected {
+                let got = df.column(expected.name()).unwrap().as_materialized_series();
+                assert_eq!(got.null_count(), 0, "{}", expected.name());
+                assert_eq!(bits(got), bits(&expected), "{}", expected.name());
+            }
+        });
+    }
+
+    fn leading_nulls(df: &DataFrame) -> usize {
+        df.get_columns()
+            .iter()
+            .map(|column| column.is_null().into_iter().take_while(|null| *null == Some(true)).count())
+            .max()
+            .unwrap_or(0)
+    }
+
+    #[test]
+    fn test_lookback() {
+        let bars = load();
+        let specs = [
+            IndicatorSpec::Sma(SmaOptions { length: Some(25), offset: Some(3), ..Default::default() }),
+            IndicatorSpec::Ema(EmaOptions { length: Some(7), offset: Some(-2), ..Default::default() }),
+            IndicatorSpec::Rsi(RsiOptions { length: Some(9), drift: Some(3), ..Default::default() }),
+            IndicatorSpec::Atr(AtrOptions { length: Some(5), drift: Some(2), mamode: Some("sma".to_string()), ..Default::default() }),
+            IndicatorSpec::TrueRange { drift: Some(4), offset: None, fill: None },
+            IndicatorSpec::Kurtosis { length: Some(12), offset: Some(1), fill: None },
+            IndicatorSpec::Entropy { length: Some(6), base: None, offset: None, fill: None },
+            IndicatorSpec::PercentReturn { length: Some(5), cumulative: false, offset: None, fill: None },
+            IndicatorSpec::LogReturn { length: Some(5), cumulative: true, offset: None, fill: None },
+            IndicatorSpec::CdlZ(CdlZOptions { length: Some(8), ..Default::default() }),
+        ];
+        // windowed outputs are missing exactly until their lookback
+        for spec in &specs {
+            let df = evaluate(&bars, std::slice::from_ref(spec)).unwrap();
+            assert_eq!(leading_nulls(&df), spec.lookback(), "{:?}", spec);
+        }
+        // the others have values before it, unconverged or 0
+        for indicator in crate::registry::indicators() {
+            let df = evaluate(&bars, std::slice::from_ref(&indicator.spec)).unwrap();
+            assert!(leading_nulls(&df) <= indicator.spec.lookback(), "{}", indicator.kind);
+        }
+        assert_eq!(IndicatorSpec::Dema(DemaOptions { length: Some(10), ..Default::default() }).lookback(), 18);
+        assert_eq!(IndicatorSpec::Aobv(Default::default()).lookback(), 13);
+        // invalid parameters count with their lenient defaults
+        assert_eq!(IndicatorSpec::Kurtosis { length: Some(2), offset: None, fill: None }.lookback(), 29);
+        assert_eq!(lookback(&specs), 27);
+        assert_eq!(lookback(&[]), 0);
+    }
+
+    #[test]
+    fn test_trim_mask() {
+        let bars = load();
+        let specs = [
+            IndicatorSpec::Sma(SmaOptions::default()),
+            IndicatorSpec::Dema(DemaOptions { length: Some(10), offset: Some(2), ..Default::default() }),
+            IndicatorSpec::Skew { length: Some(15), offset: None, fill: None },
+        ];
+        let df = evaluate(&bars, &specs).unwrap();
+        assert_eq!(lookback(&specs), 20);
+
+        let trimmed = trim(&df, &specs);
+        assert_eq!(trimmed.height(), df.height() - 20);
+        assert!(trimmed.equals_missing(&df.slice(20, df.height())));
+        assert_eq!(leading_nulls(&trimmed), 0);
+
+        let masked = mask(&df, &specs).unwrap();
+        assert_eq!(masked.height(), df.height());
+        assert_eq!(leading_nulls(&masked), 20);
+        assert!(masked.slice(20, df.height()).equals_missing(&trimmed));
+
+        let short = df.slice(0, 5);
+        assert_eq!(trim(&short, &specs).height(), 0);
+        assert_eq!(mask(&short, &specs).unwrap().height(), 5);
+    }
+
+    #[test]
+    fn test_trim_mask_backward() {
+        let bars = load();
+        let specs = [
+            IndicatorSpec::Sma(SmaOptions { length: Some(10), offset: Some(-3), ..Default::default() }),
+            IndicatorSpec::Rsi(RsiOptions { length: Some(5), offset: Some(-1), ..Default::default() }),
+        ];
+        let df = evaluate(&bars, &specs).unwrap();
+        let height = df.height();
+        assert_eq!((lookback(&specs), trailing(&specs)), (6, 3));
+
+        let trimmed = trim(&df, &specs);
+        assert_eq!(trimmed.height(), height - 9);
+        assert!(trimmed.equals_missing(&df.slice(6, height - 9)));
+        assert_eq!(trimmed.get_columns().iter().map(Column::null_count).sum::<usize>(), 0);
+
+        let masked = mask(&df, &specs).unwrap();
+        assert_eq!(masked.height(), height);
+        assert!(masked.slice(6, height - 9).equals_missing(&trimmed));
+        assert_eq!(masked.slice(height as i64 - 3, 3).get_columns().iter().map(Column::null_count).sum::<usize>(), 6);
+
+        let short = df.slice(0, 8);
+        assert_eq!(trim(&short, &specs).height(), 0);
+        assert_eq!(mask(&short, &specs).unwrap().height(), 8);
+    }
+}
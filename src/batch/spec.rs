@@ -0,0 +1,695 @@
+use crate::accessor::aliases::Input;
+use crate::batch::{Bars, Intermediate};
+use crate::candles::cdl_doji::{cdl_doji, DojiOptions};
+use crate::candles::cdl_inside::cdl_inside;
+use crate::candles::cdl_pattern::{cdl_pattern_with, CandlePattern, CdlPatternOptions};
+use crate::candles::cdl_z::{cdl_z, CdlZOptions};
+use crate::candles::ha::ha;
+use crate::candles::settings::CandleSettings;
+use crate::momentum::rsi::{rsi, RsiOptions};
+use crate::momentum::slope::slope;
+use crate::overlap::dema::{dema_from_ema, DemaOptions};
+use crate::overlap::ema::EmaOptions;
+use crate::overlap::linreg::{linreg, LinregOptions};
+use crate::overlap::rma::{rma, RmaOptions};
+use crate::overlap::sma::{sma, SmaOptions};
+use crate::performance::drawdown::drawdown;
+use crate::performance::log_return::log_return;
+use crate::performance::percent_return::percent_return;
+use crate::statistics::entropy::entropy;
+use crate::statistics::kurtosis::kurtosis;
+use crate::statistics::mad::mad;
+use crate::statistics::median::median;
+use crate::statistics::quantile::quantile;
+use crate::statistics::skew::skew;
+use crate::statistics::stdev::stdev;
+use crate::statistics::variance::variance;
+use crate::statistics::zscore::zscore;
+use crate::utils::core::{get_offset, shifted_lookback, fill_missing, FillPolicy};
+use crate::utils::error::CommandResult;
+use crate::utils::missing::shifted;
+use crate::utils::validate;
+use crate::volatility::atr::{atr_from_true_range, AtrOptions};
+use crate::volume::aobv::{aobv, AobvOptions};
+use crate::volume::nvi::nvi;
+use crate::volume::obv::obv;
+use crate::volume::pvi::pvi;
+use polars::prelude::*;
+use std::collections::HashMap;
+
+
+const CLOSE: [Input; 1] = [Input::Close];
+const CLOSE_VOLUME: [Input; 2] = [Input::Close, Input::Volume];
+const HLC: [Input; 3] = [Input::High, Input::Low, Input::Close];
+const OHLC: [Input; 4] = [Input::Open, Input::High, Input::Low, Input::Close];
+
+
+/// One indicator of a batch with its parameters. Variants wrapping options
+/// take the options of the indicator function, the others its arguments.
+/// The volume profile is left out, it has one row per price bin rather than
+/// per bar. Serialized, the variant is the snake case `kind` of the spec,
+/// as in pandas-ta strategies: `{"kind": "sma", "length": 20}`. Fields left
+/// out of a config take their defaults. Unset fields are left out when
+/// written, formats like TOML have no null, and read back as the defaults
+/// they stand for.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind", rename_all = "snake_case"))]
+pub enum IndicatorSpec {
+    Sma(SmaOptions),
+    Ema(EmaOptions),
+    Dema(DemaOptions),
+    Rma(RmaOptions),
+    Linreg(LinregOptions),
+    Slope {
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        length: Option<i32>,
+        #[cfg_attr(feature = "serde", serde(default))]
+        as_angle: bool,
+        #[cfg_attr(feature = "serde", serde(default))]
+        to_degrees: bool,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        offset: Option<i32>,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        fill: Option<FillPolicy>,
+    },
+    Rsi(RsiOptions),
+    TrueRange {
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        drift: Option<i32>,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        offset: Option<i32>,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        fill: Option<FillPolicy>,
+    },
+    Atr(AtrOptions),
+    Obv {
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        offset: Option<i32>,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        fill: Option<FillPolicy>,
+    },
+    Nvi {
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        length: Option<i32>,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        initial: Option<f64>,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        offset: Option<i32>,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        fill: Option<FillPolicy>,
+    },
+    Pvi {
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        length: Option<i32>,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        initial: Option<f64>,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        offset: Option<i32>,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        fill: Option<FillPolicy>,
+    },
+    Aobv(AobvOptions),
+    Zscore {
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        length: Option<i32>,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        std: Option<f64>,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        offset: Option<i32>,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        fill: Option<FillPolicy>,
+    },
+    Stdev {
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        length: Option<i32>,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        ddof: Option<i32>,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        offset: Option<i32>,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        fill: Option<FillPolicy>,
+    },
+    Variance {
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        length: Option<i32>,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        ddof: Option<i32>,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        offset: Option<i32>,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        fill: Option<FillPolicy>,
+    },
+    Skew {
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        length: Option<i32>,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        offset: Option<i32>,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        fill: Option<FillPolicy>,
+    },
+    Kurtosis {
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        length: Option<i32>,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        offset: Option<i32>,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        fill: Option<FillPolicy>,
+    },
+    Quantile {
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        length: Option<i32>,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        q: Option<f64>,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        offset: Option<i32>,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        fill: Option<FillPolicy>,
+    },
+    Median {
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        length: Option<i32>,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        offset: Option<i32>,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        fill: Option<FillPolicy>,
+    },
+    Mad {
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        length: Option<i32>,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        offset: Option<i32>,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        fill: Option<FillPolicy>,
+    },
+    Entropy {
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        length: Option<i32>,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        base: Option<f64>,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        offset: Option<i32>,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        fill: Option<FillPolicy>,
+    },
+    LogReturn {
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        length: Option<i32>,
+        #[cfg_attr(feature = "serde", serde(default))]
+        cumulative: bool,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        offset: Option<i32>,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        fill: Option<FillPolicy>,
+    },
+    PercentReturn {
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        length: Option<i32>,
+        #[cfg_attr(feature = "serde", serde(default))]
+        cumulative: bool,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        offset: Option<i32>,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        fill: Option<FillPolicy>,
+    },
+    Drawdown {
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        offset: Option<i32>,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        fill: Option<FillPolicy>,
+    },
+    Ha {
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        offset: Option<i32>,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        fill: Option<FillPolicy>,
+    },
+    CdlDoji(DojiOptions),
+    CdlInside {
+        #[cfg_attr(feature = "serde", serde(default))]
+        asbool: bool,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        offset: Option<i32>,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        fill: Option<FillPolicy>,
+    },
+    CdlZ(CdlZOptions),
+    /// An empty `names` matches every pattern. The options are boxed, the
+    /// candle settings would make every spec as large.
+    CdlPattern {
+        #[cfg_attr(feature = "serde", serde(default))]
+        names: Vec<CandlePattern>,
+        #[cfg_attr(feature = "serde", serde(default))]
+        options: Box<CdlPatternOptions>,
+    },
+}
+
+
+/// Shifts and fills an intermediate the way the indicator would have.
+fn finish(series: &Series, offset: Option<i32>, fill: Option<FillPolicy>, name: String) -> CommandResult<Series> {
+    let series = shifted(series.clone(), get_offset(offset))?;
+    let series = fill_missing(series, fill)?;
+    Ok(series.with_name(name.into()))
+}
+
+
+/// The value of a parameter of a spec, `Unset` for the indicator default.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ParameterValue {
+    Unset,
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    Text(String),
+    Fill(FillPolicy),
+    Patterns(Vec<CandlePattern>),
+    Settings(Box<CandleSettings>),
+}
+
+
+impl From<Option<i32>> for ParameterValue {
+    fn from(value: Option<i32>) -> Self {
+        value.map_or(ParameterValue::Unset, |value| ParameterValue::Integer(value as i64))
+    }
+}
+
+
+impl From<Option<f64>> for ParameterValue {
+    fn from(value: Option<f64>) -> Self {
+        value.map_or(ParameterValue::Unset, ParameterValue::Float)
+    }
+}
+
+
+impl From<bool> for ParameterValue {
+    fn from(value: bool) -> Self {
+        ParameterValue::Boolean(value)
+    }
+}
+
+
+impl From<Option<String>> for ParameterValue {
+    fn from(value: Option<String>) -> Self {
+        value.map_or(ParameterValue::Unset, ParameterValue::Text)
+    }
+}
+
+
+impl From<Option<FillPolicy>> for ParameterValue {
+    fn from(value: Option<FillPolicy>) -> Self {
+        value.map_or(ParameterValue::Unset, ParameterValue::Fill)
+    }
+}
+
+
+impl IndicatorSpec {
+    /// The input columns the indicator reads.
+    pub fn inputs(&self) -> &'static [Input] {
+        use IndicatorSpec::*;
+        match self {
+            Sma(_) | Ema(_) | Dema(_) | Rma(_) | Linreg(_) | Slope { .. } | Rsi(_)
+            | Zscore { .. } | Stdev { .. } | Variance { .. } | Skew { .. } | Kurtosis { .. }
+            | Quantile { .. } | Median { .. } | Mad { .. } | Entropy { .. } | LogReturn { .. }
+            | PercentReturn { .. } | Drawdown { .. } => &CLOSE,
+            TrueRange { .. } | Atr(_) => &HLC,
+            Obv { .. } | Nvi { .. } | Pvi { .. } | Aobv(_) => &CLOSE_VOLUME,
+            Ha { .. } | CdlDoji(_) | CdlInside { .. } | CdlZ(_) | CdlPattern { .. } => &OHLC,
+        }
+    }
+
+    /// The `kind` of the spec, its snake case variant name.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            IndicatorSpec::Sma(_) => "sma",
+            IndicatorSpec::Ema(_) => "ema",
+            IndicatorSpec::Dema(_) => "dema",
+            IndicatorSpec::Rma(_) => "rma",
+            IndicatorSpec::Linreg(_) => "linreg",
+            IndicatorSpec::Slope { .. } => "slope",
+            IndicatorSpec::Rsi(_) => "rsi",
+            IndicatorSpec::TrueRange { .. } => "true_range",
+            IndicatorSpec::Atr(_) => "atr",
+            IndicatorSpec::Obv { .. } => "obv",
+            IndicatorSpec::Nvi { .. } => "nvi",
+            IndicatorSpec::Pvi { .. } => "pvi",
+            IndicatorSpec::Aobv(_) => "aobv",
+            IndicatorSpec::Zscore { .. } => "zscore",
+            IndicatorSpec::Stdev { .. } => "stdev",
+            IndicatorSpec::Variance { .. } => "variance",
+            IndicatorSpec::Skew { .. } => "skew",
+            IndicatorSpec::Kurtosis { .. } => "kurtosis",
+            IndicatorSpec::Quantile { .. } => "quantile",
+            IndicatorSpec::Median { .. } => "median",
+            IndicatorSpec::Mad { .. } => "mad",
+            IndicatorSpec::Entropy { .. } => "entropy",
+            IndicatorSpec::LogReturn { .. } => "log_return",
+            IndicatorSpec::PercentReturn { .. } => "percent_return",
+            IndicatorSpec::Drawdown { .. } => "drawdown",
+            IndicatorSpec::Ha { .. } => "ha",
+            IndicatorSpec::CdlDoji(_) => "cdl_doji",
+            IndicatorSpec::CdlInside { .. } => "cdl_inside",
+            IndicatorSpec::CdlZ(_) => "cdl_z",
+            IndicatorSpec::CdlPattern { .. } => "cdl_pattern",
+        }
+    }
+
+    /// The parameters of the spec by name, in the order of the registry.
+    pub fn parameters(&self) -> Vec<(&'static str, ParameterValue)> {
+        use ParameterValue as V;
+        let window = |length: &Option<i32>, offset: &Option<i32>, fill: &Option<FillPolicy>| {
+            vec![("length", V::from(*length)), ("offset", V::from(*offset)), ("fill", V::from(*fill))]
+        };
+        let shift = |offset: &Option<i32>, fill: &Option<FillPolicy>| {
+            vec![("offset", V::from(*offset)), ("fill", V::from(*fill))]
+        };
+        let mut parameters = match self {
+            IndicatorSpec::Sma(o) => window(&o.length, &o.offset, &o.fill),
+            IndicatorSpec::Ema(o) => vec![
+                ("length", o.length.into()),
+                ("adjust", o.adjust.into()),
+                ("mamode", o.mamode.clone().into()),
+            ],
+            IndicatorSpec::Dema(o) => window(&o.length, &o.offset, &o.fill),
+            IndicatorSpec::Rma(o) => window(&o.length, &o.offset, &o.fill),
+            IndicatorSpec::Linreg(o) => vec![
+                ("length", o.length.into()),
+                ("angle", o.angle.into()),
+                ("degrees", o.degrees.into()),
+                ("intercept", o.intercept.into()),
+                ("r", o.r.into()),
+                ("slope", o.slope.into()),
+                ("tsf", o.tsf.into()),
+            ],
+            IndicatorSpec::Slope { length, as_angle, to_degrees, .. } => vec![
+                ("length", (*length).into()),
+                ("as_angle", (*as_angle).into()),
+                ("to_degrees", (*to_degrees).into()),
+            ],
+            IndicatorSpec::Rsi(o) => vec![
+                ("length", o.length.into()),
+                ("scalar", o.scalar.into()),
+                ("drift", o.drift.into()),
+            ],
+            IndicatorSpec::TrueRange { drift, .. } => vec![("drift", (*drift).into())],
+            IndicatorSpec::Atr(o) => vec![
+                ("length", o.length.into()),
+                ("mamode", o.mamode.clone().into()),
+                ("drift", o.drift.into()),
+                ("percent", o.percent.into()),
+            ],
+            IndicatorSpec::Obv { .. } | IndicatorSpec::Drawdown { .. } | IndicatorSpec::Ha { .. } => vec![],
+            IndicatorSpec::Nvi { length, initial, .. } | IndicatorSpec::Pvi { length, initial, .. } => vec![
+                ("length", (*length).into()),
+                ("initial", (*initial).into()),
+            ],
+            IndicatorSpec::Aobv(o) => vec![
+                ("fast", o.fast.into()),
+                ("slow", o.slow.into()),
+                ("max_lookback", o.max_lookback.into()),
+                ("min_lookback", o.min_lookback.into()),
+                ("mamode", o.mamode.clone().into()),
+                ("run_length", o.run_length.into()),
+            ],
+            IndicatorSpec::Zscore { length, std, .. } => vec![("length", (*length).into()), ("std", (*std).into())],
+            IndicatorSpec::Stdev { length, ddof, .. } | IndicatorSpec::Variance { length, ddof, .. } => {
+                vec![("length", (*length).into()), ("ddof", (*ddof).into())]
+            },
+            IndicatorSpec::Skew { length, .. }
+            | IndicatorSpec::Kurtosis { length, .. }
+            | IndicatorSpec::Median { length, .. }
+            | IndicatorSpec::Mad { length, .. } => vec![("length", (*length).into())],
+            IndicatorSpec::Quantile { length, q, .. } => vec![("length", (*length).into()), ("q", (*q).into())],
+            IndicatorSpec::Entropy { length, base, .. } => {
+                vec![("length", (*length).into()), ("base", (*base).into())]
+            },
+            IndicatorSpec::LogReturn { length, cumulative, .. }
+            | IndicatorSpec::PercentReturn { length, cumulative, .. } => {
+                vec![("length", (*length).into()), ("cumulative", (*cumulative).into())]
+            },
+            IndicatorSpec::CdlDoji(o) => vec![
+                ("length", o.length.into()),
+                ("factor", o.factor.into()),
+                ("scalar", o.scalar.into()),
+                ("naive", o.naive.into()),
+            ],
+            IndicatorSpec::CdlInside { asbool, .. } => vec![("asbool", (*asbool).into())],
+            IndicatorSpec::CdlZ(o) => vec![
+                ("length", o.length.into()),
+                ("full", o.full.into()),
+                ("ddof", o.ddof.into()),
+            ],
+            IndicatorSpec::CdlPattern { names, options } => vec![
+                ("names", V::Patterns(names.clone())),
+                ("settings", V::Settings(Box::new(options.settings))),
+                ("penetration", options.penetration.into()),
+            ],
+        };
+        // every indicator is shifted and filled the same way, the windowed
+        // ones already have their offset and fill
+        if let IndicatorSpec::Sma(_) | IndicatorSpec::Dema(_) | IndicatorSpec::Rma(_) = self {
+            return parameters;
+        }
+        let (offset, fill) = self.shift_and_fill();
+        parameters.extend(shift(&offset, &fill));
+        parameters
+    }
+
+    /// The offset and fill of the outputs.
+    fn shift_and_fill(&self) -> (Option<i32>, Option<FillPolicy>) {
+        match self {
+            IndicatorSpec::Sma(o) => (o.offset, o.fill),
+            IndicatorSpec::Ema(o) => (o.offset, o.fill),
+            IndicatorSpec::Dema(o) => (o.offset, o.fill),
+            IndicatorSpec::Rma(o) => (o.offset, o.fill),
+            IndicatorSpec::Linreg(o) => (o.offset, o.fill),
+            IndicatorSpec::Rsi(o) => (o.offset, o.fill),
+            IndicatorSpec::Atr(o) => (o.offset, o.fill),
+            IndicatorSpec::Aobv(o) => (o.offset, o.fill),
+            IndicatorSpec::CdlDoji(o) => (o.offset, o.fill),
+            IndicatorSpec::CdlZ(o) => (o.offset, o.fill),
+            IndicatorSpec::CdlPattern { options, .. } => (options.offset, options.fill),
+            IndicatorSpec::Slope { offset, fill, .. }
+            | IndicatorSpec::TrueRange { offset, fill, .. }
+            | IndicatorSpec::Obv { offset, fill }
+            | IndicatorSpec::Nvi { offset, fill, .. }
+            | IndicatorSpec::Pvi { offset, fill, .. }
+            | IndicatorSpec::Zscore { offset, fill, .. }
+            | IndicatorSpec::Stdev { offset, fill, .. }
+            | IndicatorSpec::Variance { offset, fill, .. }
+            | IndicatorSpec::Skew { offset, fill, .. }
+            | IndicatorSpec::Kurtosis { offset, fill, .. }
+            | IndicatorSpec::Quantile { offset, fill, .. }
+            | IndicatorSpec::Median { offset, fill, .. }
+            | IndicatorSpec::Mad { offset, fill, .. }
+            | IndicatorSpec::Entropy { offset, fill, .. }
+            | IndicatorSpec::LogReturn { offset, fill, .. }
+            | IndicatorSpec::PercentReturn { offset, fill, .. }
+            | IndicatorSpec::Drawdown { offset, fill }
+            | IndicatorSpec::Ha { offset, fill }
+            | IndicatorSpec::CdlInside { offset, fill, .. } => (*offset, *fill),
+        }
+    }
+
+    /// Leading rows of the output without a converged value, like TA-Lib's
+    /// `TA_*_Lookback`, shifted by the offset: the rows to drop so no output
+    /// column has a missing or unstable value. Invalid parameters count with
+    /// the defaults they are replaced by when lenient.
+    pub fn lookback(&self) -> usize {
+        use validate::resolved;
+        let (lookback, offset) = match self {
+            IndicatorSpec::Sma(o) => return o.lookback(),
+            IndicatorSpec::Ema(o) => return o.lookback(),
+            IndicatorSpec::Dema(o) => return o.lookback(),
+            IndicatorSpec::Rma(o) => return o.lookback(),
+            IndicatorSpec::Linreg(o) => return o.lookback(),
+            IndicatorSpec::Rsi(o) => return o.lookback(),
+            IndicatorSpec::Atr(o) => return o.lookback(),
+            IndicatorSpec::Aobv(o) => return o.lookback(),
+            IndicatorSpec::CdlDoji(o) => return o.lookback(),
+            IndicatorSpec::CdlZ(o) => return o.lookback(),
+            IndicatorSpec::Slope { length, offset, .. } => (resolved(*length, 2, 14) - 1, offset),
+            IndicatorSpec::TrueRange { drift, offset, .. } => (resolved(*drift, 1, 1), offset),
+            // cumulative from the first bar or computed bar by bar
+            IndicatorSpec::Obv { offset, .. }
+            | IndicatorSpec::Nvi { offset, .. }
+            | IndicatorSpec::Pvi { offset, .. }
+            | IndicatorSpec::Drawdown { offset, .. }
+            | IndicatorSpec::Ha { offset, .. } => (0, offset),
+            IndicatorSpec::Zscore { length, offset, .. }
+            | IndicatorSpec::Stdev { length, offset, .. }
+            | IndicatorSpec::Variance { length, offset, .. } => (resolved(*length, 2, 30) - 1, offset),
+            IndicatorSpec::Skew { length, offset, .. } => (resolved(*length, 3, 30) - 1, offset),
+            IndicatorSpec::Kurtosis { length, offset, .. } => (resolved(*length, 4, 30) - 1, offset),
+            IndicatorSpec::Quantile { length, offset, .. }
+            | IndicatorSpec::Median { length, offset, .. }
+            | IndicatorSpec::Mad { length, offset, .. } => (resolved(*length, 1, 30) - 1, offset),
+            // the probabilities and their entropy are two windows of `length`
+            IndicatorSpec::Entropy { length, offset, .. } => (2 * (resolved(*length, 1, 10) - 1), offset),
+            IndicatorSpec::LogReturn { length, cumulative, offset, .. }
+            | IndicatorSpec::PercentReturn { length, cumulative, offset, .. } => {
+                (if *cumulative { 0 } else { resolved(*length, 1, 1) }, offset)
+            },
+            IndicatorSpec::CdlInside { offset, .. } => (1, offset),
+            IndicatorSpec::CdlPattern { names, options } => {
+                let names = if names.is_empty() { &CandlePattern::ALL[..] } else { names };
+                let lookback = names.iter().map(|pattern| pattern.lookback(&options.settings)).max();
+                (lookback.unwrap_or(0), &options.offset)
+            },
+        };
+        shifted_lookback(lookback, *offset)
+    }
+
+    /// Trailing rows of the output a negative offset shifts the values out
+    /// of, left without a value.
+    pub fn trailing(&self) -> usize {
+        get_offset(self.shift_and_fill().0).min(0).unsigned_abs() as usize
+    }
+
+    /// The intermediates the indicator is computed from, shared with the
+    /// other indicators of a batch.
+    pub(crate) fn intermediates(&self) -> Vec<Intermediate> {
+        match self {
+            IndicatorSpec::Ema(options) => vec![Intermediate::Ema {
+                length: options.length,
+                adjust: options.adjust,
+                sma: options.seeded(),
+            }],
+            IndicatorSpec::Dema(options) => vec![Intermediate::Ema {
+                length: options.length,
+                adjust: false,
+                sma: true,
+            }],
+            IndicatorSpec::TrueRange { drift, .. } => vec![Intermediate::TrueRange { drift: *drift }],
+            IndicatorSpec::Atr(options) => vec![Intermediate::TrueRange { drift: options.drift }],
+            _ => vec![],
+        }
+    }
+
+    /// The output columns, from `bars` and the `shared` intermediates.
+    pub(crate) fn compute(
+        &self,
+        bars: &Bars,
+        shared: &HashMap<Intermediate, Series>
+    ) -> CommandResult<Vec<Column>> {
+        let intermediate = |index: usize| &shared[&self.intermediates()[index]];
+        let inputs = self.inputs()
+            .iter()
+            .map(|input| bars.get(*input))
+            .collect::<CommandResult<Vec<&Series>>>()?;
+        let s = inputs.as_slice();
+        let series = match self {
+            IndicatorSpec::Sma(options) => sma(s[0], options.clone())?,
+            IndicatorSpec::Ema(options) => finish(intermediate(0), options.offset, options.fill, options.name())?,
+            IndicatorSpec::Dema(options) => dema_from_ema(intermediate(0), options.clone())?,
+            IndicatorSpec::Rma(options) => rma(s[0], options.clone())?,
+            IndicatorSpec::Linreg(options) => linreg(s[0], options.clone())?,
+            IndicatorSpec::Slope { length, as_angle, to_degrees, offset, fill } => {
+                slope(s[0], *length, *as_angle, *to_degrees, *offset, *fill)?
+            },
+            IndicatorSpec::Rsi(options) => rsi(s[0], options.clone())?,
+            IndicatorSpec::TrueRange { offset, fill, .. } => {
+                let tr = intermediate(0);
+                finish(tr, *offset, *fill, tr.name().to_string())?
+            },
+            IndicatorSpec::Atr(options) => atr_from_true_range(intermediate(0), s[2], options.clone())?,
+            IndicatorSpec::Obv { offset, fill } => obv(s[0], s[1], *offset, *fill)?,
+            IndicatorSpec::Nvi { length, initial, offset, fill } => {
+                nvi(s[0], s[1], *length, *initial, *offset, *fill)?
+            },
+            IndicatorSpec::Pvi { length, initial, offset, fill } => {
+                pvi(s[0], s[1], *length, *initial, *offset, *fill)?
+            },
+            IndicatorSpec::Aobv(options) => return Ok(aobv(s[0], s[1], options.clone())?.take_columns()),
+            IndicatorSpec::Zscore { length, std, offset, fill } => zscore(s[0], *length, *std, *offset, *fill)?,
+            IndicatorSpec::Stdev { length, ddof, offset, fill } => stdev(s[0], *length, *ddof, *offset, *fill)?,
+            IndicatorSpec::Variance { length, ddof, offset, fill } => {
+                variance(s[0], *length, *ddof, *offset, *fill)?
+            },
+            IndicatorSpec::Skew { length, offset, fill } => skew(s[0], *length, *offset, *fill)?,
+            IndicatorSpec::Kurtosis { length, offset, fill } => kurtosis(s[0], *length, *offset, *fill)?,
+            IndicatorSpec::Quantile { length, q, offset, fill } => quantile(s[0], *length, *q, *offset, *fill)?,
+            IndicatorSpec::Median { length, offset, fill } => median(s[0], *length, *offset, *fill)?,
+            IndicatorSpec::Mad { length, offset, fill } => mad(s[0], *length, *offset, *fill)?,
+            IndicatorSpec::Entropy { length, base, offset, fill } => entropy(s[0], *length, *base, *offset, *fill)?,
+            IndicatorSpec::LogReturn { length, cumulative, offset, fill } => {
+                log_return(s[0], *length, *cumulative, *offset, *fill)?
+            },
+            IndicatorSpec::PercentReturn { length, cumulative, offset, fill } => {
+                percent_return(s[0], *length, *cumulative, *offset, *fill)?
+            },
+            IndicatorSpec::Drawdown { offset, fill } => return Ok(drawdown(s[0], *offset, *fill)?.take_columns()),
+            IndicatorSpec::Ha { offset, fill } => return Ok(ha(s[0], s[1], s[2], s[3], *offset, *fill)?.take_columns()),
+            IndicatorSpec::CdlDoji(options) => cdl_doji(s[0], s[1], s[2], s[3], options.clone())?,
+            IndicatorSpec::CdlInside { asbool, offset, fill } => {
+                cdl_inside(s[0], s[1], s[2], s[3], *asbool, *offset, *fill)?
+            },
+            IndicatorSpec::CdlZ(options) => return Ok(cdl_z(s[0], s[1], s[2], s[3], options.clone())?.take_columns()),
+            IndicatorSpec::CdlPattern { names, options } => {
+                return Ok(cdl_pattern_with(s[0], s[1], s[2], s[3], names, (**options).clone())?.take_columns())
+            },
+        };
+        Ok(vec![series.into_column()])
+    }
+}
+
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_spec_serde() {
+        let specs = [
+            IndicatorSpec::Ema(EmaOptions {
+                length: Some(21),
+                adjust: true,
+                mamode: Some("ema".to_string()),
+                offset: Some(-1),
+                fill: Some(FillPolicy::Value(0.5)),
+            }),
+            IndicatorSpec::Slope { length: Some(5), as_angle: true, to_degrees: true, offset: None, fill: Some(FillPolicy::Forward) },
+            IndicatorSpec::Atr(AtrOptions { mamode: Some("sma".to_string()), percent: true, ..Default::default() }),
+            IndicatorSpec::CdlPattern {
+                names: vec![CandlePattern::Doji, CandlePattern::Engulfing],
+                options: Box::new(CdlPatternOptions { penetration: Some(0.5), ..Default::default() }),
+            },
+        ];
+        let defaults = crate::registry::indicators().iter().map(|indicator| indicator.spec.clone());
+        for spec in specs.into_iter().chain(defaults) {
+            let json = serde_json::to_string(&spec).unwrap();
+            assert_eq!(serde_json::from_str::<IndicatorSpec>(&json).unwrap(), spec, "{}", json);
+            let yaml = serde_yaml::to_string(&spec).unwrap();
+            assert_eq!(serde_yaml::from_str::<IndicatorSpec>(&yaml).unwrap(), spec, "{}", yaml);
+        }
+
+        // and outside a spec, as the options of the indicator function
+        let options = SmaOptions { fill: Some(FillPolicy::Value(0.5)), ..Default::default() };
+        let yaml = serde_yaml::to_string(&options).unwrap();
+        assert_eq!(serde_yaml::from_str::<SmaOptions>(&yaml).unwrap(), options);
+    }
+
+    #[test]
+    fn test_spec_serde_defaults() {
+        // fields left out take the defaults of the options or the arguments
+        let specs: Vec<IndicatorSpec> = serde_json::from_str(r#"[
+            {"kind": "sma"},
+            {"kind": "ema", "length": 20},
+            {"kind": "log_return", "cumulative": true},
+            {"kind": "cdl_inside"},
+            {"kind": "cdl_pattern", "names": ["Doji"], "options": {"penetration": 0.3}}
+        ]"#).unwrap();
+        assert_eq!(specs, [
+            IndicatorSpec::Sma(SmaOptions::default()),
+            IndicatorSpec::Ema(EmaOptions { length: Some(20), ..Default::default() }),
+            IndicatorSpec::LogReturn { length: None, cumulative: true, offset: None, fill: None },
+            IndicatorSpec::CdlInside { asbool: false, offset: None, fill: None },
+            IndicatorSpec::CdlPattern {
+                names: vec![CandlePattern::Doji],
+                options: Box::new(CdlPatternOptions { penetration: Some(0.3), ..Default::default() }),
+            },
+        ]);
+        assert!(serde_json::from_str::<IndicatorSpec>(r#"{"kind": "wma"}"#).is_err());
+    }
+}
@@ -0,0 +1,153 @@
+use crate::batch::IndicatorSpec;
+use crate::overlap::sma::SmaOptions;
+use crate::registry;
+use crate::utils::error::CommandResult;
+
+
+/// A named set of indicators applied in one call, like pandas-ta's
+/// `Strategy`: `df.ta().append(true).strategy(&Strategy::common())`. With the
+/// `serde` feature it can be versioned as JSON or YAML next to the models it
+/// feeds.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Strategy {
+    pub name: String,
+    pub ta: Vec<IndicatorSpec>,
+}
+
+
+impl Strategy {
+    pub fn new(name: &str, ta: Vec<IndicatorSpec>) -> Self {
+        Strategy { name: name.to_string(), ta }
+    }
+
+    /// pandas-ta's `CommonStrategy`: the 10, 20, 50 and 200 bar SMAs of close.
+    /// Its SMA of volume is left out, specs always read their own inputs.
+    pub fn common() -> Self {
+        let sma = |length| IndicatorSpec::Sma(SmaOptions { length: Some(length), ..Default::default() });
+        Strategy::new("Common Price SMAs", vec![sma(10), sma(20), sma(50), sma(200)])
+    }
+
+    /// pandas-ta's `AllStrategy`: every indicator with its default parameters.
+    pub fn all() -> Self {
+        Strategy::new("All", registry::indicators().iter().map(|indicator| indicator.spec.clone()).collect())
+    }
+
+    /// The rows before every indicator has a converged value, see
+    /// `batch::trim` and `batch::mask`.
+    pub fn lookback(&self) -> usize {
+        crate::batch::lookback(&self.ta)
+    }
+
+    /// Checks the parameters of every spec, see `registry::check`.
+    pub fn check(&self) -> CommandResult<()> {
+        self.ta.iter().try_for_each(registry::check)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::accessor::TaExt;
+    use crate::utils::data_loader::{
+        csv_to_dataframe,
+        set_column_names,
+        combine_date_time,
+        convert_to_naive_datetime
+    };
+    use polars::prelude::*;
+    use super::*;
+
+    fn load() -> DataFrame {
+        let mut df = csv_to_dataframe(
+            "data/AUDNZD1.csv",
+            false
+        ).unwrap();
+        let _ = set_column_names(
+            &mut df,
+            vec!["date", "time", "open", "high", "low", "close", "volume"]
+        );
+        let df = combine_date_time(&df).unwrap();
+        convert_to_naive_datetime(
+            &df,
+            "%Y.%m.%d %H:%M"
+        ).unwrap()
+    }
+
+    #[test]
+    fn test_strategy() {
+        let mut df = load().slice(0, 500);
+        let width = df.width();
+        let common = df.ta().append(true).strategy(&Strategy::common()).unwrap();
+        let names: Vec<&str> = common.get_column_names().into_iter().map(|name| name.as_str()).collect();
+        assert_eq!(names, ["SMA_10", "SMA_20", "SMA_50", "SMA_200"]);
+        assert_eq!(df.width(), width + 4);
+
+        let all = df.ta().strategy(&Strategy::all()).unwrap();
+        assert_eq!(all.height(), 500);
+        for name in ["EMA_10", "DEMA_10", "ATRr_14", "OBV", "HA_open", "CDL_DOJI_10_0.1"] {
+            assert!(all.column(name).is_ok(), "{} is missing", name);
+        }
+
+        assert_eq!(Strategy::common().lookback(), 199);
+        assert!(Strategy::all().check().is_ok());
+        let mut invalid = Strategy::common();
+        invalid.ta.push(IndicatorSpec::Sma(SmaOptions { length: Some(0), ..Default::default() }));
+        assert!(invalid.check().is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_strategy_serde() {
+        let mut df = load().slice(0, 500);
+        let strategy = Strategy::all();
+        let saved = serde_json::to_string(&strategy).unwrap();
+        assert!(saved.contains(r#"{"kind":"true_range"}"#));
+
+        let restored: Strategy = serde_json::from_str(&saved).unwrap();
+        assert_eq!(restored.name, strategy.name);
+        assert_eq!(serde_json::to_string(&restored).unwrap(), saved);
+        let expected = df.ta().strategy(&strategy).unwrap();
+        let applied = df.ta().strategy(&restored).unwrap();
+        assert!(applied.equals_missing(&expected));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_strategy_toml() {
+        // TOML has no null, the unset fields must be left out
+        let strategy = Strategy::all();
+        let saved = toml::to_string(&strategy).unwrap();
+        let restored: Strategy = toml::from_str(&saved).unwrap();
+        assert_eq!(restored, strategy, "{}", saved);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_strategy_yaml() {
+        use crate::momentum::rsi::RsiOptions;
+        use crate::overlap::ema::EmaOptions;
+        use crate::utils::core::FillPolicy;
+        use crate::volatility::atr::AtrOptions;
+
+        let strategy: Strategy = serde_yaml::from_str("
+name: Momentum
+ta:
+  - kind: ema
+    length: 21
+  - kind: rsi
+    fill: Forward
+  - kind: atr
+    mamode: ema
+    percent: true
+").unwrap();
+        assert_eq!(strategy, Strategy::new("Momentum", vec![
+            IndicatorSpec::Ema(EmaOptions { length: Some(21), ..Default::default() }),
+            IndicatorSpec::Rsi(RsiOptions { fill: Some(FillPolicy::Forward), ..Default::default() }),
+            IndicatorSpec::Atr(AtrOptions { mamode: Some("ema".to_string()), percent: true, ..Default::default() }),
+        ]));
+        let saved = serde_yaml::to_string(&strategy).unwrap();
+        assert_eq!(serde_yaml::from_str::<Strategy>(&saved).unwrap(), strategy);
+    }
+}
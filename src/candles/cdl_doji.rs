@@ -0,0 +1,164 @@
+use crate::overlap::sma::{sma, SmaOptions};
+use crate::utils::core::{get_offset, shifted_lookback, non_zero_range, fill_missing, FillPolicy};
+use crate::utils::missing::prepare;
+use crate::utils::error::CommandResult;
+use crate::utils::validate;
+use polars::prelude::*;
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct DojiOptions {
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub length: Option<i32>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub factor: Option<f64>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub scalar: Option<i32>,
+    pub naive: bool,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub offset: Option<i32>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub fill: Option<FillPolicy>,
+}
+
+
+impl Default for DojiOptions {
+    fn default() -> Self {
+        DojiOptions {
+            length: Some(10),
+            factor: Some(10.0),
+            scalar: Some(100),
+            naive: false,
+            offset: None,
+            fill: None,
+        }
+    }
+}
+
+
+impl DojiOptions {
+    /// The output name, e.g. `CDL_DOJI_10_0.1`.
+    pub fn name(&self) -> String {
+        let length = match self.length {
+            Some(length) if length > 0 => length,
+            _ => 10
+        };
+        let factor = match self.factor {
+            Some(factor) if factor > 0.0 => factor,
+            _ => 10.0
+        };
+        format!("CDL_DOJI_{}_{}", length, 0.01 * factor)
+    }
+
+    /// Leading bars without an average range over `length` bars, shifted
+    /// by the offset. They are 0, or tested naively with `naive`.
+    pub fn lookback(&self) -> usize {
+        shifted_lookback(validate::resolved(self.length, 1, 10) - 1, self.offset)
+    }
+}
+
+
+/// Doji: the real body is smaller than `factor` percent of the average
+/// high-low range of the last `length` bars. Matches return `scalar`, other
+/// bars 0. The first `length - 1` bars have no average range and are only
+/// tested against their own range when `naive` is set.
+pub fn cdl_doji(
+    open: &Series,
+    high: &Series,
+    low: &Series,
+    close: &Series,
+    options: DojiOptions
+) -> CommandResult<Series> {
+    let prepared = prepare(&[open, high, low, close])?;
+    let (open, high, low, close) = (&prepared.inputs[0], &prepared.inputs[1], &prepared.inputs[2], &prepared.inputs[3]);
+    let length = validate::length("length", options.length, 1, 10)?;
+    let factor = validate::positive("factor", options.factor, 10.0)?;
+    validate::min_rows(close.len(), length as usize)?;
+    let scalar = options.scalar.unwrap_or(100);
+    let offset = get_offset(options.offset);
+
+    let open = open.cast(&DataType::Float64)?;
+    let close = close.cast(&DataType::Float64)?;
+    let high = high.cast(&DataType::Float64)?;
+    let low = low.cast(&DataType::Float64)?;
+
+    // like pandas-ta, a flat body is epsilon, which a bar without any
+    // range does not beat
+    let body = non_zero_range(&close, &open)?;
+    let hl_range = non_zero_range(&high, &low)?;
+    let hl_range_avg = sma(&hl_range, SmaOptions {
+        length: Some(length),
+        ..Default::default()
+    })?;
+
+    let threshold = 0.01 * factor;
+    let doji: Series = body
+        .f64()?
+        .into_iter()
+        .zip(hl_range.f64()?)
+        .zip(hl_range_avg.f64()?)
+        .map(|((body, range), range_avg)| {
+            let reference = match range_avg {
+                Some(range_avg) => Some(range_avg),
+                None if options.naive => range,
+                None => None
+            };
+            match (body, reference) {
+                (Some(body), Some(reference)) if body.abs() < threshold * reference.abs() => scalar,
+                _ => 0
+            }
+        })
+        .collect();
+
+    let doji = prepared.restore(doji, offset)?;
+
+    let doji = fill_missing(doji, options.fill)?;
+
+    Ok(doji.with_name(options.name().into()))
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::utils::data_loader::{
+        csv_to_dataframe, 
+        set_column_names, 
+        combine_date_time, 
+        convert_to_naive_datetime
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_cdl_doji() {
+        let mut df = csv_to_dataframe(
+            "data/AUDNZD1.csv", 
+            false
+        ).unwrap();
+        let _ = set_column_names(
+            &mut df, 
+            vec!["date", "time", "open", "high", "low", "close", "volume"]
+        );
+        let df = combine_date_time(&df).unwrap();
+        let df = convert_to_naive_datetime(
+            &df, 
+            "%Y.%m.%d %H:%M"
+        ).unwrap();
+
+        let open = df.column("open").unwrap().as_series().unwrap();
+        let high = df.column("high").unwrap().as_series().unwrap();
+        let low = df.column("low").unwrap().as_series().unwrap();
+        let close = df.column("close").unwrap().as_series().unwrap();
+        let doji = cdl_doji(open, high, low, close, DojiOptions {
+            ..Default::default()
+        }).unwrap();
+        assert_eq!(doji.name().as_str(), "CDL_DOJI_10_0.1");
+        let doji = doji.i32().unwrap();
+        assert!(doji.into_no_null_iter().all(|v| v == 0 || v == 100));
+        assert_eq!(doji.get(0), Some(0));
+        assert!(doji.into_no_null_iter().any(|v| v == 100));
+    }
+}
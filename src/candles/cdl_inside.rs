@@ -0,0 +1,111 @@
+use crate::utils::core::{get_offset, fill_missing, FillPolicy};
+use crate::utils::missing::prepare;
+use crate::utils::error::CommandResult;
+use polars::prelude::*;
+
+
+/// Inside bar: a lower high and a higher low than the previous bar. Unless
+/// `asbool` is set the match is signed with the candle colour, 1 for a white
+/// candle (close >= open) and -1 for a black one.
+pub fn cdl_inside(
+    open: &Series,
+    high: &Series,
+    low: &Series,
+    close: &Series,
+    asbool: bool,
+    offset: Option<i32>,
+    fill: Option<FillPolicy>
+) -> CommandResult<Series> {
+    let prepared = prepare(&[open, high, low, close])?;
+    let (open, high, low, close) = (&prepared.inputs[0], &prepared.inputs[1], &prepared.inputs[2], &prepared.inputs[3]);
+    let offset = get_offset(offset);
+
+    let open = open.cast(&DataType::Float64)?;
+    let high = high.cast(&DataType::Float64)?;
+    let low = low.cast(&DataType::Float64)?;
+    let close = close.cast(&DataType::Float64)?;
+
+    let high: Vec<Option<f64>> = high.f64()?.into_iter().collect();
+    let low: Vec<Option<f64>> = low.f64()?.into_iter().collect();
+
+    let inside: Vec<bool> = (0..high.len())
+        .map(|i| {
+            if i == 0 {
+                return false;
+            }
+            match (high[i - 1], high[i], low[i - 1], low[i]) {
+                (Some(prev_high), Some(high), Some(prev_low), Some(low)) => {
+                    high < prev_high && low > prev_low
+                },
+                _ => false
+            }
+        })
+        .collect();
+
+    let inside: Series = if asbool {
+        inside.into_iter().collect()
+    } else {
+        inside
+            .into_iter()
+            .zip(open.f64()?)
+            .zip(close.f64()?)
+            .map(|((inside, open), close)| {
+                let color = match (open, close) {
+                    (Some(open), Some(close)) if close < open => -1,
+                    _ => 1
+                };
+                i32::from(inside) * color
+            })
+            .collect()
+    };
+
+    let inside = prepared.restore(inside, offset)?;
+
+    let inside = fill_missing(inside, fill)?;
+
+    Ok(inside.with_name("CDL_INSIDE".into()))
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::utils::data_loader::{
+        csv_to_dataframe, 
+        set_column_names, 
+        combine_date_time, 
+        convert_to_naive_datetime
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_cdl_inside() {
+        let mut df = csv_to_dataframe(
+            "data/AUDNZD1.csv", 
+            false
+        ).unwrap();
+        let _ = set_column_names(
+            &mut df, 
+            vec!["date", "time", "open", "high", "low", "close", "volume"]
+        );
+        let df = combine_date_time(&df).unwrap();
+        let df = convert_to_naive_datetime(
+            &df, 
+            "%Y.%m.%d %H:%M"
+        ).unwrap();
+
+        let open = df.column("open").unwrap().as_series().unwrap();
+        let high = df.column("high").unwrap().as_series().unwrap();
+        let low = df.column("low").unwrap().as_series().unwrap();
+        let close = df.column("close").unwrap().as_series().unwrap();
+        let inside = cdl_inside(open, high, low, close, false, None, None).unwrap();
+        assert_eq!(inside.name().as_str(), "CDL_INSIDE");
+        let inside = inside.i32().unwrap();
+        assert_eq!(inside.get(0), Some(0));
+        assert!(inside.into_no_null_iter().all(|v| (-1..=1).contains(&v)));
+
+        let inside = cdl_inside(open, high, low, close, true, None, None).unwrap();
+        assert_eq!(inside.dtype(), &DataType::Boolean);
+    }
+}
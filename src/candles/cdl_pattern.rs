@@ -0,0 +1,1318 @@
+use crate::candles::cdl_inside::cdl_inside;
+use crate::candles::settings::{CandleSetting, CandleSettings, RangeType};
+use crate::utils::core::{get_offset, fill_missing_frame, FillPolicy};
+use crate::utils::missing::prepare;
+use crate::utils::error::CommandResult;
+use polars::prelude::*;
+
+
+/// The TA-Lib candlestick patterns plus pandas-ta's inside bar.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CandlePattern {
+    TwoCrows,
+    ThreeBlackCrows,
+    ThreeInside,
+    ThreeLineStrike,
+    ThreeOutside,
+    ThreeStarsInSouth,
+    ThreeWhiteSoldiers,
+    AbandonedBaby,
+    AdvanceBlock,
+    BeltHold,
+    Breakaway,
+    ClosingMarubozu,
+    ConcealBabySwallow,
+    Counterattack,
+    DarkCloudCover,
+    Doji,
+    DojiStar,
+    DragonflyDoji,
+    Engulfing,
+    EveningDojiStar,
+    EveningStar,
+    GapSideSideWhite,
+    GravestoneDoji,
+    Hammer,
+    HangingMan,
+    Harami,
+    HaramiCross,
+    HighWave,
+    Hikkake,
+    HikkakeMod,
+    HomingPigeon,
+    IdenticalThreeCrows,
+    InNeck,
+    Inside,
+    InvertedHammer,
+    Kicking,
+    KickingByLength,
+    LadderBottom,
+    LongLeggedDoji,
+    LongLine,
+    Marubozu,
+    MatchingLow,
+    MatHold,
+    MorningDojiStar,
+    MorningStar,
+    OnNeck,
+    Piercing,
+    RickshawMan,
+    RiseFallThreeMethods,
+    SeparatingLines,
+    ShootingStar,
+    ShortLine,
+    SpinningTop,
+    StalledPattern,
+    StickSandwich,
+    Takuri,
+    TasukiGap,
+    Thrusting,
+    Tristar,
+    UniqueThreeRiver,
+    UpsideGapTwoCrows,
+    XSideGapThreeMethods,
+}
+
+
+impl CandlePattern {
+    pub const ALL: [CandlePattern; 62] = [
+        CandlePattern::TwoCrows,
+        CandlePattern::ThreeBlackCrows,
+        CandlePattern::ThreeInside,
+        CandlePattern::ThreeLineStrike,
+        CandlePattern::ThreeOutside,
+        CandlePattern::ThreeStarsInSouth,
+        CandlePattern::ThreeWhiteSoldiers,
+        CandlePattern::AbandonedBaby,
+        CandlePattern::AdvanceBlock,
+        CandlePattern::BeltHold,
+        CandlePattern::Breakaway,
+        CandlePattern::ClosingMarubozu,
+        CandlePattern::ConcealBabySwallow,
+        CandlePattern::Counterattack,
+        CandlePattern::DarkCloudCover,
+        CandlePattern::Doji,
+        CandlePattern::DojiStar,
+        CandlePattern::DragonflyDoji,
+        CandlePattern::Engulfing,
+        CandlePattern::EveningDojiStar,
+        CandlePattern::EveningStar,
+        CandlePattern::GapSideSideWhite,
+        CandlePattern::GravestoneDoji,
+        CandlePattern::Hammer,
+        CandlePattern::HangingMan,
+        CandlePattern::Harami,
+        CandlePattern::HaramiCross,
+        CandlePattern::HighWave,
+        CandlePattern::Hikkake,
+        CandlePattern::HikkakeMod,
+        CandlePattern::HomingPigeon,
+        CandlePattern::IdenticalThreeCrows,
+        CandlePattern::InNeck,
+        CandlePattern::Inside,
+        CandlePattern::InvertedHammer,
+        CandlePattern::Kicking,
+        CandlePattern::KickingByLength,
+        CandlePattern::LadderBottom,
+        CandlePattern::LongLeggedDoji,
+        CandlePattern::LongLine,
+        CandlePattern::Marubozu,
+        CandlePattern::MatchingLow,
+        CandlePattern::MatHold,
+        CandlePattern::MorningDojiStar,
+        CandlePattern::MorningStar,
+        CandlePattern::OnNeck,
+        CandlePattern::Piercing,
+        CandlePattern::RickshawMan,
+        CandlePattern::RiseFallThreeMethods,
+        CandlePattern::SeparatingLines,
+        CandlePattern::ShootingStar,
+        CandlePattern::ShortLine,
+        CandlePattern::SpinningTop,
+        CandlePattern::StalledPattern,
+        CandlePattern::StickSandwich,
+        CandlePattern::Takuri,
+        CandlePattern::TasukiGap,
+        CandlePattern::Thrusting,
+        CandlePattern::Tristar,
+        CandlePattern::UniqueThreeRiver,
+        CandlePattern::UpsideGapTwoCrows,
+        CandlePattern::XSideGapThreeMethods,
+    ];
+
+    /// The TA-Lib name without the `CDL` prefix, as used by pandas-ta.
+    pub fn name(&self) -> &'static str {
+        match self {
+            CandlePattern::TwoCrows => "2CROWS",
+            CandlePattern::ThreeBlackCrows => "3BLACKCROWS",
+            CandlePattern::ThreeInside => "3INSIDE",
+            CandlePattern::ThreeLineStrike => "3LINESTRIKE",
+            CandlePattern::ThreeOutside => "3OUTSIDE",
+            CandlePattern::ThreeStarsInSouth => "3STARSINSOUTH",
+            CandlePattern::ThreeWhiteSoldiers => "3WHITESOLDIERS",
+            CandlePattern::AbandonedBaby => "ABANDONEDBABY",
+            CandlePattern::AdvanceBlock => "ADVANCEBLOCK",
+            CandlePattern::BeltHold => "BELTHOLD",
+            CandlePattern::Breakaway => "BREAKAWAY",
+            CandlePattern::ClosingMarubozu => "CLOSINGMARUBOZU",
+            CandlePattern::ConcealBabySwallow => "CONCEALBABYSWALL",
+            CandlePattern::Counterattack => "COUNTERATTACK",
+            CandlePattern::DarkCloudCover => "DARKCLOUDCOVER",
+            CandlePattern::Doji => "DOJI",
+            CandlePattern::DojiStar => "DOJISTAR",
+            CandlePattern::DragonflyDoji => "DRAGONFLYDOJI",
+            CandlePattern::Engulfing => "ENGULFING",
+            CandlePattern::EveningDojiStar => "EVENINGDOJISTAR",
+            CandlePattern::EveningStar => "EVENINGSTAR",
+            CandlePattern::GapSideSideWhite => "GAPSIDESIDEWHITE",
+            CandlePattern::GravestoneDoji => "GRAVESTONEDOJI",
+            CandlePattern::Hammer => "HAMMER",
+            CandlePattern::HangingMan => "HANGINGMAN",
+            CandlePattern::Harami => "HARAMI",
+            CandlePattern::HaramiCross => "HARAMICROSS",
+            CandlePattern::HighWave => "HIGHWAVE",
+            CandlePattern::Hikkake => "HIKKAKE",
+            CandlePattern::HikkakeMod => "HIKKAKEMOD",
+            CandlePattern::HomingPigeon => "HOMINGPIGEON",
+            CandlePattern::IdenticalThreeCrows => "IDENTICAL3CROWS",
+            CandlePattern::InNeck => "INNECK",
+            CandlePattern::Inside => "INSIDE",
+            CandlePattern::InvertedHammer => "INVERTEDHAMMER",
+            CandlePattern::Kicking => "KICKING",
+            CandlePattern::KickingByLength => "KICKINGBYLENGTH",
+            CandlePattern::LadderBottom => "LADDERBOTTOM",
+            CandlePattern::LongLeggedDoji => "LONGLEGGEDDOJI",
+            CandlePattern::LongLine => "LONGLINE",
+            CandlePattern::Marubozu => "MARUBOZU",
+            CandlePattern::MatchingLow => "MATCHINGLOW",
+            CandlePattern::MatHold => "MATHOLD",
+            CandlePattern::MorningDojiStar => "MORNINGDOJISTAR",
+            CandlePattern::MorningStar => "MORNINGSTAR",
+            CandlePattern::OnNeck => "ONNECK",
+            CandlePattern::Piercing => "PIERCING",
+            CandlePattern::RickshawMan => "RICKSHAWMAN",
+            CandlePattern::RiseFallThreeMethods => "RISEFALL3METHODS",
+            CandlePattern::SeparatingLines => "SEPARATINGLINES",
+            CandlePattern::ShootingStar => "SHOOTINGSTAR",
+            CandlePattern::ShortLine => "SHORTLINE",
+            CandlePattern::SpinningTop => "SPINNINGTOP",
+            CandlePattern::StalledPattern => "STALLEDPATTERN",
+            CandlePattern::StickSandwich => "STICKSANDWICH",
+            CandlePattern::Takuri => "TAKURI",
+            CandlePattern::TasukiGap => "TASUKIGAP",
+            CandlePattern::Thrusting => "THRUSTING",
+            CandlePattern::Tristar => "TRISTAR",
+            CandlePattern::UniqueThreeRiver => "UNIQUE3RIVER",
+            CandlePattern::UpsideGapTwoCrows => "UPSIDEGAP2CROWS",
+            CandlePattern::XSideGapThreeMethods => "XSIDEGAP3METHODS",
+        }
+    }
+
+    /// Looks a pattern up by its pandas-ta name, ignoring case and an optional
+    /// `cdl_` prefix, e.g. "engulfing" or "CDL_3WHITESOLDIERS".
+    pub fn from_name(name: &str) -> Option<CandlePattern> {
+        let name = name.to_ascii_uppercase();
+        let name = name
+            .strip_prefix("CDL_")
+            .or_else(|| name.strip_prefix("CDL"))
+            .unwrap_or(&name);
+        CandlePattern::ALL
+            .iter()
+            .find(|pattern| pattern.name() == name)
+            .copied()
+    }
+
+    /// Number of leading bars without a result, like TA-Lib's `TA_CDL*_Lookback`.
+    pub fn lookback(&self, settings: &CandleSettings) -> usize {
+        let s = settings;
+        let max = |values: &[usize]| values.iter().copied().max().unwrap_or(0);
+        match self {
+            CandlePattern::TwoCrows => s.body_long.period + 2,
+            CandlePattern::ThreeBlackCrows => s.shadow_very_short.period + 3,
+            CandlePattern::ThreeInside => max(&[s.body_short.period, s.body_long.period]) + 2,
+            CandlePattern::ThreeLineStrike => s.near.period + 3,
+            CandlePattern::ThreeOutside => 3,
+            CandlePattern::ThreeStarsInSouth => max(&[
+                s.shadow_very_short.period, s.shadow_long.period, s.body_long.period, s.body_short.period
+            ]) + 2,
+            CandlePattern::ThreeWhiteSoldiers => max(&[
+                s.shadow_very_short.period, s.body_short.period, s.far.period, s.near.period
+            ]) + 2,
+            CandlePattern::AbandonedBaby
+            | CandlePattern::EveningDojiStar
+            | CandlePattern::MorningDojiStar => max(&[
+                s.body_doji.period, s.body_long.period, s.body_short.period
+            ]) + 2,
+            CandlePattern::AdvanceBlock => max(&[
+                s.shadow_long.period, s.shadow_short.period, s.far.period, s.near.period, s.body_long.period
+            ]) + 2,
+            CandlePattern::BeltHold
+            | CandlePattern::ClosingMarubozu
+            | CandlePattern::Marubozu => max(&[s.body_long.period, s.shadow_very_short.period]),
+            CandlePattern::Breakaway => s.body_long.period + 4,
+            CandlePattern::ConcealBabySwallow => s.shadow_very_short.period + 3,
+            CandlePattern::Counterattack
+            | CandlePattern::InNeck
+            | CandlePattern::OnNeck
+            | CandlePattern::Thrusting => max(&[s.equal.period, s.body_long.period]) + 1,
+            CandlePattern::DarkCloudCover | CandlePattern::Piercing => s.body_long.period + 1,
+            CandlePattern::Doji => s.body_doji.period,
+            CandlePattern::DojiStar | CandlePattern::HaramiCross => {
+                max(&[s.body_doji.period, s.body_long.period]) + 1
+            },
+            CandlePattern::DragonflyDoji | CandlePattern::GravestoneDoji => {
+                max(&[s.body_doji.period, s.shadow_very_short.period])
+            },
+            CandlePattern::Engulfing => 2,
+            CandlePattern::EveningStar
+            | CandlePattern::MorningStar
+            | CandlePattern::UniqueThreeRiver
+            | CandlePattern::UpsideGapTwoCrows => max(&[s.body_short.period, s.body_long.period]) + 2,
+            CandlePattern::GapSideSideWhite => max(&[s.near.period, s.equal.period]) + 2,
+            CandlePattern::Hammer | CandlePattern::HangingMan => max(&[
+                s.body_short.period, s.shadow_long.period, s.shadow_very_short.period, s.near.period
+            ]) + 1,
+            CandlePattern::Harami | CandlePattern::HomingPigeon => {
+                max(&[s.body_short.period, s.body_long.period]) + 1
+            },
+            CandlePattern::HighWave => max(&[s.body_short.period, s.shadow_very_long.period]),
+            CandlePattern::Hikkake => 5,
+            CandlePattern::HikkakeMod => max(&[1, s.near.period]) + 5,
+            CandlePattern::IdenticalThreeCrows => max(&[s.shadow_very_short.period, s.equal.period]) + 2,
+            CandlePattern::Inside => 1,
+            CandlePattern::InvertedHammer | CandlePattern::ShootingStar => max(&[
+                s.body_short.period, s.shadow_long.period, s.shadow_very_short.period
+            ]) + 1,
+            CandlePattern::Kicking | CandlePattern::KickingByLength => {
+                max(&[s.shadow_very_short.period, s.body_long.period]) + 1
+            },
+            CandlePattern::LadderBottom => s.shadow_very_short.period + 4,
+            CandlePattern::LongLeggedDoji => max(&[s.body_doji.period, s.shadow_long.period]),
+            CandlePattern::LongLine => max(&[s.body_long.period, s.shadow_short.period]),
+            CandlePattern::MatchingLow => s.equal.period + 1,
+            CandlePattern::MatHold | CandlePattern::RiseFallThreeMethods => {
+                max(&[s.body_short.period, s.body_long.period]) + 4
+            },
+            CandlePattern::RickshawMan => max(&[s.body_doji.period, s.shadow_long.period, s.near.period]),
+            CandlePattern::SeparatingLines => max(&[
+                s.shadow_very_short.period, s.body_long.period, s.equal.period
+            ]) + 1,
+            CandlePattern::ShortLine => max(&[s.body_short.period, s.shadow_short.period]),
+            CandlePattern::SpinningTop => s.body_short.period,
+            CandlePattern::StalledPattern => max(&[
+                s.body_long.period, s.body_short.period, s.shadow_very_short.period, s.near.period
+            ]) + 2,
+            CandlePattern::StickSandwich => s.equal.period + 2,
+            CandlePattern::Takuri => max(&[
+                s.body_doji.period, s.shadow_very_short.period, s.shadow_very_long.period
+            ]),
+            CandlePattern::TasukiGap => s.near.period + 2,
+            CandlePattern::Tristar => s.body_doji.period + 2,
+            CandlePattern::XSideGapThreeMethods => 2,
+        }
+    }
+
+    /// TA-Lib's default penetration for the patterns that take one.
+    fn default_penetration(&self) -> f64 {
+        match self {
+            CandlePattern::DarkCloudCover | CandlePattern::MatHold => 0.5,
+            _ => 0.3,
+        }
+    }
+}
+
+
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct CdlPatternOptions {
+    pub settings: CandleSettings,
+    /// Overrides the penetration of abandoned baby, dark cloud cover, evening
+    /// and morning (doji) star and mat hold.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub penetration: Option<f64>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub offset: Option<i32>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub fill: Option<FillPolicy>,
+}
+
+
+#[derive(Clone, Copy)]
+enum Setting {
+    BodyLong,
+    BodyVeryLong,
+    BodyShort,
+    BodyDoji,
+    ShadowLong,
+    ShadowVeryLong,
+    ShadowShort,
+    ShadowVeryShort,
+    Near,
+    Far,
+    Equal,
+}
+
+
+use Setting as S;
+
+
+/// OHLC as plain slices with nulls as NaN, so that every comparison against a
+/// missing price or average fails and the pattern is not matched.
+struct Candles<'a> {
+    open: Vec<f64>,
+    high: Vec<f64>,
+    low: Vec<f64>,
+    close: Vec<f64>,
+    settings: &'a CandleSettings,
+    /// Trailing mean of each (range, period) in use.
+    trailing: Vec<((RangeType, usize), Vec<f64>)>,
+}
+
+
+impl<'a> Candles<'a> {
+    fn new(
+        open: &Series,
+        high: &Series,
+        low: &Series,
+        close: &Series,
+        settings: &'a CandleSettings
+    ) -> CommandResult<Self> {
+        let values = |series: &Series| -> CommandResult<Vec<f64>> {
+            let series = series.cast(&DataType::Float64)?;
+            Ok(series.f64()?.into_iter().map(|v| v.unwrap_or(f64::NAN)).collect())
+        };
+        let mut candles = Candles {
+            open: values(open)?,
+            high: values(high)?,
+            low: values(low)?,
+            close: values(close)?,
+            settings,
+            trailing: Vec::new(),
+        };
+        let all = [
+            S::BodyLong, S::BodyVeryLong, S::BodyShort, S::BodyDoji, S::ShadowLong, S::ShadowVeryLong,
+            S::ShadowShort, S::ShadowVeryShort, S::Near, S::Far, S::Equal,
+        ];
+        for setting in all {
+            let setting = candles.setting(setting);
+            let key = (setting.range, setting.period);
+            if setting.period > 0 && !candles.trailing.iter().any(|(k, _)| *k == key) {
+                let mean = candles.trailing_mean(setting.range, setting.period);
+                candles.trailing.push((key, mean));
+            }
+        }
+        Ok(candles)
+    }
+
+    fn len(&self) -> usize {
+        self.close.len()
+    }
+
+    fn setting(&self, setting: Setting) -> CandleSetting {
+        let s = self.settings;
+        match setting {
+            S::BodyLong => s.body_long,
+            S::BodyVeryLong => s.body_very_long,
+            S::BodyShort => s.body_short,
+            S::BodyDoji => s.body_doji,
+            S::ShadowLong => s.shadow_long,
+            S::ShadowVeryLong => s.shadow_very_long,
+            S::ShadowShort => s.shadow_short,
+            S::ShadowVeryShort => s.shadow_very_short,
+            S::Near => s.near,
+            S::Far => s.far,
+            S::Equal => s.equal,
+        }
+    }
+
+    fn range(&self, range: RangeType, i: usize) -> f64 {
+        match range {
+            RangeType::RealBody => self.rb(i),
+            RangeType::HighLow => self.hl(i),
+            RangeType::Shadows => self.uss(i) + self.lss(i),
+        }
+    }
+
+    /// Mean of `range` over the `period` bars before each bar, kept as a
+    /// running total like TA-Lib and restarted after a missing bar.
+    fn trailing_mean(&self, range: RangeType, period: usize) -> Vec<f64> {
+        let mut mean = vec![f64::NAN; self.len()];
+        let mut total = 0.0;
+        let mut valid = 0;
+        for (i, mean) in mean.iter_mut().enumerate() {
+            if valid >= period {
+                *mean = total / period as f64;
+            }
+            let value = self.range(range, i);
+            if value.is_nan() {
+                total = 0.0;
+                valid = 0;
+                continue;
+            }
+            total += value;
+            valid += 1;
+            if valid > period {
+                total -= self.range(range, i - period);
+            }
+        }
+        mean
+    }
+
+    /// TA_CANDLEAVERAGE: the threshold of `setting` for the candle at `i`.
+    fn avg(&self, setting: Setting, i: usize) -> f64 {
+        let setting = self.setting(setting);
+        let base = if setting.period == 0 {
+            self.range(setting.range, i)
+        } else {
+            self.trailing
+                .iter()
+                .find(|(key, _)| *key == (setting.range, setting.period))
+                .map_or(f64::NAN, |(_, mean)| mean[i])
+        };
+        let divisor = if setting.range == RangeType::Shadows { 2.0 } else { 1.0 };
+        setting.factor * base / divisor
+    }
+
+    fn rb(&self, i: usize) -> f64 {
+        (self.close[i] - self.open[i]).abs()
+    }
+
+    fn hl(&self, i: usize) -> f64 {
+        self.high[i] - self.low[i]
+    }
+
+    fn uss(&self, i: usize) -> f64 {
+        self.high[i] - self.close[i].max(self.open[i])
+    }
+
+    fn lss(&self, i: usize) -> f64 {
+        self.close[i].min(self.open[i]) - self.low[i]
+    }
+
+    fn body_top(&self, i: usize) -> f64 {
+        self.close[i].max(self.open[i])
+    }
+
+    fn body_bottom(&self, i: usize) -> f64 {
+        self.close[i].min(self.open[i])
+    }
+
+    fn color(&self, i: usize) -> i32 {
+        if self.close[i] >= self.open[i] { 1 } else { -1 }
+    }
+
+    /// The real body of `i` is entirely above the real body of `j`.
+    fn rb_gap_up(&self, i: usize, j: usize) -> bool {
+        self.body_bottom(i) > self.body_top(j)
+    }
+
+    /// The real body of `i` is entirely below the real body of `j`.
+    fn rb_gap_down(&self, i: usize, j: usize) -> bool {
+        self.body_top(i) < self.body_bottom(j)
+    }
+
+    fn candle_gap_up(&self, i: usize, j: usize) -> bool {
+        self.low[i] > self.high[j]
+    }
+
+    fn candle_gap_down(&self, i: usize, j: usize) -> bool {
+        self.high[i] < self.low[j]
+    }
+
+    /// The result of a single bar pattern at bar `i`, which must be at least
+    /// the pattern's lookback.
+    fn eval(&self, pattern: CandlePattern, i: usize, penetration: f64) -> i32 {
+        let (o, h, l, c) = (&self.open, &self.high, &self.low, &self.close);
+        let color = |i| self.color(i);
+        let rb = |i| self.rb(i);
+        let uss = |i| self.uss(i);
+        let lss = |i| self.lss(i);
+        let avg = |s, i| self.avg(s, i);
+        let signal = |matched: bool, value: i32| if matched { value } else { 0 };
+
+        match pattern {
+            CandlePattern::TwoCrows => signal(
+                color(i - 2) == 1
+                    && rb(i - 2) > avg(S::BodyLong, i - 2)
+                    && color(i - 1) == -1
+                    && self.rb_gap_up(i - 1, i - 2)
+                    && color(i) == -1
+                    && o[i] < o[i - 1] && o[i] > c[i - 1]
+                    && c[i] > o[i - 2] && c[i] < c[i - 2],
+                -100
+            ),
+            CandlePattern::ThreeBlackCrows => signal(
+                color(i - 3) == 1
+                    && color(i - 2) == -1
+                    && lss(i - 2) < avg(S::ShadowVeryShort, i - 2)
+                    && color(i - 1) == -1
+                    && lss(i - 1) < avg(S::ShadowVeryShort, i - 1)
+                    && color(i) == -1
+                    && lss(i) < avg(S::ShadowVeryShort, i)
+                    && o[i - 1] < o[i - 2] && o[i - 1] > c[i - 2]
+                    && o[i] < o[i - 1] && o[i] > c[i - 1]
+                    && h[i - 3] > c[i - 2]
+                    && c[i - 2] > c[i - 1] && c[i - 1] > c[i],
+                -100
+            ),
+            CandlePattern::ThreeInside => signal(
+                rb(i - 2) > avg(S::BodyLong, i - 2)
+                    && rb(i - 1) <= avg(S::BodyShort, i - 1)
+                    && self.body_top(i - 1) < self.body_top(i - 2)
+                    && self.body_bottom(i - 1) > self.body_bottom(i - 2)
+                    && ((color(i - 2) == 1 && color(i) == -1 && c[i] < o[i - 2])
+                        || (color(i - 2) == -1 && color(i) == 1 && c[i] > o[i - 2])),
+                -color(i - 2) * 100
+            ),
+            CandlePattern::ThreeLineStrike => signal(
+                color(i - 3) == color(i - 2)
+                    && color(i - 2) == color(i - 1)
+                    && color(i) == -color(i - 1)
+                    && o[i - 2] >= self.body_bottom(i - 3) - avg(S::Near, i - 3)
+                    && o[i - 2] <= self.body_top(i - 3) + avg(S::Near, i - 3)
+                    && o[i - 1] >= self.body_bottom(i - 2) - avg(S::Near, i - 2)
+                    && o[i - 1] <= self.body_top(i - 2) + avg(S::Near, i - 2)
+                    && ((color(i - 1) == 1
+                        && c[i - 1] > c[i - 2] && c[i - 2] > c[i - 3]
+                        && o[i] > c[i - 1]
+                        && c[i] < o[i - 3])
+                        || (color(i - 1) == -1
+                            && c[i - 1] < c[i - 2] && c[i - 2] < c[i - 3]
+                            && o[i] < c[i - 1]
+                            && c[i] > o[i - 3])),
+                color(i - 1) * 100
+            ),
+            CandlePattern::ThreeOutside => signal(
+                (color(i - 1) == 1 && color(i - 2) == -1
+                    && c[i - 1] > o[i - 2] && o[i - 1] < c[i - 2]
+                    && c[i] > c[i - 1])
+                    || (color(i - 1) == -1 && color(i - 2) == 1
+                        && o[i - 1] > c[i - 2] && c[i - 1] < o[i - 2]
+                        && c[i] < c[i - 1]),
+                color(i - 1) * 100
+            ),
+            CandlePattern::ThreeStarsInSouth => signal(
+                color(i - 2) == -1 && color(i - 1) == -1 && color(i) == -1
+                    && rb(i - 2) > avg(S::BodyLong, i - 2)
+                    && lss(i - 2) > avg(S::ShadowLong, i - 2)
+                    && rb(i - 1) < rb(i - 2)
+                    && o[i - 1] > c[i - 2] && o[i - 1] <= h[i - 2]
+                    && l[i - 1] < c[i - 2]
+                    && l[i - 1] >= l[i - 2]
+                    && lss(i - 1) > avg(S::ShadowVeryShort, i - 1)
+                    && rb(i) < avg(S::BodyShort, i)
+                    && lss(i) < avg(S::ShadowVeryShort, i)
+                    && uss(i) < avg(S::ShadowVeryShort, i)
+                    && l[i] > l[i - 1] && h[i] < h[i - 1],
+                100
+            ),
+            CandlePattern::ThreeWhiteSoldiers => signal(
+                color(i - 2) == 1 && uss(i - 2) < avg(S::ShadowVeryShort, i - 2)
+                    && color(i - 1) == 1 && uss(i - 1) < avg(S::ShadowVeryShort, i - 1)
+                    && color(i) == 1 && uss(i) < avg(S::ShadowVeryShort, i)
+                    && c[i] > c[i - 1] && c[i - 1] > c[i - 2]
+                    && o[i - 1] > o[i - 2]
+                    && o[i - 1] <= c[i - 2] + avg(S::Near, i - 2)
+                    && o[i] > o[i - 1]
+                    && o[i] <= c[i - 1] + avg(S::Near, i - 1)
+                    && rb(i - 1) > rb(i - 2) - avg(S::Far, i - 2)
+                    && rb(i) > rb(i - 1) - avg(S::Far, i - 1)
+                    && rb(i) > avg(S::BodyShort, i),
+                100
+            ),
+            CandlePattern::AbandonedBaby => signal(
+                rb(i - 2) > avg(S::BodyLong, i - 2)
+                    && rb(i - 1) <= avg(S::BodyDoji, i - 1)
+                    && rb(i) > avg(S::BodyShort, i)
+                    && ((color(i - 2) == 1 && color(i) == -1
+                        && c[i] < c[i - 2] - rb(i - 2) * penetration
+                        && self.candle_gap_up(i - 1, i - 2)
+                        && self.candle_gap_down(i, i - 1))
+                        || (color(i - 2) == -1 && color(i) == 1
+                            && c[i] > c[i - 2] + rb(i - 2) * penetration
+                            && self.candle_gap_down(i - 1, i - 2)
+                            && self.candle_gap_up(i, i - 1))),
+                color(i) * 100
+            ),
+            CandlePattern::AdvanceBlock => signal(
+                color(i - 2) == 1 && color(i - 1) == 1 && color(i) == 1
+                    && c[i] > c[i - 1] && c[i - 1] > c[i - 2]
+                    && o[i - 1] > o[i - 2]
+                    && o[i - 1] <= c[i - 2] + avg(S::Near, i - 2)
+                    && o[i] > o[i - 1]
+                    && o[i] <= c[i - 1] + avg(S::Near, i - 1)
+                    && rb(i - 2) > avg(S::BodyLong, i - 2)
+                    && uss(i - 2) < avg(S::ShadowShort, i - 2)
+                    && ((rb(i - 1) < rb(i - 2) - avg(S::Far, i - 2)
+                        && rb(i) < rb(i - 1) + avg(S::Near, i - 1))
+                        || rb(i) < rb(i - 1) - avg(S::Far, i - 1)
+                        || (rb(i) < rb(i - 1) && rb(i - 1) < rb(i - 2)
+                            && (uss(i) > avg(S::ShadowShort, i)
+                                || uss(i - 1) > avg(S::ShadowShort, i - 1)))
+                        || (rb(i) < rb(i - 1) && uss(i) > avg(S::ShadowLong, i))),
+                -100
+            ),
+            CandlePattern::BeltHold => signal(
+                rb(i) > avg(S::BodyLong, i)
+                    && ((color(i) == 1 && lss(i) < avg(S::ShadowVeryShort, i))
+                        || (color(i) == -1 && uss(i) < avg(S::ShadowVeryShort, i))),
+                color(i) * 100
+            ),
+            CandlePattern::Breakaway => signal(
+                rb(i - 4) > avg(S::BodyLong, i - 4)
+                    && color(i - 4) == color(i - 3)
+                    && color(i - 3) == color(i - 1)
+                    && color(i - 1) == -color(i)
+                    && ((color(i - 4) == -1
+                        && self.rb_gap_down(i - 3, i - 4)
+                        && h[i - 2] < h[i - 3] && l[i - 2] < l[i - 3]
+                        && h[i - 1] < h[i - 2] && l[i - 1] < l[i - 2]
+                        && c[i] > o[i - 3] && c[i] < c[i - 4])
+                        || (color(i - 4) == 1
+                            && self.rb_gap_up(i - 3, i - 4)
+                            && h[i - 2] > h[i - 3] && l[i - 2] > l[i - 3]
+                            && h[i - 1] > h[i - 2] && l[i - 1] > l[i - 2]
+                            && c[i] < o[i - 3] && c[i] > c[i - 4])),
+                color(i) * 100
+            ),
+            CandlePattern::ClosingMarubozu => signal(
+                rb(i) > avg(S::BodyLong, i)
+                    && ((color(i) == 1 && uss(i) < avg(S::ShadowVeryShort, i))
+                        || (color(i) == -1 && lss(i) < avg(S::ShadowVeryShort, i))),
+                color(i) * 100
+            ),
+            CandlePattern::ConcealBabySwallow => signal(
+                color(i - 3) == -1 && color(i - 2) == -1 && color(i - 1) == -1 && color(i) == -1
+                    && lss(i - 3) < avg(S::ShadowVeryShort, i - 3)
+                    && uss(i - 3) < avg(S::ShadowVeryShort, i - 3)
+                    && lss(i - 2) < avg(S::ShadowVeryShort, i - 2)
+                    && uss(i - 2) < avg(S::ShadowVeryShort, i - 2)
+                    && self.rb_gap_down(i - 1, i - 2)
+                    && uss(i - 1) > avg(S::ShadowVeryShort, i - 1)
+                    && h[i - 1] > c[i - 2]
+                    && h[i] > h[i - 1] && l[i] < l[i - 1],
+                100
+            ),
+            CandlePattern::Counterattack => signal(
+                color(i - 1) == -color(i)
+                    && rb(i - 1) > avg(S::BodyLong, i - 1)
+                    && rb(i) > avg(S::BodyLong, i)
+                    && c[i] <= c[i - 1] + avg(S::Equal, i - 1)
+                    && c[i] >= c[i - 1] - avg(S::Equal, i - 1),
+                color(i) * 100
+            ),
+            CandlePattern::DarkCloudCover => signal(
+                color(i - 1) == 1 && rb(i - 1) > avg(S::BodyLong, i - 1)
+                    && color(i) == -1
+                    && o[i] > h[i - 1]
+                    && c[i] > o[i - 1]
+                    && c[i] < c[i - 1] - rb(i - 1) * penetration,
+                -100
+            ),
+            CandlePattern::Doji => signal(rb(i) <= avg(S::BodyDoji, i), 100),
+            CandlePattern::DojiStar => signal(
+                rb(i - 1) > avg(S::BodyLong, i - 1)
+                    && rb(i) <= avg(S::BodyDoji, i)
+                    && ((color(i - 1) == 1 && self.rb_gap_up(i, i - 1))
+                        || (color(i - 1) == -1 && self.rb_gap_down(i, i - 1))),
+                -color(i - 1) * 100
+            ),
+            CandlePattern::DragonflyDoji => signal(
+                rb(i) <= avg(S::BodyDoji, i)
+                    && uss(i) < avg(S::ShadowVeryShort, i)
+                    && lss(i) > avg(S::ShadowVeryShort, i),
+                100
+            ),
+            CandlePattern::Engulfing => signal(
+                (color(i) == 1 && color(i - 1) == -1 && c[i] > o[i - 1] && o[i] < c[i - 1])
+                    || (color(i) == -1 && color(i - 1) == 1 && o[i] > c[i - 1] && c[i] < o[i - 1]),
+                color(i) * 100
+            ),
+            CandlePattern::EveningDojiStar => signal(
+                color(i - 2) == 1 && rb(i - 2) > avg(S::BodyLong, i - 2)
+                    && rb(i - 1) <= avg(S::BodyDoji, i - 1)
+                    && self.rb_gap_up(i - 1, i - 2)
+                    && rb(i) > avg(S::BodyShort, i)
+                    && color(i) == -1
+                    && c[i] < c[i - 2] - rb(i - 2) * penetration,
+                -100
+            ),
+            CandlePattern::EveningStar => signal(
+                rb(i - 2) > avg(S::BodyLong, i - 2) && color(i - 2) == 1
+                    && rb(i - 1) <= avg(S::BodyShort, i - 1)
+                    && self.rb_gap_up(i - 1, i - 2)
+                    && rb(i) > avg(S::BodyShort, i)
+                    && color(i) == -1
+                    && c[i] < c[i - 2] - rb(i - 2) * penetration,
+                -100
+            ),
+            CandlePattern::GapSideSideWhite => signal(
+                ((self.rb_gap_up(i - 1, i - 2) && self.rb_gap_up(i, i - 2))
+                    || (self.rb_gap_down(i - 1, i - 2) && self.rb_gap_down(i, i - 2)))
+                    && color(i - 1) == 1 && color(i) == 1
+                    && rb(i) >= rb(i - 1) - avg(S::Near, i - 1)
+                    && rb(i) <= rb(i - 1) + avg(S::Near, i - 1)
+                    && o[i] >= o[i - 1] - avg(S::Equal, i - 1)
+                    && o[i] <= o[i - 1] + avg(S::Equal, i - 1),
+                if self.rb_gap_up(i - 1, i - 2) { 100 } else { -100 }
+            ),
+            CandlePattern::GravestoneDoji => signal(
+                rb(i) <= avg(S::BodyDoji, i)
+                    && lss(i) < avg(S::ShadowVeryShort, i)
+                    && uss(i) > avg(S::ShadowVeryShort, i),
+                100
+            ),
+            CandlePattern::Hammer => signal(
+                rb(i) < avg(S::BodyShort, i)
+                    && lss(i) > avg(S::ShadowLong, i)
+                    && uss(i) < avg(S::ShadowVeryShort, i)
+                    && self.body_bottom(i) <= l[i - 1] + avg(S::Near, i - 1),
+                100
+            ),
+            CandlePattern::HangingMan => signal(
+                rb(i) < avg(S::BodyShort, i)
+                    && lss(i) > avg(S::ShadowLong, i)
+                    && uss(i) < avg(S::ShadowVeryShort, i)
+                    && self.body_bottom(i) >= h[i - 1] - avg(S::Near, i - 1),
+                -100
+            ),
+            CandlePattern::Harami => signal(
+                rb(i - 1) > avg(S::BodyLong, i - 1)
+                    && rb(i) <= avg(S::BodyShort, i)
+                    && self.body_top(i) < self.body_top(i - 1)
+                    && self.body_bottom(i) > self.body_bottom(i - 1),
+                -color(i - 1) * 100
+            ),
+            CandlePattern::HaramiCross => signal(
+                rb(i - 1) > avg(S::BodyLong, i - 1)
+                    && rb(i) <= avg(S::BodyDoji, i)
+                    && self.body_top(i) < self.body_top(i - 1)
+                    && self.body_bottom(i) > self.body_bottom(i - 1),
+                -color(i - 1) * 100
+            ),
+            CandlePattern::HighWave => signal(
+                rb(i) < avg(S::BodyShort, i)
+                    && uss(i) > avg(S::ShadowVeryLong, i)
+                    && lss(i) > avg(S::ShadowVeryLong, i),
+                color(i) * 100
+            ),
+            CandlePattern::HomingPigeon => signal(
+                color(i - 1) == -1 && color(i) == -1
+                    && rb(i - 1) > avg(S::BodyLong, i - 1)
+                    && rb(i) <= avg(S::BodyShort, i)
+                    && o[i] < o[i - 1] && c[i] > c[i - 1],
+                100
+            ),
+            CandlePattern::IdenticalThreeCrows => signal(
+                color(i - 2) == -1 && lss(i - 2) < avg(S::ShadowVeryShort, i - 2)
+                    && color(i - 1) == -1 && lss(i - 1) < avg(S::ShadowVeryShort, i - 1)
+                    && color(i) == -1 && lss(i) < avg(S::ShadowVeryShort, i)
+                    && c[i - 2] > c[i - 1] && c[i - 1] > c[i]
+                    && o[i - 1] <= c[i - 2] + avg(S::Equal, i - 2)
+                    && o[i - 1] >= c[i - 2] - avg(S::Equal, i - 2)
+                    && o[i] <= c[i - 1] + avg(S::Equal, i - 1)
+                    && o[i] >= c[i - 1] - avg(S::Equal, i - 1),
+                -100
+            ),
+            CandlePattern::InNeck => signal(
+                color(i - 1) == -1 && rb(i - 1) > avg(S::BodyLong, i - 1)
+                    && color(i) == 1
+                    && o[i] < l[i - 1]
+                    && c[i] <= c[i - 1] + avg(S::Equal, i - 1)
+                    && c[i] >= c[i - 1],
+                -100
+            ),
+            CandlePattern::InvertedHammer => signal(
+                rb(i) < avg(S::BodyShort, i)
+                    && uss(i) > avg(S::ShadowLong, i)
+                    && lss(i) < avg(S::ShadowVeryShort, i)
+                    && self.rb_gap_down(i, i - 1),
+                100
+            ),
+            CandlePattern::Kicking | CandlePattern::KickingByLength => {
+                let matched = color(i - 1) == -color(i)
+                    && rb(i - 1) > avg(S::BodyLong, i - 1)
+                    && uss(i - 1) < avg(S::ShadowVeryShort, i - 1)
+                    && lss(i - 1) < avg(S::ShadowVeryShort, i - 1)
+                    && rb(i) > avg(S::BodyLong, i)
+                    && uss(i) < avg(S::ShadowVeryShort, i)
+                    && lss(i) < avg(S::ShadowVeryShort, i)
+                    && ((color(i - 1) == -1 && self.candle_gap_up(i, i - 1))
+                        || (color(i - 1) == 1 && self.candle_gap_down(i, i - 1)));
+                let direction = if pattern == CandlePattern::KickingByLength && rb(i) <= rb(i - 1) {
+                    color(i - 1)
+                } else {
+                    color(i)
+                };
+                signal(matched, direction * 100)
+            },
+            CandlePattern::LadderBottom => signal(
+                color(i - 4) == -1 && color(i - 3) == -1 && color(i - 2) == -1
+                    && o[i - 4] > o[i - 3] && o[i - 3] > o[i - 2]
+                    && c[i - 4] > c[i - 3] && c[i - 3] > c[i - 2]
+                    && color(i - 1) == -1
+                    && uss(i - 1) > avg(S::ShadowVeryShort, i - 1)
+                    && color(i) == 1
+                    && o[i] > o[i - 1]
+                    && c[i] > h[i - 1],
+                100
+            ),
+            CandlePattern::LongLeggedDoji => signal(
+                rb(i) <= avg(S::BodyDoji, i)
+                    && (lss(i) > avg(S::ShadowLong, i) || uss(i) > avg(S::ShadowLong, i)),
+                100
+            ),
+            CandlePattern::LongLine => signal(
+                rb(i) > avg(S::BodyLong, i)
+                    && uss(i) < avg(S::ShadowShort, i)
+                    && lss(i) < avg(S::ShadowShort, i),
+                color(i) * 100
+            ),
+            CandlePattern::Marubozu => signal(
+                rb(i) > avg(S::BodyLong, i)
+                    && uss(i) < avg(S::ShadowVeryShort, i)
+                    && lss(i) < avg(S::ShadowVeryShort, i),
+                color(i) * 100
+            ),
+            CandlePattern::MatchingLow => signal(
+                color(i - 1) == -1 && color(i) == -1
+                    && c[i] <= c[i - 1] + avg(S::Equal, i - 1)
+                    && c[i] >= c[i - 1] - avg(S::Equal, i - 1),
+                100
+            ),
+            CandlePattern::MatHold => signal(
+                rb(i - 4) > avg(S::BodyLong, i - 4)
+                    && rb(i - 3) < avg(S::BodyShort, i - 3)
+                    && rb(i - 2) < avg(S::BodyShort, i - 2)
+                    && rb(i - 1) < avg(S::BodyShort, i - 1)
+                    && color(i - 4) == 1
+                    && color(i - 3) == -1
+                    && color(i) == 1
+                    && self.rb_gap_up(i - 3, i - 4)
+                    && self.body_bottom(i - 2) < c[i - 4]
+                    && self.body_bottom(i - 1) < c[i - 4]
+                    && self.body_bottom(i - 2) > c[i - 4] - rb(i - 4) * penetration
+                    && self.body_bottom(i - 1) > c[i - 4] - rb(i - 4) * penetration
+                    && self.body_top(i - 2) < o[i - 3]
+                    && self.body_top(i - 1) < self.body_top(i - 2)
+                    && o[i] > c[i - 1]
+                    && c[i] > h[i - 3].max(h[i - 2]).max(h[i - 1]),
+                100
+            ),
+            CandlePattern::MorningDojiStar => signal(
+                color(i - 2) == -1 && rb(i - 2) > avg(S::BodyLong, i - 2)
+                    && rb(i - 1) <= avg(S::BodyDoji, i - 1)
+                    && self.rb_gap_down(i - 1, i - 2)
+                    && rb(i) > avg(S::BodyShort, i)
+                    && color(i) == 1
+                    && c[i] > c[i - 2] + rb(i - 2) * penetration,
+                100
+            ),
+            CandlePattern::MorningStar => signal(
+                rb(i - 2) > avg(S::BodyLong, i - 2) && color(i - 2) == -1
+                    && rb(i - 1) <= avg(S::BodyShort, i - 1)
+                    && self.rb_gap_down(i - 1, i - 2)
+                    && rb(i) > avg(S::BodyShort, i)
+                    && color(i) == 1
+                    && c[i] > c[i - 2] + rb(i - 2) * penetration,
+                100
+            ),
+            CandlePattern::OnNeck => signal(
+                color(i - 1) == -1 && rb(i - 1) > avg(S::BodyLong, i - 1)
+                    && color(i) == 1
+                    && o[i] < l[i - 1]
+                    && c[i] <= l[i - 1] + avg(S::Equal, i - 1)
+                    && c[i] >= l[i - 1] - avg(S::Equal, i - 1),
+                -100
+            ),
+            CandlePattern::Piercing => signal(
+                color(i - 1) == -1 && rb(i - 1) > avg(S::BodyLong, i - 1)
+                    && color(i) == 1 && rb(i) > avg(S::BodyLong, i)
+                    && o[i] < l[i - 1]
+                    && c[i] < o[i - 1]
+                    && c[i] > c[i - 1] + rb(i - 1) * 0.5,
+                100
+            ),
+            CandlePattern::RickshawMan => signal(
+                rb(i) <= avg(S::BodyDoji, i)
+                    && lss(i) > avg(S::ShadowLong, i)
+                    && uss(i) > avg(S::ShadowLong, i)
+                    && self.body_bottom(i) <= l[i] + self.hl(i) / 2.0 + avg(S::Near, i)
+                    && self.body_top(i) >= l[i] + self.hl(i) / 2.0 - avg(S::Near, i),
+                100
+            ),
+            CandlePattern::RiseFallThreeMethods => {
+                let first = f64::from(color(i - 4));
+                signal(
+                    rb(i - 4) > avg(S::BodyLong, i - 4)
+                        && rb(i - 3) < avg(S::BodyShort, i - 3)
+                        && rb(i - 2) < avg(S::BodyShort, i - 2)
+                        && rb(i - 1) < avg(S::BodyShort, i - 1)
+                        && rb(i) > avg(S::BodyLong, i)
+                        && color(i - 4) == -color(i - 3)
+                        && color(i - 3) == color(i - 2)
+                        && color(i - 2) == color(i - 1)
+                        && color(i - 1) == -color(i)
+                        && self.body_bottom(i - 3) < h[i - 4] && self.body_top(i - 3) > l[i - 4]
+                        && self.body_bottom(i - 2) < h[i - 4] && self.body_top(i - 2) > l[i - 4]
+                        && self.body_bottom(i - 1) < h[i - 4] && self.body_top(i - 1) > l[i - 4]
+                        && c[i - 2] * first < c[i - 3] * first
+                        && c[i - 1] * first < c[i - 2] * first
+                        && o[i] * first > c[i - 1] * first
+                        && c[i] * first > c[i - 4] * first,
+                    color(i - 4) * 100
+                )
+            },
+            CandlePattern::SeparatingLines => signal(
+                color(i - 1) == -color(i)
+                    && o[i] <= o[i - 1] + avg(S::Equal, i - 1)
+                    && o[i] >= o[i - 1] - avg(S::Equal, i - 1)
+                    && rb(i) > avg(S::BodyLong, i)
+                    && ((color(i) == 1 && lss(i) < avg(S::ShadowVeryShort, i))
+                        || (color(i) == -1 && uss(i) < avg(S::ShadowVeryShort, i))),
+                color(i) * 100
+            ),
+            CandlePattern::ShootingStar => signal(
+                rb(i) < avg(S::BodyShort, i)
+                    && uss(i) > avg(S::ShadowLong, i)
+                    && lss(i) < avg(S::ShadowVeryShort, i)
+                    && self.rb_gap_up(i, i - 1),
+                -100
+            ),
+            CandlePattern::ShortLine => signal(
+                rb(i) < avg(S::BodyShort, i)
+                    && uss(i) < avg(S::ShadowShort, i)
+                    && lss(i) < avg(S::ShadowShort, i),
+                color(i) * 100
+            ),
+            CandlePattern::SpinningTop => signal(
+                rb(i) < avg(S::BodyShort, i) && uss(i) > rb(i) && lss(i) > rb(i),
+                color(i) * 100
+            ),
+            CandlePattern::StalledPattern => signal(
+                color(i - 2) == 1 && color(i - 1) == 1 && color(i) == 1
+                    && c[i] > c[i - 1] && c[i - 1] > c[i - 2]
+                    && rb(i - 2) > avg(S::BodyLong, i - 2)
+                    && rb(i - 1) > avg(S::BodyLong, i - 1)
+                    && uss(i - 1) < avg(S::ShadowVeryShort, i - 1)
+                    && o[i - 1] > o[i - 2]
+                    && o[i - 1] <= c[i - 2] + avg(S::Near, i - 2)
+                    && rb(i) < avg(S::BodyShort, i)
+                    && o[i] >= c[i - 1] - rb(i) - avg(S::Near, i - 1),
+                -100
+            ),
+            CandlePattern::StickSandwich => signal(
+                color(i - 2) == -1 && color(i - 1) == 1 && color(i) == -1
+                    && l[i - 1] > c[i - 2]
+                    && c[i] <= c[i - 2] + avg(S::Equal, i - 2)
+                    && c[i] >= c[i - 2] - avg(S::Equal, i - 2),
+                100
+            ),
+            CandlePattern::Takuri => signal(
+                rb(i) <= avg(S::BodyDoji, i)
+                    && uss(i) < avg(S::ShadowVeryShort, i)
+                    && lss(i) > avg(S::ShadowVeryLong, i),
+                100
+            ),
+            CandlePattern::TasukiGap => signal(
+                (self.rb_gap_up(i - 1, i - 2)
+                    && color(i - 1) == 1 && color(i) == -1
+                    && o[i] < c[i - 1] && o[i] > o[i - 1]
+                    && c[i] < o[i - 1]
+                    && c[i] > self.body_top(i - 2)
+                    && (rb(i - 1) - rb(i)).abs() < avg(S::Near, i - 1))
+                    || (self.rb_gap_down(i - 1, i - 2)
+                        && color(i - 1) == -1 && color(i) == 1
+                        && o[i] < o[i - 1] && o[i] > c[i - 1]
+                        && c[i] > o[i - 1]
+                        && c[i] < self.body_bottom(i - 2)
+                        && (rb(i - 1) - rb(i)).abs() < avg(S::Near, i - 1)),
+                color(i - 1) * 100
+            ),
+            CandlePattern::Thrusting => signal(
+                color(i - 1) == -1 && rb(i - 1) > avg(S::BodyLong, i - 1)
+                    && color(i) == 1
+                    && o[i] < l[i - 1]
+                    && c[i] > c[i - 1] + avg(S::Equal, i - 1)
+                    && c[i] <= c[i - 1] + rb(i - 1) * 0.5,
+                -100
+            ),
+            CandlePattern::Tristar => {
+                // all three bodies are measured against the first candle's average
+                let doji = avg(S::BodyDoji, i - 2);
+                if rb(i - 2) <= doji && rb(i - 1) <= doji && rb(i) <= doji {
+                    if self.rb_gap_down(i - 1, i - 2) && self.body_bottom(i) > self.body_bottom(i - 1) {
+                        100
+                    } else if self.rb_gap_up(i - 1, i - 2) && self.body_top(i) < self.body_top(i - 1) {
+                        -100
+                    } else {
+                        0
+                    }
+                } else {
+                    0
+                }
+            },
+            CandlePattern::UniqueThreeRiver => signal(
+                rb(i - 2) > avg(S::BodyLong, i - 2) && color(i - 2) == -1
+                    && color(i - 1) == -1 && c[i - 1] > c[i - 2]
+                    && o[i - 1] <= o[i - 2] && l[i - 1] < l[i - 2]
+                    && rb(i) < avg(S::BodyShort, i) && color(i) == 1
+                    && o[i] > l[i - 1],
+                100
+            ),
+            CandlePattern::UpsideGapTwoCrows => signal(
+                color(i - 2) == 1 && rb(i - 2) > avg(S::BodyLong, i - 2)
+                    && color(i - 1) == -1 && rb(i - 1) <= avg(S::BodyShort, i - 1)
+                    && self.rb_gap_up(i - 1, i - 2)
+                    && color(i) == -1
+                    && o[i] > o[i - 1] && c[i] < c[i - 1]
+                    && c[i] > c[i - 2],
+                -100
+            ),
+            CandlePattern::XSideGapThreeMethods => signal(
+                color(i - 2) == color(i - 1) && color(i - 1) == -color(i)
+                    && o[i] < self.body_top(i - 1) && o[i] > self.body_bottom(i - 1)
+                    && c[i] < self.body_top(i - 2) && c[i] > self.body_bottom(i - 2)
+                    && ((color(i - 2) == 1 && self.rb_gap_up(i - 1, i - 2))
+                        || (color(i - 2) == -1 && self.rb_gap_down(i - 1, i - 2))),
+                color(i - 2) * 100
+            ),
+            // stateful and pandas-ta patterns are evaluated over the whole series
+            CandlePattern::Hikkake | CandlePattern::HikkakeMod | CandlePattern::Inside => 0,
+        }
+    }
+
+    /// Hikkake and its modified version: the pattern bar scores +-100 and a
+    /// close beyond the second candle within three bars confirms it with +-200.
+    fn hikkake(&self, modified: bool, lookback: usize) -> Vec<i32> {
+        let (h, l, c) = (&self.high, &self.low, &self.close);
+        let mut result = vec![0; self.len()];
+        let mut pattern_idx = 0;
+        let mut pattern_result: i32 = 0;
+        // TA-Lib starts tracking three bars before the first output
+        for i in lookback.saturating_sub(3)..self.len() {
+            let matched = if modified {
+                h[i - 2] < h[i - 3] && l[i - 2] > l[i - 3]
+                    && h[i - 1] < h[i - 2] && l[i - 1] > l[i - 2]
+                    && ((h[i] < h[i - 1] && l[i] < l[i - 1]
+                        && c[i - 2] <= l[i - 2] + self.avg(S::Near, i - 2))
+                        || (h[i] > h[i - 1] && l[i] > l[i - 1]
+                            && c[i - 2] >= h[i - 2] - self.avg(S::Near, i - 2)))
+            } else {
+                h[i - 1] < h[i - 2] && l[i - 1] > l[i - 2]
+                    && ((h[i] < h[i - 1] && l[i] < l[i - 1])
+                        || (h[i] > h[i - 1] && l[i] > l[i - 1]))
+            };
+            let output = if matched {
+                pattern_result = if h[i] < h[i - 1] { 100 } else { -100 };
+                pattern_idx = i;
+                pattern_result
+            } else if pattern_idx > 0 && i <= pattern_idx + 3
+                && ((pattern_result > 0 && c[i] > h[pattern_idx - 1])
+                    || (pattern_result < 0 && c[i] < l[pattern_idx - 1]))
+            {
+                pattern_idx = 0;
+                pattern_result + pattern_result.signum() * 100
+            } else {
+                0
+            };
+            if i >= lookback {
+                result[i] = output;
+            }
+        }
+        result
+    }
+}
+
+
+/// Candlestick patterns with TA-Lib's default settings, see
+/// `cdl_pattern_with`. An empty `names` slice evaluates every pattern.
+pub fn cdl_pattern(
+    open: &Series,
+    high: &Series,
+    low: &Series,
+    close: &Series,
+    names: &[CandlePattern]
+) -> CommandResult<DataFrame> {
+    cdl_pattern_with(open, high, low, close, names, CdlPatternOptions::default())
+}
+
+
+/// Recognises each of `names` and returns one `CDL_<NAME>` column per pattern
+/// holding 100 (bullish), -100 (bearish) or 0, like TA-Lib; the hikkake
+/// patterns report confirmations as +-200. Body and shadow thresholds are
+/// trailing averages configured by `options.settings`, and the first
+/// `lookback` bars of each pattern are 0.
+pub fn cdl_pattern_with(
+    open: &Series,
+    high: &Series,
+    low: &Series,
+    close: &Series,
+    names: &[CandlePattern],
+    options: CdlPatternOptions
+) -> CommandResult<DataFrame> {
+    let prepared = prepare(&[open, high, low, close])?;
+    let (open, high, low, close) = (
+        &prepared.inputs[0],
+        &prepared.inputs[1],
+        &prepared.inputs[2],
+        &prepared.inputs[3]
+    );
+    let len = close.len();
+    let offset = get_offset(options.offset);
+    let names = if names.is_empty() { &CandlePattern::ALL[..] } else { names };
+
+    let candles = Candles::new(open, high, low, close, &options.settings)?;
+
+    let mut columns = Vec::with_capacity(names.len());
+    for pattern in names {
+        let lookback = pattern.lookback(&options.settings);
+        let values: Vec<i32> = match pattern {
+            CandlePattern::Hikkake => candles.hikkake(false, lookback),
+            CandlePattern::HikkakeMod => candles.hikkake(true, lookback),
+            CandlePattern::Inside => {
+                let inside = cdl_inside(open, high, low, close, false, None, None)?;
+                inside.i32()?.into_iter().map(|v| v.unwrap_or(0) * 100).collect()
+            },
+            _ => {
+                let penetration = options.penetration.unwrap_or(pattern.default_penetration());
+                (0..len)
+                    .map(|i| if i < lookback { 0 } else { candles.eval(*pattern, i, penetration) })
+                    .collect()
+            }
+        };
+
+        let column = Series::new(format!("CDL_{}", pattern.name()).into(), values);
+        let column = prepared.restore(column, offset)?;
+        columns.push(column.into_column());
+    }
+
+    fill_missing_frame(DataFrame::new(columns)?, options.fill)
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::utils::data_loader::{
+        csv_to_dataframe,
+        set_column_names,
+        combine_date_time,
+        convert_to_naive_datetime
+    };
+
+    use super::*;
+
+    /// Ten quiet bars followed by `bars`, as (open, high, low, close).
+    fn candles(bars: &[(f64, f64, f64, f64)]) -> [Series; 4] {
+        let mut all = vec![(10.0, 10.6, 9.4, 10.1); 10];
+        all.extend_from_slice(bars);
+        [
+            Series::new("open".into(), all.iter().map(|b| b.0).collect::<Vec<_>>()),
+            Series::new("high".into(), all.iter().map(|b| b.1).collect::<Vec<_>>()),
+            Series::new("low".into(), all.iter().map(|b| b.2).collect::<Vec<_>>()),
+            Series::new("close".into(), all.iter().map(|b| b.3).collect::<Vec<_>>()),
+        ]
+    }
+
+    fn last(df: &DataFrame, name: &str) -> i32 {
+        let column = df.column(name).unwrap().i32().unwrap();
+        column.get(column.len() - 1).unwrap()
+    }
+
+    #[test]
+    fn test_cdl_pattern() {
+        let mut df = csv_to_dataframe(
+            "data/AUDNZD1.csv",
+            false
+        ).unwrap();
+        let _ = set_column_names(
+            &mut df,
+            vec!["date", "time", "open", "high", "low", "close", "volume"]
+        );
+        let df = combine_date_time(&df).unwrap();
+        let df = convert_to_naive_datetime(
+            &df,
+            "%Y.%m.%d %H:%M"
+        ).unwrap();
+
+        let open = df.column("open").unwrap().as_series().unwrap();
+        let high = df.column("high").unwrap().as_series().unwrap();
+        let low = df.column("low").unwrap().as_series().unwrap();
+        let close = df.column("close").unwrap().as_series().unwrap();
+
+        let patterns = cdl_pattern(open, high, low, close, &[]).unwrap();
+        assert_eq!(patterns.width(), 62);
+        assert_eq!(patterns.height(), close.len());
+        for column in patterns.get_columns() {
+            assert!(column.name().starts_with("CDL_"));
+            let values = column.i32().unwrap();
+            assert!(values.into_no_null_iter().all(|v| [-200, -100, 0, 100, 200].contains(&v)));
+        }
+        let doji = patterns.column("CDL_DOJI").unwrap().i32().unwrap();
+        assert!(doji.into_no_null_iter().take(10).all(|v| v == 0));
+        assert!(doji.into_no_null_iter().any(|v| v == 100));
+    }
+
+    #[test]
+    fn test_engulfing() {
+        let [open, high, low, close] = candles(&[
+            (10.5, 10.6, 9.9, 10.0),
+            (9.9, 10.8, 9.8, 10.7),
+        ]);
+        let df = cdl_pattern(&open, &high, &low, &close, &[CandlePattern::Engulfing]).unwrap();
+        assert_eq!(last(&df, "CDL_ENGULFING"), 100);
+    }
+
+    #[test]
+    fn test_hammer() {
+        let [open, high, low, close] = candles(&[
+            (10.0, 10.2, 9.5, 9.6),
+            (9.65, 9.72, 8.5, 9.7),
+        ]);
+        let df = cdl_pattern(&open, &high, &low, &close, &[CandlePattern::Hammer]).unwrap();
+        assert_eq!(last(&df, "CDL_HAMMER"), 100);
+    }
+
+    #[test]
+    fn test_morning_star() {
+        let [open, high, low, close] = candles(&[
+            (11.0, 11.1, 9.4, 9.5),
+            (9.2, 9.3, 8.9, 9.1),
+            (9.3, 10.9, 9.2, 10.8),
+        ]);
+        let df = cdl_pattern(&open, &high, &low, &close, &[CandlePattern::MorningStar]).unwrap();
+        assert_eq!(last(&df, "CDL_MORNINGSTAR"), 100);
+    }
+
+    #[test]
+    fn test_three_white_soldiers() {
+        let [open, high, low, close] = candles(&[
+            (10.0, 10.82, 9.95, 10.8),
+            (10.5, 11.42, 10.45, 11.4),
+            (11.1, 12.12, 11.05, 12.1),
+        ]);
+        let df = cdl_pattern(&open, &high, &low, &close, &[CandlePattern::ThreeWhiteSoldiers]).unwrap();
+        assert_eq!(last(&df, "CDL_3WHITESOLDIERS"), 100);
+    }
+
+    #[test]
+    fn test_harami() {
+        let [open, high, low, close] = candles(&[
+            (11.0, 11.1, 9.4, 9.5),
+            (10.0, 10.3, 9.9, 10.2),
+        ]);
+        let df = cdl_pattern(&open, &high, &low, &close, &[CandlePattern::Harami]).unwrap();
+        assert_eq!(last(&df, "CDL_HARAMI"), 100);
+    }
+
+    #[test]
+    fn test_from_name() {
+        assert_eq!(CandlePattern::from_name("engulfing"), Some(CandlePattern::Engulfing));
+        assert_eq!(CandlePattern::from_name("CDL_3WHITESOLDIERS"), Some(CandlePattern::ThreeWhiteSoldiers));
+        assert_eq!(CandlePattern::from_name("cdlinside"), Some(CandlePattern::Inside));
+        assert_eq!(CandlePattern::from_name("unknown"), None);
+    }
+}
@@ -0,0 +1,179 @@
+use crate::overlap::sma::{sma, SmaOptions};
+use crate::statistics::stdev::stdev;
+use crate::utils::core::{get_offset, shifted_lookback, fill_missing_frame, FillPolicy};
+use crate::utils::missing::prepare;
+use crate::utils::error::CommandResult;
+use crate::utils::validate;
+use polars::prelude::*;
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct CdlZOptions {
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub length: Option<i32>,
+    pub full: bool,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub ddof: Option<i32>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub offset: Option<i32>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub fill: Option<FillPolicy>,
+}
+
+
+impl Default for CdlZOptions {
+    fn default() -> Self {
+        CdlZOptions {
+            length: Some(30),
+            full: false,
+            ddof: Some(1),
+            offset: None,
+            fill: None,
+        }
+    }
+}
+
+
+impl CdlZOptions {
+    /// The output columns, e.g. `open_Z_30_1` or `open_Za` when `full`.
+    pub fn names(&self) -> Vec<String> {
+        let length = match self.length {
+            Some(length) if length > 1 => length,
+            _ => 30
+        };
+        let ddof = match self.ddof {
+            Some(ddof) if ddof >= 0 && ddof < length => ddof,
+            _ => 1
+        };
+        let suffix = if self.full {
+            "a".to_string()
+        } else {
+            format!("_{}_{}", length, ddof)
+        };
+        ["open", "high", "low", "close"]
+            .iter()
+            .map(|name| format!("{}_Z{}", name, suffix))
+            .collect()
+    }
+
+    /// Leading bars without a full window, shifted by the offset.
+    pub fn lookback(&self) -> usize {
+        shifted_lookback(validate::resolved(self.length, 2, 30) - 1, self.offset)
+    }
+}
+
+
+fn zscore(series: &Series, length: i32, ddof: i32, full: bool) -> CommandResult<Series> {
+    let series = series.cast(&DataType::Float64)?;
+    if full {
+        // one mean and deviation over the whole input
+        let values = series.f64()?;
+        let n = values.len() - values.null_count();
+        let mean = values.mean();
+        let stdev = values.std(ddof as u8);
+        let zscore: Series = values
+            .into_iter()
+            .map(|value| match (value, mean, stdev) {
+                (Some(value), Some(mean), Some(stdev)) if n > ddof as usize => {
+                    Some((value - mean) / stdev)
+                },
+                _ => None
+            })
+            .collect();
+        return Ok(zscore);
+    }
+
+    let mean = sma(&series, SmaOptions {
+        length: Some(length),
+        ..Default::default()
+    })?;
+    let stdev = stdev(&series, Some(length), Some(ddof), None, None)?;
+    match &series - &mean {
+        Ok(diff) => match diff / stdev {
+            Ok(zscore) => Ok(zscore),
+            Err(_) => Err("Failed to calculate z-score".into())
+        },
+        Err(_) => Err("Failed to calculate difference".into())
+    }
+}
+
+
+/// Z-scored open, high, low and close, either over a rolling `length` window
+/// or, when `full` is set, against the mean and deviation of the whole input.
+pub fn cdl_z(
+    open: &Series,
+    high: &Series,
+    low: &Series,
+    close: &Series,
+    options: CdlZOptions
+) -> CommandResult<DataFrame> {
+    let prepared = prepare(&[open, high, low, close])?;
+    let length = validate::length("length", options.length, 2, 30)?;
+    let ddof = validate::parameter("ddof", options.ddof, 1, |ddof| ddof >= 0 && ddof < length, "below length")?;
+    validate::min_rows(close.len(), length as usize)?;
+    let offset = get_offset(options.offset);
+    let full = options.full;
+
+    let names = options.names();
+
+    let mut columns = Vec::with_capacity(4);
+    for (name, series) in names.into_iter().zip(&prepared.inputs) {
+        let z = prepared.restore(zscore(series, length, ddof, full)?, offset)?;
+        columns.push(z.with_name(name.into()).into_column());
+    }
+
+    fill_missing_frame(DataFrame::new(columns)?, options.fill)
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::utils::data_loader::{
+        csv_to_dataframe, 
+        set_column_names, 
+        combine_date_time, 
+        convert_to_naive_datetime
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_cdl_z() {
+        let mut df = csv_to_dataframe(
+            "data/AUDNZD1.csv", 
+            false
+        ).unwrap();
+        let _ = set_column_names(
+            &mut df, 
+            vec!["date", "time", "open", "high", "low", "close", "volume"]
+        );
+        let df = combine_date_time(&df).unwrap();
+        let df = convert_to_naive_datetime(
+            &df, 
+            "%Y.%m.%d %H:%M"
+        ).unwrap();
+
+        let open = df.column("open").unwrap().as_series().unwrap();
+        let high = df.column("high").unwrap().as_series().unwrap();
+        let low = df.column("low").unwrap().as_series().unwrap();
+        let close = df.column("close").unwrap().as_series().unwrap();
+        let z = cdl_z(open, high, low, close, CdlZOptions {
+            ..Default::default()
+        }).unwrap();
+        assert_eq!(
+            z.get_column_names(),
+            vec!["open_Z_30_1", "high_Z_30_1", "low_Z_30_1", "close_Z_30_1"]
+        );
+        assert_eq!(z.column("close_Z_30_1").unwrap().f64().unwrap().get(28), None);
+
+        let z = cdl_z(open, high, low, close, CdlZOptions {
+            full: true,
+            ..Default::default()
+        }).unwrap();
+        let close_z = z.column("close_Za").unwrap().f64().unwrap();
+        assert!(close_z.mean().unwrap().abs() < 1e-9);
+    }
+}
@@ -0,0 +1,122 @@
+use crate::utils::core::{get_offset, same_length, fill_missing_frame, FillPolicy};
+use crate::utils::missing::prepare;
+use crate::utils::error::CommandResult;
+use polars::prelude::*;
+
+
+/// Heikin-Ashi candles. HA_open is recursive: the midpoint of the previous
+/// HA candle's body, seeded with the midpoint of the first real candle.
+pub fn ha(
+    open: &Series,
+    high: &Series,
+    low: &Series,
+    close: &Series,
+    offset: Option<i32>,
+    fill: Option<FillPolicy>
+) -> CommandResult<DataFrame> {
+    let offset = get_offset(offset);
+
+    let len = same_length(&[close, open, high, low])?;
+
+    let prepared = prepare(&[open, high, low, close])?;
+    let (open, high, low, close) = (
+        &prepared.inputs[0],
+        &prepared.inputs[1],
+        &prepared.inputs[2],
+        &prepared.inputs[3]
+    );
+
+    let mut ha_open = Vec::with_capacity(len);
+    let mut ha_high = Vec::with_capacity(len);
+    let mut ha_low = Vec::with_capacity(len);
+    let mut ha_close = Vec::with_capacity(len);
+
+    // the previous HA body, reset by a bar with missing prices
+    let mut prev: Option<(f64, f64)> = None;
+    let bars = open.f64()?.into_iter()
+        .zip(high.f64()?)
+        .zip(low.f64()?)
+        .zip(close.f64()?);
+    for (((o, h), l), c) in bars {
+        let (o, h, l, c) = match (o, h, l, c) {
+            (Some(o), Some(h), Some(l), Some(c)) => (o, h, l, c),
+            _ => {
+                ha_open.push(None);
+                ha_high.push(None);
+                ha_low.push(None);
+                ha_close.push(None);
+                prev = None;
+                continue;
+            }
+        };
+        let bar_close = 0.25 * (o + h + l + c);
+        let bar_open = match prev {
+            Some((prev_open, prev_close)) => 0.5 * (prev_open + prev_close),
+            None => 0.5 * (o + c)
+        };
+        ha_open.push(Some(bar_open));
+        ha_high.push(Some(h.max(bar_open).max(bar_close)));
+        ha_low.push(Some(l.min(bar_open).min(bar_close)));
+        ha_close.push(Some(bar_close));
+        prev = Some((bar_open, bar_close));
+    }
+
+    let columns = vec![
+        Series::new("HA_open".into(), ha_open),
+        Series::new("HA_high".into(), ha_high),
+        Series::new("HA_low".into(), ha_low),
+        Series::new("HA_close".into(), ha_close),
+    ];
+
+    let columns = columns
+        .into_iter()
+        .map(|column| {
+            Ok(prepared.restore(column, offset)?.into_column())
+        })
+        .collect::<CommandResult<Vec<_>>>()?;
+
+    fill_missing_frame(DataFrame::new(columns)?, fill)
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::utils::data_loader::{
+        csv_to_dataframe, 
+        set_column_names, 
+        combine_date_time, 
+        convert_to_naive_datetime
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_ha() {
+        let mut df = csv_to_dataframe(
+            "data/AUDNZD1.csv", 
+            false
+        ).unwrap();
+        let _ = set_column_names(
+            &mut df, 
+            vec!["date", "time", "open", "high", "low", "close", "volume"]
+        );
+        let df = combine_date_time(&df).unwrap();
+        let df = convert_to_naive_datetime(
+            &df, 
+            "%Y.%m.%d %H:%M"
+        ).unwrap();
+
+        let open = df.column("open").unwrap().as_series().unwrap();
+        let high = df.column("high").unwrap().as_series().unwrap();
+        let low = df.column("low").unwrap().as_series().unwrap();
+        let close = df.column("close").unwrap().as_series().unwrap();
+        let ha = ha(open, high, low, close, None, None).unwrap();
+        assert_eq!(ha.get_column_names(), vec!["HA_open", "HA_high", "HA_low", "HA_close"]);
+        let ha_open = ha.column("HA_open").unwrap().f64().unwrap();
+        let ha_close = ha.column("HA_close").unwrap().f64().unwrap();
+        assert!((ha_open.get(0).unwrap() - 0.5 * (1.08083 + 1.08086)).abs() < 1e-15);
+        let expected = 0.5 * (ha_open.get(0).unwrap() + ha_close.get(0).unwrap());
+        assert!((ha_open.get(1).unwrap() - expected).abs() < 1e-15);
+    }
+}
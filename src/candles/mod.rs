@@ -0,0 +1,6 @@
+pub mod ha;
+pub mod cdl_doji;
+pub mod cdl_inside;
+pub mod cdl_z;
+pub mod settings;
+pub mod cdl_pattern;
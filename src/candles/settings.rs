@@ -0,0 +1,68 @@
+/// The part of a candle a setting is measured on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RangeType {
+    /// Distance between open and close.
+    RealBody,
+    /// Distance between high and low.
+    HighLow,
+    /// Upper plus lower shadow, averaged per shadow.
+    Shadows,
+}
+
+
+/// A threshold as used by TA-Lib: `factor` times the average `range` of the
+/// `period` bars before the candle, or of the candle itself when `period` is 0.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CandleSetting {
+    pub range: RangeType,
+    pub period: usize,
+    pub factor: f64,
+}
+
+
+impl CandleSetting {
+    pub fn new(range: RangeType, period: usize, factor: f64) -> Self {
+        CandleSetting { range, period, factor }
+    }
+}
+
+
+/// Body and shadow thresholds of the candlestick patterns, defaulting to the
+/// TA-Lib values.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct CandleSettings {
+    pub body_long: CandleSetting,
+    pub body_very_long: CandleSetting,
+    pub body_short: CandleSetting,
+    pub body_doji: CandleSetting,
+    pub shadow_long: CandleSetting,
+    pub shadow_very_long: CandleSetting,
+    pub shadow_short: CandleSetting,
+    pub shadow_very_short: CandleSetting,
+    pub near: CandleSetting,
+    pub far: CandleSetting,
+    pub equal: CandleSetting,
+}
+
+
+impl Default for CandleSettings {
+    fn default() -> Self {
+        CandleSettings {
+            body_long: CandleSetting::new(RangeType::RealBody, 10, 1.0),
+            body_very_long: CandleSetting::new(RangeType::RealBody, 10, 3.0),
+            body_short: CandleSetting::new(RangeType::RealBody, 10, 1.0),
+            body_doji: CandleSetting::new(RangeType::HighLow, 10, 0.1),
+            shadow_long: CandleSetting::new(RangeType::RealBody, 0, 1.0),
+            shadow_very_long: CandleSetting::new(RangeType::RealBody, 0, 2.0),
+            shadow_short: CandleSetting::new(RangeType::Shadows, 10, 1.0),
+            shadow_very_short: CandleSetting::new(RangeType::HighLow, 10, 0.1),
+            near: CandleSetting::new(RangeType::HighLow, 5, 0.2),
+            far: CandleSetting::new(RangeType::HighLow, 5, 0.6),
+            equal: CandleSetting::new(RangeType::HighLow, 5, 0.05),
+        }
+    }
+}
@@ -0,0 +1,682 @@
+use crate::candles::cdl_doji::{cdl_doji, DojiOptions};
+use crate::candles::cdl_inside::cdl_inside;
+use crate::candles::cdl_pattern::{cdl_pattern_with, CandlePattern, CdlPatternOptions};
+use crate::candles::cdl_z::{cdl_z, CdlZOptions};
+use crate::candles::ha::ha;
+use crate::momentum::rsi::{rsi, RsiOptions};
+use crate::momentum::slope::slope;
+use crate::overlap::dema::{dema, DemaOptions};
+use crate::overlap::ema::{ema, EmaOptions};
+use crate::overlap::linreg::{linreg, LinregOptions};
+use crate::overlap::rma::{rma, RmaOptions};
+use crate::overlap::sma::{sma, SmaOptions};
+use crate::performance::drawdown::drawdown;
+use crate::performance::log_return::log_return;
+use crate::performance::percent_return::percent_return;
+use crate::statistics::entropy::entropy;
+use crate::statistics::kurtosis::kurtosis;
+use crate::statistics::mad::mad;
+use crate::statistics::median::median;
+use crate::statistics::quantile::quantile;
+use crate::statistics::skew::skew;
+use crate::statistics::stdev::stdev;
+use crate::statistics::variance::variance;
+use crate::statistics::zscore::zscore;
+use crate::utils::core::{fill_missing, get_drift, get_length, get_offset, FillPolicy};
+use crate::utils::error::{CommandError, CommandResult};
+use crate::utils::missing::{missing_policy, with_missing_policy, MissingPolicy};
+use crate::utils::validate::{self, validation, with_validation};
+use crate::volatility::atr::{atr, AtrOptions};
+use crate::volatility::true_range::true_range;
+use crate::volume::aobv::{aobv, AobvOptions};
+use crate::volume::nvi::nvi;
+use crate::volume::obv::obv;
+use crate::volume::pvi::pvi;
+use polars::prelude::*;
+
+
+fn to_polars(error: CommandError) -> PolarsError {
+    match error {
+        CommandError::Polars(error) => error,
+        error => PolarsError::ComputeError(error.to_string().into()),
+    }
+}
+
+
+/// Runs a batch indicator on the whole input columns once the plan executes.
+/// It is applied group-wise, so the engine never hands it a partial column and
+/// `.over([col("symbol")])` computes it separately per symbol. The missing
+/// value policy and validation mode are those in effect when it is built.
+fn indicator<F>(first: Expr, rest: &[Expr], name: String, dtype: DataType, f: F) -> Expr
+where
+    F: Fn(&[Series]) -> CommandResult<Series> + Send + Sync + 'static,
+{
+    let (policy, validation) = (missing_policy(), validation());
+    first
+        .apply_many(
+            move |columns| {
+                let series: Vec<Series> = columns
+                    .iter()
+                    .map(|column| column.as_materialized_series().clone())
+                    .collect();
+                let result = with_missing_policy(policy, || {
+                    with_validation(validation, || f(&series))
+                });
+                let result = result.map_err(to_polars)?;
+                Ok(Some(result.into_column()))
+            },
+            rest,
+            GetOutput::from_type(dtype),
+        )
+        .alias(name)
+}
+
+
+/// Like `indicator` for indicators with several outputs, which are returned
+/// as a struct of `fields`; use `unnest` to spread them into columns.
+fn struct_indicator<F>(first: Expr, rest: &[Expr], name: &str, fields: Vec<Field>, f: F) -> Expr
+where
+    F: Fn(&[Series]) -> CommandResult<DataFrame> + Send + Sync + 'static,
+{
+    let struct_name: PlSmallStr = name.into();
+    indicator(first, rest, name.to_string(), DataType::Struct(fields), move |series| {
+        Ok(f(series)?.into_struct(struct_name.clone()).into_series())
+    })
+}
+
+
+fn float_fields<S: AsRef<str>>(names: &[S]) -> Vec<Field> {
+    names
+        .iter()
+        .map(|name| Field::new(name.as_ref().into(), DataType::Float64))
+        .collect()
+}
+
+
+fn shifted(expr: Expr, offset: Option<i32>) -> Expr {
+    match get_offset(offset) {
+        0 => expr,
+        offset => expr.shift(lit(offset as i64)),
+    }
+}
+
+
+/// Native expressions match the eager indicators under the default missing
+/// value policy only; the policy is read when the expression is built.
+fn native() -> bool {
+    missing_policy() == MissingPolicy::default()
+}
+
+
+/// The input of a native expression as Float64 with NaN turned into nulls,
+/// as the eager indicators see it.
+fn prepared(expr: Expr) -> Expr {
+    expr.cast(DataType::Float64).fill_nan(lit(NULL))
+}
+
+
+/// Checks every group of a native expression has the `needed` rows the
+/// eager indicator asks for.
+fn checked(expr: Expr, needed: usize) -> Expr {
+    let validation = validation();
+    expr.apply(
+        move |column| {
+            with_validation(validation, || validate::min_rows(column.len(), needed))
+                .map_err(to_polars)?;
+            Ok(Some(column))
+        },
+        GetOutput::same_type(),
+    )
+}
+
+
+/// Fills a native expression the way the batch indicators fill their output,
+/// group-wise like `indicator`.
+fn filled(expr: Expr, fill: Option<FillPolicy>) -> Expr {
+    match fill {
+        None => expr,
+        Some(fill) => expr.apply(
+            move |column| {
+                let series = column.as_materialized_series().clone();
+                let filled = fill_missing(series, Some(fill)).map_err(to_polars)?;
+                Ok(Some(filled.into_column()))
+            },
+            GetOutput::same_type(),
+        ),
+    }
+}
+
+
+/// EMA; with `mamode` "ema" this is a native `ewm_mean` expression, unless a
+/// missing value policy other than the default asks for the eager indicator
+/// or the eager indicator is needed to report an invalid length.
+pub fn ema_expr(close: Expr, options: EmaOptions) -> Expr {
+    let name = options.name();
+    match (options.seeded(), validate::length("length", options.length, 1, 10)) {
+        (true, _) | (_, Err(_)) => indicator(close, &[], name, DataType::Float64, move |s| {
+            ema(&s[0], options.clone())
+        }),
+        _ if !native() => indicator(close, &[], name, DataType::Float64, move |s| {
+            ema(&s[0], options.clone())
+        }),
+        (_, Ok(length)) => {
+            let close = checked(prepared(close), length as usize);
+            let ema = close.ewm_mean(EWMOptions {
+                alpha: 2.0 / (length as f64 + 1.0),
+                adjust: options.adjust,
+                bias: false,
+                min_periods: 0,
+                ignore_nulls: false,
+            });
+            filled(shifted(ema, options.offset), options.fill).alias(name)
+        }
+    }
+}
+
+
+pub fn dema_expr(close: Expr, options: DemaOptions) -> Expr {
+    indicator(close, &[], options.name(), DataType::Float64, move |s| {
+        dema(&s[0], options.clone())
+    })
+}
+
+
+pub fn sma_expr(close: Expr, options: SmaOptions) -> Expr {
+    let length = match validate::length("length", options.length, 1, 10) {
+        Ok(length) if native() => length,
+        _ => return indicator(close, &[], options.name(), DataType::Float64, move |s| {
+            sma(&s[0], options.clone())
+        })
+    };
+    let close = checked(prepared(close), length as usize);
+    let sma = close.rolling_mean(RollingOptionsFixedWindow {
+        window_size: length as usize,
+        min_periods: length as usize,
+        ..Default::default()
+    });
+    filled(shifted(sma, options.offset), options.fill).alias(options.name())
+}
+
+
+pub fn rma_expr(close: Expr, options: RmaOptions) -> Expr {
+    let length = match validate::length("length", options.length, 1, 10) {
+        Ok(length) if native() => length,
+        _ => return indicator(close, &[], options.name(), DataType::Float64, move |s| {
+            rma(&s[0], options.clone())
+        })
+    };
+    let close = checked(prepared(close), length as usize);
+    let rma = close.ewm_mean(EWMOptions {
+        alpha: 1.0 / length as f64,
+        adjust: true,
+        bias: false,
+        min_periods: length as usize,
+        ignore_nulls: false,
+    });
+    filled(shifted(rma, options.offset), options.fill).alias(options.name())
+}
+
+
+pub fn linreg_expr(close: Expr, options: LinregOptions) -> Expr {
+    indicator(close, &[], options.name(), DataType::Float64, move |s| {
+        linreg(&s[0], options.clone())
+    })
+}
+
+
+pub fn slope_expr(
+    close: Expr,
+    length: Option<i32>,
+    as_angle: bool,
+    to_degrees: bool,
+    offset: Option<i32>,
+    fill: Option<FillPolicy>
+) -> Expr {
+    let name = match length {
+        Some(length) if length > 1 => format!("SLOPE_{}", length),
+        _ => "SLOPE_14".to_string()
+    };
+    indicator(close, &[], name, DataType::Float64, move |s| {
+        slope(&s[0], length, as_angle, to_degrees, offset, fill)
+    })
+}
+
+
+pub fn rsi_expr(close: Expr, options: RsiOptions) -> Expr {
+    indicator(close, &[], options.name(), DataType::Float64, move |s| {
+        rsi(&s[0], options.clone())
+    })
+}
+
+
+pub fn true_range_expr(
+    high: Expr,
+    low: Expr,
+    close: Expr,
+    drift: Option<i32>,
+    offset: Option<i32>,
+    fill: Option<FillPolicy>
+) -> Expr {
+    let name = format!("TRUERANGE_{}", get_drift(drift));
+    indicator(high, &[low, close], name, DataType::Float64, move |s| {
+        true_range(&s[0], &s[1], &s[2], drift, offset, fill)
+    })
+}
+
+
+pub fn atr_expr(high: Expr, low: Expr, close: Expr, options: AtrOptions) -> Expr {
+    indicator(high, &[low, close], options.name(), DataType::Float64, move |s| {
+        atr(&s[0], &s[1], &s[2], options.clone())
+    })
+}
+
+
+pub fn obv_expr(
+    close: Expr,
+    volume: Expr,
+    offset: Option<i32>,
+    fill: Option<FillPolicy>
+) -> Expr {
+    indicator(close, &[volume], "OBV".to_string(), DataType::Float64, move |s| {
+        obv(&s[0], &s[1], offset, fill)
+    })
+}
+
+
+pub fn nvi_expr(
+    close: Expr,
+    volume: Expr,
+    length: Option<i32>,
+    initial: Option<f64>,
+    offset: Option<i32>,
+    fill: Option<FillPolicy>
+) -> Expr {
+    let name = format!("NVI_{}", get_length(length, 1));
+    indicator(close, &[volume], name, DataType::Float64, move |s| {
+        nvi(&s[0], &s[1], length, initial, offset, fill)
+    })
+}
+
+
+pub fn pvi_expr(
+    close: Expr,
+    volume: Expr,
+    length: Option<i32>,
+    initial: Option<f64>,
+    offset: Option<i32>,
+    fill: Option<FillPolicy>
+) -> Expr {
+    let name = format!("PVI_{}", get_length(length, 1));
+    indicator(close, &[volume], name, DataType::Float64, move |s| {
+        pvi(&s[0], &s[1], length, initial, offset, fill)
+    })
+}
+
+
+pub fn aobv_expr(close: Expr, volume: Expr, options: AobvOptions) -> Expr {
+    let names = options.names();
+    let mut fields = float_fields(&names[..5]);
+    fields.extend(names[5..].iter().map(|name| Field::new(name.into(), DataType::Int32)));
+    struct_indicator(close, &[volume], "AOBV", fields, move |s| {
+        aobv(&s[0], &s[1], options.clone())
+    })
+}
+
+
+pub fn zscore_expr(
+    close: Expr,
+    length: Option<i32>,
+    std: Option<f64>,
+    offset: Option<i32>,
+    fill: Option<FillPolicy>
+) -> Expr {
+    let name = format!("ZS_{}", get_length(length, 30));
+    indicator(close, &[], name, DataType::Float64, move |s| {
+        zscore(&s[0], length, std, offset, fill)
+    })
+}
+
+
+pub fn stdev_expr(
+    close: Expr,
+    length: Option<i32>,
+    ddof: Option<i32>,
+    offset: Option<i32>,
+    fill: Option<FillPolicy>
+) -> Expr {
+    let name = format!("STDEV_{}", get_length(length, 30));
+    indicator(close, &[], name, DataType::Float64, move |s| {
+        stdev(&s[0], length, ddof, offset, fill)
+    })
+}
+
+
+pub fn variance_expr(
+    close: Expr,
+    length: Option<i32>,
+    ddof: Option<i32>,
+    offset: Option<i32>,
+    fill: Option<FillPolicy>
+) -> Expr {
+    let name = format!("VAR_{}", get_length(length, 30));
+    indicator(close, &[], name, DataType::Float64, move |s| {
+        variance(&s[0], length, ddof, offset, fill)
+    })
+}
+
+
+pub fn skew_expr(
+    close: Expr,
+    length: Option<i32>,
+    offset: Option<i32>,
+    fill: Option<FillPolicy>
+) -> Expr {
+    let name = format!("SKEW_{}", get_length(length, 30));
+    indicator(close, &[], name, DataType::Float64, move |s| {
+        skew(&s[0], length, offset, fill)
+    })
+}
+
+
+pub fn kurtosis_expr(
+    close: Expr,
+    length: Option<i32>,
+    offset: Option<i32>,
+    fill: Option<FillPolicy>
+) -> Expr {
+    let name = format!("KURT_{}", get_length(length, 30));
+    indicator(close, &[], name, DataType::Float64, move |s| {
+        kurtosis(&s[0], length, offset, fill)
+    })
+}
+
+
+pub fn quantile_expr(
+    close: Expr,
+    length: Option<i32>,
+    q: Option<f64>,
+    offset: Option<i32>,
+    fill: Option<FillPolicy>
+) -> Expr {
+    let prob = match q {
+        Some(q) if (0.0..=1.0).contains(&q) => q,
+        _ => 0.5
+    };
+    let name = format!("QTL_{}_{}", get_length(length, 30), prob);
+    indicator(close, &[], name, DataType::Float64, move |s| {
+        quantile(&s[0], length, q, offset, fill)
+    })
+}
+
+
+pub fn median_expr(
+    close: Expr,
+    length: Option<i32>,
+    offset: Option<i32>,
+    fill: Option<FillPolicy>
+) -> Expr {
+    let name = format!("MEDIAN_{}", get_length(length, 30));
+    indicator(close, &[], name, DataType::Float64, move |s| {
+        median(&s[0], length, offset, fill)
+    })
+}
+
+
+pub fn mad_expr(
+    close: Expr,
+    length: Option<i32>,
+    offset: Option<i32>,
+    fill: Option<FillPolicy>
+) -> Expr {
+    let name = format!("MAD_{}", get_length(length, 30));
+    indicator(close, &[], name, DataType::Float64, move |s| {
+        mad(&s[0], length, offset, fill)
+    })
+}
+
+
+pub fn entropy_expr(
+    close: Expr,
+    length: Option<i32>,
+    base: Option<f64>,
+    offset: Option<i32>,
+    fill: Option<FillPolicy>
+) -> Expr {
+    let name = format!("ENTP_{}", get_length(length, 10));
+    indicator(close, &[], name, DataType::Float64, move |s| {
+        entropy(&s[0], length, base, offset, fill)
+    })
+}
+
+
+pub fn log_return_expr(
+    close: Expr,
+    length: Option<i32>,
+    cumulative: bool,
+    offset: Option<i32>,
+    fill: Option<FillPolicy>
+) -> Expr {
+    let prefix = if cumulative { "CUM" } else { "" };
+    let name = format!("{}LOGRET_{}", prefix, get_length(length, 1));
+    indicator(close, &[], name, DataType::Float64, move |s| {
+        log_return(&s[0], length, cumulative, offset, fill)
+    })
+}
+
+
+pub fn percent_return_expr(
+    close: Expr,
+    length: Option<i32>,
+    cumulative: bool,
+    offset: Option<i32>,
+    fill: Option<FillPolicy>
+) -> Expr {
+    let prefix = if cumulative { "CUM" } else { "" };
+    let name = format!("{}PCTRET_{}", prefix, get_length(length, 1));
+    indicator(close, &[], name, DataType::Float64, move |s| {
+        percent_return(&s[0], length, cumulative, offset, fill)
+    })
+}
+
+
+pub fn drawdown_expr(
+    close: Expr,
+    offset: Option<i32>,
+    fill: Option<FillPolicy>
+) -> Expr {
+    let fields = float_fields(&["DD", "DD_PCT", "DD_LOG"]);
+    struct_indicator(close, &[], "DD", fields, move |s| {
+        drawdown(&s[0], offset, fill)
+    })
+}
+
+
+pub fn ha_expr(
+    open: Expr,
+    high: Expr,
+    low: Expr,
+    close: Expr,
+    offset: Option<i32>,
+    fill: Option<FillPolicy>
+) -> Expr {
+    let fields = float_fields(&["HA_open", "HA_high", "HA_low", "HA_close"]);
+    struct_indicator(open, &[high, low, close], "HA", fields, move |s| {
+        ha(&s[0], &s[1], &s[2], &s[3], offset, fill)
+    })
+}
+
+
+pub fn cdl_doji_expr(open: Expr, high: Expr, low: Expr, close: Expr, options: DojiOptions) -> Expr {
+    indicator(open, &[high, low, close], options.name(), DataType::Int32, move |s| {
+        cdl_doji(&s[0], &s[1], &s[2], &s[3], options.clone())
+    })
+}
+
+
+pub fn cdl_inside_expr(
+    open: Expr,
+    high: Expr,
+    low: Expr,
+    close: Expr,
+    asbool: bool,
+    offset: Option<i32>,
+    fill: Option<FillPolicy>
+) -> Expr {
+    let dtype = if asbool { DataType::Boolean } else { DataType::Int32 };
+    indicator(open, &[high, low, close], "CDL_INSIDE".to_string(), dtype, move |s| {
+        cdl_inside(&s[0], &s[1], &s[2], &s[3], asbool, offset, fill)
+    })
+}
+
+
+pub fn cdl_z_expr(open: Expr, high: Expr, low: Expr, close: Expr, options: CdlZOptions) -> Expr {
+    let fields = float_fields(&options.names());
+    struct_indicator(open, &[high, low, close], "CDL_Z", fields, move |s| {
+        cdl_z(&s[0], &s[1], &s[2], &s[3], options.clone())
+    })
+}
+
+
+pub fn cdl_pattern_expr(
+    open: Expr,
+    high: Expr,
+    low: Expr,
+    close: Expr,
+    names: &[CandlePattern],
+    options: CdlPatternOptions
+) -> Expr {
+    let names = if names.is_empty() { CandlePattern::ALL.to_vec() } else { names.to_vec() };
+    let fields = names
+        .iter()
+        .map(|pattern| Field::new(format!("CDL_{}", pattern.name()).into(), DataType::Int32))
+        .collect();
+    struct_indicator(open, &[high, low, close], "CDL_PATTERN", fields, move |s| {
+        cdl_pattern_with(&s[0], &s[1], &s[2], &s[3], &names, options.clone())
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::utils::data_loader::{
+        csv_to_dataframe,
+        set_column_names,
+        combine_date_time,
+        convert_to_naive_datetime
+    };
+
+    use super::*;
+
+    fn load() -> DataFrame {
+        let mut df = csv_to_dataframe(
+            "data/AUDNZD1.csv",
+            false
+        ).unwrap();
+        let _ = set_column_names(
+            &mut df,
+            vec!["date", "time", "open", "high", "low", "close", "volume"]
+        );
+        let df = combine_date_time(&df).unwrap();
+        convert_to_naive_datetime(
+            &df,
+            "%Y.%m.%d %H:%M"
+        ).unwrap()
+    }
+
+    fn assert_same(lazy: &DataFrame, name: &str, eager: &Series) {
+        let lazy = lazy.column(name).unwrap().as_materialized_series();
+        assert_eq!(lazy.len(), eager.len());
+        assert!(lazy.equals_missing(eager), "{} differs from the eager indicator", name);
+    }
+
+    #[test]
+    fn test_expr_matches_eager() {
+        let df = load();
+        let close = df.column("close").unwrap().as_series().unwrap();
+        let high = df.column("high").unwrap().as_series().unwrap();
+        let low = df.column("low").unwrap().as_series().unwrap();
+        let volume = df.column("volume").unwrap().as_series().unwrap();
+
+        let result = df.clone().lazy().with_columns([
+            ema_expr(col("close"), EmaOptions::default()),
+            sma_expr(col("close"), SmaOptions { length: Some(5), offset: Some(1), ..Default::default() }),
+            atr_expr(col("high"), col("low"), col("close"), AtrOptions::default()),
+            obv_expr(col("close"), col("volume"), None, None),
+            zscore_expr(col("close"), None, None, None, None),
+        ]).collect().unwrap();
+
+        assert_same(&result, "EMA_10", &ema(close, EmaOptions::default()).unwrap());
+        let sma = crate::overlap::sma::sma(close, SmaOptions { length: Some(5), offset: Some(1), ..Default::default() }).unwrap();
+        assert_same(&result, "SMA_5", &sma);
+        assert_same(&result, "ATRr_14", &atr(high, low, close, AtrOptions::default()).unwrap());
+        assert_same(&result, "OBV", &obv(close, volume, None, None).unwrap());
+        assert_same(&result, "ZS_30", &zscore(close, None, None, None, None).unwrap());
+
+        let ema = ema_expr(col("close"), EmaOptions { mamode: Some("ema".to_string()), ..Default::default() });
+        let result = df.clone().lazy().select([ema]).collect().unwrap();
+        let eager = crate::overlap::ema::ema(close, EmaOptions {
+            mamode: Some("ema".to_string()),
+            ..Default::default()
+        }).unwrap();
+        assert_same(&result, "EMA_10", &eager);
+
+        let options = SmaOptions { fill: Some(FillPolicy::Backward), ..Default::default() };
+        let result = df.clone().lazy().select([sma_expr(col("close"), options.clone())]).collect().unwrap();
+        let eager = crate::overlap::sma::sma(close, options).unwrap();
+        assert_eq!(eager.null_count(), 0);
+        assert_same(&result, "SMA_10", &eager);
+    }
+
+    #[test]
+    fn test_expr_nan_input() {
+        let close = Series::new("close".into(), [1.0, f64::NAN, 3.0, 4.0]);
+        let df = DataFrame::new(vec![close.clone().into_column()]).unwrap();
+
+        let ema_options = EmaOptions { length: Some(2), mamode: Some("ema".to_string()), ..Default::default() };
+        let sma_options = SmaOptions { length: Some(2), ..Default::default() };
+        let rma_options = RmaOptions { length: Some(2), ..Default::default() };
+        let result = df.lazy().select([
+            ema_expr(col("close"), ema_options.clone()),
+            sma_expr(col("close"), sma_options.clone()),
+            rma_expr(col("close"), rma_options.clone()),
+        ]).collect().unwrap();
+
+        // NaN is missing in both, so no NaN reaches the later rows
+        let eager = ema(&close, ema_options).unwrap();
+        let values: Vec<_> = eager.f64().unwrap().into_iter().map(|v| v.map(|v| (v * 1e3).round() / 1e3)).collect();
+        assert_eq!(values, [Some(1.0), None, Some(2.714), Some(3.571)]);
+        assert_same(&result, "EMA_2", &eager);
+        assert_same(&result, "SMA_2", &sma(&close, sma_options).unwrap());
+        assert_same(&result, "RMA_2", &rma(&close, rma_options).unwrap());
+    }
+
+    #[test]
+    fn test_struct_expr() {
+        let df = load();
+        let close = df.column("close").unwrap().as_series().unwrap();
+
+        let result = df.clone().lazy()
+            .select([drawdown_expr(col("close"), None, None)])
+            .unnest(["DD"])
+            .collect()
+            .unwrap();
+        let eager = drawdown(close, None, None).unwrap();
+        assert_eq!(result.get_column_names(), eager.get_column_names());
+        assert!(result.equals_missing(&eager));
+    }
+
+    #[test]
+    fn test_expr_validation() {
+        let df = load().slice(0, 3);
+        let options = SmaOptions { length: Some(5), ..Default::default() };
+        assert!(df.clone().lazy().select([sma_expr(col("close"), options.clone())]).collect().is_err());
+        let invalid = RmaOptions { length: Some(0), ..Default::default() };
+        assert!(df.clone().lazy().select([rma_expr(col("close"), invalid)]).collect().is_err());
+
+        // the mode in effect when the expression is built applies
+        let sma = validate::with_validation(validate::Validation::Lenient, || sma_expr(col("close"), options));
+        let result = df.lazy().select([sma]).collect().unwrap();
+        assert_eq!(result.column("SMA_5").unwrap().null_count(), 3);
+    }
+}
@@ -1,3 +1,15 @@
-mod utils;
-mod volatility;
-mod overlap;
+pub mod utils;
+pub mod volatility;
+pub mod overlap;
+pub mod volume;
+pub mod statistics;
+pub mod momentum;
+pub mod performance;
+pub mod candles;
+pub mod accessor;
+pub mod expr;
+pub mod streaming;
+pub mod batch;
+pub mod registry;
+
+pub use expr::*;
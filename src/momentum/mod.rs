@@ -0,0 +1 @@
+pub mod slope;pub mod rsi;
@@ -0,0 +1,147 @@
+use crate::overlap::rma::{rma, RmaOptions};
+use crate::utils::core::{get_offset, shifted_lookback, fill_missing, FillPolicy};
+use crate::utils::missing::prepare;
+use crate::utils::error::CommandResult;
+use crate::utils::validate;
+use polars::prelude::*;
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct RsiOptions {
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub length: Option<i32>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub scalar: Option<f64>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub drift: Option<i32>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub offset: Option<i32>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub fill: Option<FillPolicy>,
+}
+
+
+impl Default for RsiOptions {
+    fn default() -> Self {
+        RsiOptions {
+            length: Some(14),
+            scalar: Some(100.0),
+            drift: None,
+            offset: None,
+            fill: None,
+        }
+    }
+}
+
+
+impl RsiOptions {
+    /// The output name, e.g. `RSI_14`.
+    pub fn name(&self) -> String {
+        let length = match self.length {
+            Some(length) if length > 0 => length,
+            _ => 14
+        };
+        format!("RSI_{}", length)
+    }
+
+    /// Leading bars without `length` changes, like TA-Lib's
+    /// `TA_RSI_Lookback`, shifted by the offset.
+    pub fn lookback(&self) -> usize {
+        let length = validate::resolved(self.length, 1, 14);
+        let drift = validate::resolved(self.drift, 1, 1);
+        shifted_lookback(length + drift - 1, self.offset)
+    }
+}
+
+
+/// Relative strength index: the Wilder averages of the up and down moves
+/// over `drift` bars, scaled so that only up moves give `scalar`.
+pub fn rsi(
+    close: &Series,
+    options: RsiOptions
+) -> CommandResult<Series> {
+    let prepared = prepare(&[close])?;
+    let close = &prepared.inputs[0];
+    let close = close.cast(&DataType::Float64)?;
+
+    let length = validate::length("length", options.length, 1, 14)?;
+
+    let name = options.name();
+    let scalar = options.scalar.unwrap_or(100.0);
+    let drift = validate::length("drift", options.drift, 1, 1)?;
+    validate::min_rows(close.len(), (length + drift) as usize)?;
+    let offset = get_offset(options.offset);
+
+    let change = match &close - &close.shift(drift as i64) {
+        Ok(change) => change,
+        Err(_) => return Err("Failed to calculate difference".into())
+    };
+    let change = change.f64()?;
+
+    let positive = change
+        .apply_values(|v| if v < 0.0 { 0.0 } else { v })
+        .into_series();
+    let negative = change
+        .apply_values(|v| if v > 0.0 { 0.0 } else { v })
+        .into_series();
+
+    let rma_options = RmaOptions {
+        length: Some(length),
+        ..Default::default()
+    };
+    let positive_avg = rma(&positive, rma_options.clone())?;
+    let negative_avg = rma(&negative, rma_options)?;
+
+    let total = match positive_avg.clone() + abs(&negative_avg)? {
+        Ok(total) => total,
+        Err(_) => return Err("Failed to calculate RSI".into())
+    };
+    // the share of up moves is divided first so rounding cannot take it
+    // past one, and the RSI past scalar
+    let rsi = match positive_avg / total {
+        Ok(share) => share * scalar,
+        Err(_) => return Err("Failed to calculate RSI".into())
+    };
+
+    let rsi = prepared.restore(rsi, offset)?;
+
+    let rsi = fill_missing(rsi, options.fill)?;
+
+    Ok(rsi.with_name(name.into()))
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_rsi() {
+        let close = Series::new("close".into(), vec![1.0, 2.0, 3.0, 2.0, 4.0]);
+        let rsi = rsi(&close, RsiOptions { length: Some(2), ..Default::default() }).unwrap();
+        assert_eq!(rsi.name().as_str(), "RSI_2");
+        let rsi = rsi.f64().unwrap();
+        assert_eq!(rsi.get(1), None);
+        // two up moves and no down move
+        assert_eq!(rsi.get(2), Some(100.0));
+        assert!(rsi.get(3).unwrap() < 100.0);
+    }
+
+    #[test]
+    fn test_rsi_flat() {
+        use crate::utils::missing::{with_missing_policy, MissingPolicy, WarmUpValue};
+
+        // no move at all is 0 / 0, which takes the policy's missing value
+        let close = Series::new("close".into(), vec![2.0; 5]);
+        let options = RsiOptions { length: Some(2), ..Default::default() };
+        let values: Vec<_> = rsi(&close, options.clone()).unwrap().f64().unwrap().into_iter().collect();
+        assert_eq!(values, [None; 5]);
+
+        let policy = MissingPolicy { warm_up: WarmUpValue::NaN, ..Default::default() };
+        let rsi = with_missing_policy(policy, || rsi(&close, options)).unwrap();
+        assert!(rsi.f64().unwrap().into_iter().all(|value| value.unwrap().is_nan()));
+    }
+}
@@ -0,0 +1,78 @@
+use crate::utils::core::{fill_missing, FillPolicy};
+use crate::overlap::linreg::{linreg, LinregOptions};
+use crate::utils::error::CommandResult;
+use crate::utils::validate;
+use polars::prelude::*;
+
+
+/// Least squares slope of close over `length` bars, optionally as an angle.
+pub fn slope(
+    close: &Series,
+    length: Option<i32>,
+    as_angle: bool,
+    to_degrees: bool,
+    offset: Option<i32>,
+    fill: Option<FillPolicy>
+) -> CommandResult<Series> {
+    let length = validate::length("length", length, 2, 14)?;
+
+    let slope = linreg(close, LinregOptions {
+        length: Some(length),
+        offset,
+        slope: true,
+        ..Default::default()
+    })?;
+
+    let slope = if as_angle {
+        let angle = slope.f64()?.apply_values(f64::atan);
+        if to_degrees {
+            angle.apply_values(f64::to_degrees).into_series()
+        } else {
+            angle.into_series()
+        }
+    } else {
+        slope
+    };
+
+    let slope = fill_missing(slope, fill)?;
+
+    Ok(slope.with_name(format!("SLOPE_{}", length).into()))
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::utils::data_loader::{
+        csv_to_dataframe, 
+        set_column_names, 
+        combine_date_time, 
+        convert_to_naive_datetime
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_slope() {
+        let mut df = csv_to_dataframe(
+            "data/AUDNZD1.csv", 
+            false
+        ).unwrap();
+        let _ = set_column_names(
+            &mut df, 
+            vec!["date", "time", "open", "high", "low", "close", "volume"]
+        );
+        let df = combine_date_time(&df).unwrap();
+        let df = convert_to_naive_datetime(
+            &df, 
+            "%Y.%m.%d %H:%M"
+        ).unwrap();
+
+        let close = df.column("close").unwrap().as_series().unwrap();
+        // over two bars the fitted slope is the one bar difference
+        let slope = slope(close, Some(2), false, false, None, None).unwrap();
+        assert_eq!(slope.name().as_str(), "SLOPE_2");
+        let slope = slope.f64().unwrap();
+        assert!((slope.get(1).unwrap() - (1.08069 - 1.08086)).abs() < 1e-12);
+    }
+}
@@ -1,13 +1,21 @@
 use crate::overlap::ema::{ema, EmaOptions};
-use crate::utils::core::get_offset;
+use crate::utils::core::{get_length, get_offset, shifted_lookback, fill_missing, FillPolicy};
+use crate::utils::missing::{prepare, shifted};
 use crate::utils::error::CommandResult;
+use crate::utils::validate;
 use polars::prelude::*;
 
 
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct DemaOptions {
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub length: Option<i32>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub offset: Option<i32>,
-    pub fillna: bool,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub fill: Option<FillPolicy>,
 }
 
 
@@ -16,28 +24,38 @@ impl Default for DemaOptions {
         DemaOptions {
             length: Some(10),
             offset: None,
-            fillna: false,
+            fill: None,
         }
     }
 }
 
 
+impl DemaOptions {
+    /// The output name, e.g. `DEMA_10`.
+    pub fn name(&self) -> String {
+        format!("DEMA_{}", get_length(self.length, 10))
+    }
+
+    /// Leading bars before the EMA of the EMA is seeded from a full window,
+    /// like TA-Lib's `TA_DEMA_Lookback`, shifted by the offset. Only the
+    /// first `length - 1` are missing, the others are unconverged.
+    pub fn lookback(&self) -> usize {
+        shifted_lookback(2 * (validate::resolved(self.length, 1, 10) - 1), self.offset)
+    }
+}
+
+
 pub fn dema(
     close: &Series,
     options: DemaOptions
 ) -> CommandResult<Series> {
+    let prepared = prepare(&[close])?;
+    let close = &prepared.inputs[0];
+    let name = options.name();
     let close = close.clone();
 
-    let length = match options.length {
-        Some(length) => {
-            if length > 0 {
-                length
-            } else {
-                14
-            }
-        },
-        None => 14
-    };
+    let length = validate::length("length", options.length, 1, 10)?;
+    validate::min_rows(close.len(), 2 * length as usize - 1)?;
 
     let offset = get_offset(options.offset);
 
@@ -46,26 +64,50 @@ pub fn dema(
         ..Default::default()
     })?;
 
-    let ema2 = ema(&ema1, EmaOptions {
+    let dema = double(&ema1, length)?;
+
+    let dema = prepared.restore(dema, offset)?;
+
+    let dema = fill_missing(dema, options.fill)?;
+
+    Ok(dema.with_name(name.into()))
+}
+
+
+/// DEMA from `ema1`, the EMA of close with the same length and the default
+/// options, for callers that already have it. The missing value policy was
+/// applied when `ema1` was computed, so its gaps are kept as they are.
+pub(crate) fn dema_from_ema(ema1: &Series, options: DemaOptions) -> CommandResult<Series> {
+    let name = options.name();
+    let length = validate::length("length", options.length, 1, 10)?;
+    validate::min_rows(ema1.len(), 2 * length as usize - 1)?;
+    let offset = get_offset(options.offset);
+
+    let dema = double(ema1, length)?;
+
+    let dema = shifted(dema, offset)?;
+
+    let dema = fill_missing(dema, options.fill)?;
+
+    Ok(dema.with_name(name.into()))
+}
+
+
+/// `2 * ema1 - ema(ema1)`.
+fn double(ema1: &Series, length: i32) -> CommandResult<Series> {
+    let ema2 = ema(ema1, EmaOptions {
         length: Some(length),
         ..Default::default()
     })?;
 
-    let dema = match ema1 * 2.0 - ema2 {
-        Ok(dema) => dema,
-        Err(_) => return Err("Failed to calculate DEMA".into())
-    };
-
-    let dema = if offset != 0 {
-        dema.shift(offset as i64)
-    } else {
-        dema
-    };
-
-    Ok(dema)
+    match ema1 * 2.0 - ema2 {
+        Ok(dema) => Ok(dema),
+        Err(_) => Err("Failed to calculate DEMA".into())
+    }
 }
 
 
+#[cfg(test)]
 mod tests {
 
     use crate::utils::data_loader::{
@@ -77,6 +119,17 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_dema_unset_options() {
+        // unset options resolve to the default ones
+        let close = Series::new("close".into(), (0..30).map(|i| (i % 7) as f64).collect::<Vec<_>>());
+        let unset = DemaOptions { length: None, ..Default::default() };
+        assert_eq!(unset.name(), DemaOptions::default().name());
+        assert_eq!(unset.lookback(), DemaOptions::default().lookback());
+        let expected = dema(&close, DemaOptions::default()).unwrap();
+        assert!(dema(&close, unset).unwrap().equals_missing(&expected));
+    }
+
     #[test]
     fn test_dema() {
         let mut df = csv_to_dataframe(
@@ -95,7 +148,7 @@ mod tests {
 
         let close = df.column("close").unwrap().as_series().unwrap();
         let dema = dema(
-            &close,
+            close,
             DemaOptions {
                 ..Default::default()
             }
@@ -1,15 +1,23 @@
-use std::process::Command;
-
-use crate::utils::core::get_offset;
+use crate::utils::core::{get_length, get_offset, shifted_lookback, fill_missing, FillPolicy};
 use polars::prelude::*;
+use crate::utils::missing::prepare;
 use crate::utils::error::CommandResult;
+use crate::utils::validate;
+use crate::utils::math::mean;
 
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct EmaOptions {
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub length: Option<i32>,
     pub adjust: bool,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub mamode: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub offset: Option<i32>,
-    pub fillna: bool,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub fill: Option<FillPolicy>,
 }
 
 impl Default for EmaOptions {
@@ -19,95 +27,89 @@ impl Default for EmaOptions {
             adjust: false,
             mamode: Some("sma".to_string()),
             offset: None,
-            fillna: false,
+            fill: None,
         }
     }
 }
 
 
+impl EmaOptions {
+    /// The output name, e.g. `EMA_10`.
+    pub fn name(&self) -> String {
+        format!("EMA_{}", get_length(self.length, 10))
+    }
+
+    /// Leading bars before the seed, like TA-Lib's `TA_EMA_Lookback`, shifted
+    /// by the offset. With the "ema" mode they have values, unconverged.
+    pub fn lookback(&self) -> usize {
+        shifted_lookback(validate::resolved(self.length, 1, 10) - 1, self.offset)
+    }
+
+    /// Whether the ewm starts from the mean of the first `length` values,
+    /// the "sma" mode, which is also the mode without one. Other modes are
+    /// read as "ema".
+    pub(crate) fn seeded(&self) -> bool {
+        matches!(self.mamode.as_deref(), Some("sma") | None)
+    }
+}
+
+
 pub fn ema(
     close: &Series,
     options: EmaOptions
 ) -> CommandResult<Series> {
+    let prepared = prepare(&[close])?;
+    let close = &prepared.inputs[0];
+    let name = options.name();
     let mut close = close.clone();
 
-    let length = match options.length {
-        Some(length) => {
-            if length > 0 {
-                length
-            } else {
-                14
-            }
-        },
-        None => 14
-    };
-
-    let mamode = match options.mamode {
-        Some(mamode) => {
-            if mamode == "sma" || mamode == "ema" {
-                mamode
-            } else {
-                "ema".to_string()
-            }
-        },
-        None => "ema".to_string()
-    };
+    let length = validate::length("length", options.length, 1, 10)?;
+    validate::min_rows(close.len(), length as usize)?;
 
     let offset = get_offset(options.offset);
 
     let alpha = 2.0 / (length as f64 + 1.0);
 
     let ewm_options = EWMOptions {
-        alpha: alpha,
+        alpha,
         adjust: options.adjust,
         bias: false,
         min_periods: 0,
         ignore_nulls: false,
     };
     
-    if mamode == "sma" {
-        let items_to_nth: Series = close.slice(0, length as usize);
-        let sma_nth: Option<f64> = items_to_nth.mean();
-        // let nan_vec = vec![f64::NAN; (length - 1) as usize];
-        let nones: Vec<Option<f64>> = vec![None; (length - 1) as usize];
-        let rest_of_close = close
-            .slice(
-                (length - 1) as i64, 
-                close.len()
-            );
-        let rest_of_close_iter = rest_of_close
-            .f64()
-            .unwrap()
-            .into_iter()
-            .flatten()
-            .map(Some);
-        
-        // combine the NaN vector with the rest of the values
-        let mut new_close = nones
-            .into_iter()
-            .chain(rest_of_close_iter)
-            .collect::<Vec<Option<f64>>>();
-        
-        new_close[length as usize - 1] = sma_nth;
-        close = Series::new("close".into(), new_close);
+    if options.seeded() {
+        // the ewm starts from the mean of the first length values: the
+        // warm-up nulls, the seed and the rest of close are chained as chunks
+        // of one array, the rest sliced without copying. Missing values stay
+        // in place so the output lines up with the input
+        let length = length as usize;
+        let values = close.cast(&DataType::Float64)?;
+        let values = values.f64()?;
+        let mut seeded = Float64Chunked::full_null("close".into(), (length - 1).min(values.len()));
+        if values.len() >= length {
+            let seed = mean(values.slice(0, length).into_iter().flatten());
+            seeded.append(&Float64Chunked::from_slice_options("close".into(), &[seed]))?;
+            seeded.append(&values.slice(length as i64, values.len() - length))?;
+        }
+        close = seeded.into_series();
     }
-    
+
     let ema : Series = ewm_mean(
         &close,
         ewm_options
     )?;
 
-    let ema = if offset != 0 {
-        ema.shift(offset as i64)
-    } else {
-        ema
-    };
+    let ema = prepared.restore(ema, offset)?;
+
+    let ema = fill_missing(ema, options.fill)?;
 
-    Ok(ema)   
+    Ok(ema.with_name(name.into()))
 }
 
 
 
+#[cfg(test)]
 mod tests {
 
     use crate::utils::data_loader::{
@@ -116,11 +118,51 @@ mod tests {
         combine_date_time, 
         convert_to_naive_datetime
     };
-    use polars::prelude::abs;
+    use crate::utils::missing::{with_missing_policy, InteriorNulls, MissingPolicy};
     use polars::prelude::*;
     use super::ema;
     use super::EmaOptions;
 
+    #[test]
+    fn test_ema_unset_options() {
+        // unset options resolve to the default ones
+        let close = Series::new("close".into(), (0..30).map(|i| (i % 7) as f64).collect::<Vec<_>>());
+        let unset = EmaOptions { length: None, mamode: None, ..Default::default() };
+        assert_eq!(unset.name(), EmaOptions::default().name());
+        assert_eq!(unset.lookback(), EmaOptions::default().lookback());
+        let expected = ema(&close, EmaOptions::default()).unwrap();
+        assert!(ema(&close, unset).unwrap().equals_missing(&expected));
+    }
+
+    #[test]
+    fn test_ema_interior_nulls() {
+        let close = Series::new("close".into(), [Some(1.0), Some(2.0), None, Some(4.0), Some(5.0)]);
+        let options = EmaOptions { length: Some(2), ..Default::default() };
+
+        // the gap stays in place instead of pulling later values forward
+        let propagated = ema(&close, options.clone()).unwrap();
+        assert_eq!(propagated.len(), close.len());
+        assert_eq!(propagated.f64().unwrap().get(2), None);
+
+        let policy = MissingPolicy { interior: InteriorNulls::Skip, ..Default::default() };
+        let skipped = with_missing_policy(policy, || ema(&close, options.clone())).unwrap();
+        let compact = Series::new("close".into(), [1.0, 2.0, 4.0, 5.0]);
+        let expected = ema(&compact, options.clone()).unwrap();
+        assert_eq!(skipped.f64().unwrap().get(2), None);
+        assert_eq!(skipped.f64().unwrap().get(4), expected.f64().unwrap().get(3));
+
+        let policy = MissingPolicy { interior: InteriorNulls::Error, ..Default::default() };
+        assert!(with_missing_policy(policy, || ema(&close, options)).is_err());
+    }
+
+
+    #[test]
+    fn test_ema_integer_close() {
+        let close = Series::new("close".into(), [1i64, 2, 3, 4, 5]);
+        let result = ema(&close, EmaOptions { length: Some(2), ..Default::default() }).unwrap();
+        assert_eq!(result.f64().unwrap().get(1), Some(1.5));
+    }
+
 
     #[test]
     fn test_ema() {
@@ -140,7 +182,7 @@ mod tests {
 
         let close = df.column("close").unwrap().as_series().unwrap();
         let ema = ema(
-            &close,
+            close,
             EmaOptions {
                 mamode: Some("ema".to_string()),
                 ..Default::default()
@@ -167,7 +209,7 @@ mod tests {
 
         let close = df.column("close").unwrap().as_series().unwrap();
         let ema = ema(
-            &close,
+            close,
             EmaOptions {
                 mamode: Some("sma".to_string()),
                 offset: Some(2),
@@ -0,0 +1,273 @@
+use crate::utils::core::{get_offset, shifted_lookback, fill_missing, FillPolicy};
+use crate::utils::missing::prepare;
+use crate::utils::error::CommandResult;
+use crate::utils::validate;
+use polars::prelude::*;
+
+
+/// Selects the output of `linreg`, checked in the order slope, intercept,
+/// angle, r, tsf. Without any flag the fitted value at the last bar of each
+/// window is returned. As in TA-Lib the intercept is the fitted value at the
+/// first bar of the window and tsf the forecast one bar past its end.
+/// pandas-ta reports the same fit one bar earlier, its value and intercept
+/// are these less one slope and its tsf is the value here.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct LinregOptions {
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub length: Option<i32>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub offset: Option<i32>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub fill: Option<FillPolicy>,
+    pub angle: bool,
+    pub degrees: bool,
+    pub intercept: bool,
+    pub r: bool,
+    pub slope: bool,
+    pub tsf: bool,
+}
+
+
+impl Default for LinregOptions {
+    fn default() -> Self {
+        LinregOptions {
+            length: Some(14),
+            offset: None,
+            fill: None,
+            angle: false,
+            degrees: false,
+            intercept: false,
+            r: false,
+            slope: false,
+            tsf: false,
+        }
+    }
+}
+
+
+impl LinregOptions {
+    /// The output name, e.g. `LR_14` or `LRm_14` for the slope.
+    pub fn name(&self) -> String {
+        let length = match self.length {
+            Some(length) if length > 1 => length,
+            _ => 14
+        };
+        let suffix = if self.slope {
+            "m"
+        } else if self.intercept {
+            "b"
+        } else if self.angle {
+            "a"
+        } else if self.r {
+            "r"
+        } else if self.tsf {
+            "tsf"
+        } else {
+            ""
+        };
+        format!("LR{}_{}", suffix, length)
+    }
+
+    /// Leading bars without a full window, like TA-Lib's
+    /// `TA_LINEARREG_Lookback`, shifted by the offset.
+    pub fn lookback(&self) -> usize {
+        shifted_lookback(validate::resolved(self.length, 2, 14) - 1, self.offset)
+    }
+}
+
+
+/// Sums over one window with x = 1..=length. Nulls and NaNs count as zero and
+/// are tracked in `nulls` so the window can be skipped.
+#[derive(Default)]
+struct WindowSums {
+    y: f64,
+    xy: f64,
+    yy: f64,
+    nulls: usize,
+}
+
+
+impl WindowSums {
+    fn compute(values: &[Option<f64>]) -> Self {
+        let mut sums = WindowSums::default();
+        for (k, value) in values.iter().enumerate() {
+            match value {
+                Some(y) => {
+                    sums.y += y;
+                    sums.xy += (k + 1) as f64 * y;
+                    sums.yy += y * y;
+                },
+                None => sums.nulls += 1
+            }
+        }
+        sums
+    }
+
+    /// Slide the window one bar: `old` leaves at x = 1, everything else moves
+    /// down one x and `new` enters at x = length.
+    fn slide(&mut self, old: Option<f64>, new: Option<f64>, length: f64) {
+        let (old_y, new_y) = (old.unwrap_or(0.0), new.unwrap_or(0.0));
+        self.xy += length * new_y - self.y;
+        self.y += new_y - old_y;
+        self.yy += new_y * new_y - old_y * old_y;
+        self.nulls = self.nulls + usize::from(new.is_none()) - usize::from(old.is_none());
+    }
+}
+
+
+/// Rolling least squares fit of close against x = 1..=length.
+///
+/// The window sums are updated in a single pass and recomputed from scratch
+/// once every `length` bars so rounding errors cannot accumulate. Values are
+/// centred on the first valid close before summing, which keeps the sums
+/// small for prices that barely move relative to their level.
+pub fn linreg(
+    close: &Series,
+    options: LinregOptions
+) -> CommandResult<Series> {
+    let prepared = prepare(&[close])?;
+    let close = &prepared.inputs[0];
+    let length = validate::length("length", options.length, 2, 14)? as usize;
+    validate::min_rows(close.len(), length)?;
+    let offset = get_offset(options.offset);
+
+    let values: Vec<Option<f64>> = close.f64()?.into_iter().collect();
+    let center = values.iter().flatten().next().copied().unwrap_or(0.0);
+    let values: Vec<Option<f64>> = values.iter().map(|v| v.map(|v| v - center)).collect();
+
+    let l = length as f64;
+    let x_sum = 0.5 * l * (l + 1.0);
+    let x2_sum = x_sum * (2.0 * l + 1.0) / 3.0;
+    let divisor = l * x2_sum - x_sum * x_sum;
+
+    let mut sums = WindowSums::default();
+    let mut result: Vec<Option<f64>> = vec![None; values.len().min(length - 1)];
+    for t in (length - 1)..values.len() {
+        let start = t + 1 - length;
+        if start.is_multiple_of(length) {
+            sums = WindowSums::compute(&values[start..=t]);
+        } else {
+            sums.slide(values[start - 1], values[t], l);
+        }
+
+        if sums.nulls > 0 {
+            result.push(None);
+            continue;
+        }
+
+        let m = (l * sums.xy - x_sum * sums.y) / divisor;
+        let value = if options.slope {
+            m
+        } else {
+            let b = (sums.y - m * x_sum) / l;
+            if options.intercept {
+                m + b + center
+            } else if options.angle {
+                let theta = m.atan();
+                if options.degrees {
+                    theta.to_degrees()
+                } else {
+                    theta
+                }
+            } else if options.r {
+                let numerator = l * sums.xy - x_sum * sums.y;
+                let denominator = (divisor * (l * sums.yy - sums.y * sums.y)).sqrt();
+                numerator / denominator
+            } else if options.tsf {
+                m * (l + 1.0) + b + center
+            } else {
+                m * l + b + center
+            }
+        };
+        result.push(Some(value));
+    }
+
+    let linreg = Series::new(options.name().into(), result);
+
+    let linreg = prepared.restore(linreg, offset)?;
+
+    let linreg = fill_missing(linreg, options.fill)?;
+
+    Ok(linreg)
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::utils::data_loader::{
+        csv_to_dataframe, 
+        set_column_names, 
+        combine_date_time, 
+        convert_to_naive_datetime
+    };
+
+    use super::*;
+
+    /// Direct least squares fit of every window, for comparison.
+    fn refit(close: &[f64], length: usize) -> Vec<(f64, f64)> {
+        let x: Vec<f64> = (1..=length).map(|x| x as f64).collect();
+        let x_mean = x.iter().sum::<f64>() / length as f64;
+        close.windows(length).map(|y| {
+            let y_mean = y.iter().sum::<f64>() / length as f64;
+            let cov: f64 = x.iter().zip(y).map(|(x, y)| (x - x_mean) * (y - y_mean)).sum();
+            let var: f64 = x.iter().map(|x| (x - x_mean).powi(2)).sum();
+            let m = cov / var;
+            (m, y_mean - m * x_mean)
+        }).collect()
+    }
+
+    #[test]
+    fn test_linreg() {
+        let mut df = csv_to_dataframe(
+            "data/AUDNZD1.csv", 
+            false
+        ).unwrap();
+        let _ = set_column_names(
+            &mut df, 
+            vec!["date", "time", "open", "high", "low", "close", "volume"]
+        );
+        let df = combine_date_time(&df).unwrap();
+        let df = convert_to_naive_datetime(
+            &df, 
+            "%Y.%m.%d %H:%M"
+        ).unwrap();
+
+        let close = df.column("close").unwrap().as_series().unwrap();
+        let values: Vec<f64> = close.f64().unwrap().into_no_null_iter().collect();
+        let expected = refit(&values, 14);
+
+        let slope = linreg(close, LinregOptions {
+            slope: true,
+            ..Default::default()
+        }).unwrap();
+        assert_eq!(slope.name().as_str(), "LRm_14");
+        let slope = slope.f64().unwrap();
+        assert_eq!(slope.get(12), None);
+
+        let fitted = linreg(close, LinregOptions {
+            ..Default::default()
+        }).unwrap();
+        let fitted = fitted.f64().unwrap();
+
+        for (i, (m, b)) in expected.iter().enumerate() {
+            let t = i + 13;
+            assert!((slope.get(t).unwrap() - m).abs() < 1e-10, "slope diverges at {}", t);
+            assert!((fitted.get(t).unwrap() - (m * 14.0 + b)).abs() < 1e-10, "fit diverges at {}", t);
+        }
+    }
+
+    #[test]
+    fn test_linreg_r() {
+        let close = Series::new("close".into(), vec![Some(1.0), Some(2.0), Some(3.0), None, Some(5.0), Some(6.0), Some(7.0)]);
+        let r = linreg(&close, LinregOptions {
+            length: Some(3),
+            r: true,
+            ..Default::default()
+        }).unwrap();
+        let r: Vec<_> = r.f64().unwrap().into_iter().collect();
+        assert_eq!(r, vec![None, None, Some(1.0), None, None, None, Some(1.0)]);
+    }
+}
@@ -1,2 +1,5 @@
 pub mod ema;
-pub mod dema;
\ No newline at end of file
+pub mod dema;
+pub mod sma;
+pub mod linreg;
+pub mod rma;
@@ -0,0 +1,91 @@
+use crate::utils::core::{get_length, get_offset, shifted_lookback, fill_missing, FillPolicy};
+use crate::utils::missing::prepare;
+use crate::utils::error::CommandResult;
+use crate::utils::validate;
+use polars::prelude::*;
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct RmaOptions {
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub length: Option<i32>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub offset: Option<i32>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub fill: Option<FillPolicy>,
+}
+
+
+impl Default for RmaOptions {
+    fn default() -> Self {
+        RmaOptions {
+            length: Some(10),
+            offset: None,
+            fill: None,
+        }
+    }
+}
+
+
+impl RmaOptions {
+    /// The output name, e.g. `RMA_10`.
+    pub fn name(&self) -> String {
+        format!("RMA_{}", get_length(self.length, 10))
+    }
+
+    /// Leading bars before the seed, shifted by the offset.
+    pub fn lookback(&self) -> usize {
+        shifted_lookback(validate::resolved(self.length, 1, 10) - 1, self.offset)
+    }
+}
+
+
+/// Wilder's moving average, an adjusted EMA with `alpha = 1 / length` and the
+/// first `length - 1` valid values left null, like pandas-ta.
+pub fn rma(
+    close: &Series,
+    options: RmaOptions
+) -> CommandResult<Series> {
+    let prepared = prepare(&[close])?;
+    let close = &prepared.inputs[0];
+    let name = options.name();
+    let close = close.cast(&DataType::Float64)?;
+
+    let length = validate::length("length", options.length, 1, 10)?;
+    validate::min_rows(close.len(), length as usize)?;
+
+    let offset = get_offset(options.offset);
+
+    let rma = ewm_mean(&close, EWMOptions {
+        alpha: 1.0 / length as f64,
+        adjust: true,
+        bias: false,
+        min_periods: length as usize,
+        ignore_nulls: false,
+    })?;
+
+    let rma = prepared.restore(rma, offset)?;
+
+    let rma = fill_missing(rma, options.fill)?;
+
+    Ok(rma.with_name(name.into()))
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_rma() {
+        let close = Series::new("close".into(), vec![1.0, 2.0, 3.0, 4.0]);
+        let rma = rma(&close, RmaOptions { length: Some(2), ..Default::default() }).unwrap();
+        let rma = rma.f64().unwrap();
+        assert_eq!(rma.get(0), None);
+        // adjusted weights 1/2 and 1 over the first two values
+        assert!((rma.get(1).unwrap() - (1.0 * 0.5 + 2.0) / 1.5).abs() < 1e-12);
+    }
+}
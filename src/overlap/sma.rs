@@ -0,0 +1,128 @@
+use crate::utils::core::{get_length, get_offset, shifted_lookback, fill_missing, FillPolicy};
+use crate::utils::missing::prepare;
+use crate::utils::error::CommandResult;
+use crate::utils::validate;
+use polars::prelude::*;
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct SmaOptions {
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub length: Option<i32>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub offset: Option<i32>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub fill: Option<FillPolicy>,
+}
+
+
+impl Default for SmaOptions {
+    fn default() -> Self {
+        SmaOptions {
+            length: Some(10),
+            offset: None,
+            fill: None,
+        }
+    }
+}
+
+
+impl SmaOptions {
+    /// The output name, e.g. `SMA_10`.
+    pub fn name(&self) -> String {
+        format!("SMA_{}", get_length(self.length, 10))
+    }
+
+    /// Leading bars without a full window, like TA-Lib's `TA_SMA_Lookback`,
+    /// shifted by the offset.
+    pub fn lookback(&self) -> usize {
+        shifted_lookback(validate::resolved(self.length, 1, 10) - 1, self.offset)
+    }
+}
+
+
+pub fn sma(
+    close: &Series,
+    options: SmaOptions
+) -> CommandResult<Series> {
+    let prepared = prepare(&[close])?;
+    let close = &prepared.inputs[0];
+    let name = options.name();
+    let close = close.cast(&DataType::Float64)?;
+
+    let length = validate::length("length", options.length, 1, 10)?;
+    validate::min_rows(close.len(), length as usize)?;
+
+    let offset = get_offset(options.offset);
+
+    // the first length - 1 values are null, like pandas' min_periods
+    let sma = close.rolling_mean(RollingOptionsFixedWindow {
+        window_size: length as usize,
+        min_periods: length as usize,
+        ..Default::default()
+    })?;
+
+    let sma = prepared.restore(sma, offset)?;
+
+    let sma = fill_missing(sma, options.fill)?;
+
+    Ok(sma.with_name(name.into()))
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::utils::data_loader::{
+        csv_to_dataframe, 
+        set_column_names, 
+        combine_date_time, 
+        convert_to_naive_datetime
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_sma() {
+        let mut df = csv_to_dataframe(
+            "data/AUDNZD1.csv", 
+            false
+        ).unwrap();
+        let _ = set_column_names(
+            &mut df, 
+            vec!["date", "time", "open", "high", "low", "close", "volume"]
+        );
+        let df = combine_date_time(&df).unwrap();
+        let df = convert_to_naive_datetime(
+            &df, 
+            "%Y.%m.%d %H:%M"
+        ).unwrap();
+
+        let close = df.column("close").unwrap().as_series().unwrap();
+        let sma = sma(
+            close,
+            SmaOptions {
+                length: Some(3),
+                ..Default::default()
+            }
+        ).unwrap();
+        assert_eq!(sma.name().as_str(), "SMA_3");
+        let sma = sma.f64().unwrap();
+        assert_eq!(sma.get(1), None);
+        let expected = (1.08086 + 1.08069 + 1.08077) / 3.0;
+        assert!((sma.get(2).unwrap() - expected).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_sma_negative_offset() {
+        let close = Series::new("close".into(), vec![1.0, 2.0, 3.0, 4.0]);
+        let options = SmaOptions { length: Some(2), ..Default::default() };
+        let sma_back = sma(&close, SmaOptions { offset: Some(-1), ..options.clone() }).unwrap();
+        assert_eq!(sma_back.name().as_str(), "SMA_2");
+        let values: Vec<_> = sma_back.f64().unwrap().into_iter().collect();
+        // shifted one bar backward, the last bar has no later value
+        assert_eq!(values, vec![Some(1.5), Some(2.5), Some(3.5), None]);
+    }
+}
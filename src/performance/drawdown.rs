@@ -0,0 +1,89 @@
+use crate::utils::core::{get_offset, fill_missing_frame, FillPolicy};
+use crate::utils::missing::prepare;
+use crate::utils::error::CommandResult;
+use polars::prelude::*;
+
+
+/// Drawdown from the running maximum of close: absolute (DD), as a fraction
+/// of the maximum (DD_PCT) and in log terms (DD_LOG).
+pub fn drawdown(
+    close: &Series,
+    offset: Option<i32>,
+    fill: Option<FillPolicy>
+) -> CommandResult<DataFrame> {
+    let prepared = prepare(&[close])?;
+    let close = &prepared.inputs[0];
+    let offset = get_offset(offset);
+
+    let mut max_close = f64::NEG_INFINITY;
+    let (mut dd, mut dd_pct, mut dd_log) = (Vec::new(), Vec::new(), Vec::new());
+    for close in close.f64()? {
+        match close {
+            Some(close) => {
+                max_close = max_close.max(close);
+                dd.push(Some(max_close - close));
+                dd_pct.push(Some(1.0 - close / max_close));
+                dd_log.push(Some(max_close.ln() - close.ln()));
+            },
+            None => {
+                dd.push(None);
+                dd_pct.push(None);
+                dd_log.push(None);
+            }
+        }
+    }
+
+    let columns = vec![
+        Series::new("DD".into(), dd),
+        Series::new("DD_PCT".into(), dd_pct),
+        Series::new("DD_LOG".into(), dd_log),
+    ];
+
+    let columns = columns
+        .into_iter()
+        .map(|column| {
+            Ok(prepared.restore(column, offset)?.into_column())
+        })
+        .collect::<CommandResult<Vec<_>>>()?;
+
+    fill_missing_frame(DataFrame::new(columns)?, fill)
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::utils::data_loader::{
+        csv_to_dataframe, 
+        set_column_names, 
+        combine_date_time, 
+        convert_to_naive_datetime
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_drawdown() {
+        let mut df = csv_to_dataframe(
+            "data/AUDNZD1.csv", 
+            false
+        ).unwrap();
+        let _ = set_column_names(
+            &mut df, 
+            vec!["date", "time", "open", "high", "low", "close", "volume"]
+        );
+        let df = combine_date_time(&df).unwrap();
+        let df = convert_to_naive_datetime(
+            &df, 
+            "%Y.%m.%d %H:%M"
+        ).unwrap();
+
+        let close = df.column("close").unwrap().as_series().unwrap();
+        let drawdown = drawdown(close, None, None).unwrap();
+        assert_eq!(drawdown.get_column_names(), vec!["DD", "DD_PCT", "DD_LOG"]);
+        let dd = drawdown.column("DD").unwrap().f64().unwrap();
+        assert_eq!(dd.get(0), Some(0.0));
+        assert!((dd.get(1).unwrap() - (1.08086 - 1.08069)).abs() < 1e-15);
+        assert!(dd.into_no_null_iter().all(|dd| dd >= 0.0));
+    }
+}
@@ -0,0 +1,90 @@
+use crate::utils::core::{get_offset, fill_missing, FillPolicy};
+use crate::utils::missing::prepare;
+use crate::utils::error::CommandResult;
+use crate::utils::validate;
+use polars::prelude::*;
+
+
+/// Log return over `length` bars or, when `cumulative`, since the first bar.
+pub fn log_return(
+    close: &Series,
+    length: Option<i32>,
+    cumulative: bool,
+    offset: Option<i32>,
+    fill: Option<FillPolicy>
+) -> CommandResult<Series> {
+    let prepared = prepare(&[close])?;
+    let close = &prepared.inputs[0];
+    let length = validate::length("length", length, 1, 1)?;
+    validate::min_rows(close.len(), length as usize + 1)?;
+    let offset = get_offset(offset);
+    let close = close.cast(&DataType::Float64)?;
+
+    let base = if cumulative {
+        let first = close.f64()?.into_iter().flatten().next();
+        Series::new("base".into(), vec![first; close.len()])
+    } else {
+        close.shift(length as i64)
+    };
+
+    let log_return: Series = close
+        .f64()?
+        .into_iter()
+        .zip(base.f64()?)
+        .map(|(close, base)| Some((close? / base?).ln()))
+        .collect();
+
+    let log_return = prepared.restore(log_return, offset)?;
+
+    let prefix = if cumulative { "CUM" } else { "" };
+    let log_return = fill_missing(log_return, fill)?;
+
+    Ok(log_return.with_name(format!("{}LOGRET_{}", prefix, length).into()))
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::utils::data_loader::{
+        csv_to_dataframe, 
+        set_column_names, 
+        combine_date_time, 
+        convert_to_naive_datetime
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_log_return() {
+        let mut df = csv_to_dataframe(
+            "data/AUDNZD1.csv", 
+            false
+        ).unwrap();
+        let _ = set_column_names(
+            &mut df, 
+            vec!["date", "time", "open", "high", "low", "close", "volume"]
+        );
+        let df = combine_date_time(&df).unwrap();
+        let df = convert_to_naive_datetime(
+            &df, 
+            "%Y.%m.%d %H:%M"
+        ).unwrap();
+
+        let close = df.column("close").unwrap().as_series().unwrap();
+        let log_return = log_return(close, None, false, None, None).unwrap();
+        assert_eq!(log_return.name().as_str(), "LOGRET_1");
+        let log_return = log_return.f64().unwrap();
+        assert_eq!(log_return.get(0), None);
+        assert!((log_return.get(1).unwrap() - (1.08069f64 / 1.08086).ln()).abs() < 1e-15);
+    }
+
+    #[test]
+    fn test_cumulative_log_return() {
+        let close = Series::new("close".into(), vec![1.0, 2.0, 4.0]);
+        let log_return = log_return(&close, None, true, None, None).unwrap();
+        assert_eq!(log_return.name().as_str(), "CUMLOGRET_1");
+        let log_return: Vec<_> = log_return.f64().unwrap().into_no_null_iter().collect();
+        assert_eq!(log_return, vec![0.0, 2f64.ln(), 4f64.ln()]);
+    }
+}
@@ -0,0 +1,3 @@
+pub mod log_return;
+pub mod percent_return;
+pub mod drawdown;
\ No newline at end of file
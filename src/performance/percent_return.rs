@@ -0,0 +1,89 @@
+use crate::utils::core::{get_offset, fill_missing, FillPolicy};
+use crate::utils::missing::prepare;
+use crate::utils::error::CommandResult;
+use crate::utils::validate;
+use polars::prelude::*;
+
+
+/// Percent return over `length` bars or, when `cumulative`, since the first bar.
+pub fn percent_return(
+    close: &Series,
+    length: Option<i32>,
+    cumulative: bool,
+    offset: Option<i32>,
+    fill: Option<FillPolicy>
+) -> CommandResult<Series> {
+    let prepared = prepare(&[close])?;
+    let close = &prepared.inputs[0];
+    let length = validate::length("length", length, 1, 1)?;
+    validate::min_rows(close.len(), length as usize + 1)?;
+    let offset = get_offset(offset);
+    let close = close.cast(&DataType::Float64)?;
+
+    let base = if cumulative {
+        let first = close.f64()?.into_iter().flatten().next();
+        Series::new("base".into(), vec![first; close.len()])
+    } else {
+        close.shift(length as i64)
+    };
+
+    let percent_return: Series = close
+        .f64()?
+        .into_iter()
+        .zip(base.f64()?)
+        .map(|(close, base)| Some(close? / base? - 1.0))
+        .collect();
+
+    let percent_return = prepared.restore(percent_return, offset)?;
+
+    let prefix = if cumulative { "CUM" } else { "" };
+    let percent_return = fill_missing(percent_return, fill)?;
+
+    Ok(percent_return.with_name(format!("{}PCTRET_{}", prefix, length).into()))
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::utils::data_loader::{
+        csv_to_dataframe, 
+        set_column_names, 
+        combine_date_time, 
+        convert_to_naive_datetime
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_percent_return() {
+        let mut df = csv_to_dataframe(
+            "data/AUDNZD1.csv", 
+            false
+        ).unwrap();
+        let _ = set_column_names(
+            &mut df, 
+            vec!["date", "time", "open", "high", "low", "close", "volume"]
+        );
+        let df = combine_date_time(&df).unwrap();
+        let df = convert_to_naive_datetime(
+            &df, 
+            "%Y.%m.%d %H:%M"
+        ).unwrap();
+
+        let close = df.column("close").unwrap().as_series().unwrap();
+        let percent_return = percent_return(close, Some(2), false, None, None).unwrap();
+        assert_eq!(percent_return.name().as_str(), "PCTRET_2");
+        let percent_return = percent_return.f64().unwrap();
+        assert_eq!(percent_return.get(1), None);
+        assert!((percent_return.get(2).unwrap() - (1.08077 / 1.08086 - 1.0)).abs() < 1e-15);
+    }
+
+    #[test]
+    fn test_cumulative_percent_return() {
+        let close = Series::new("close".into(), vec![2.0, 1.0, 3.0]);
+        let percent_return = percent_return(&close, None, true, None, None).unwrap();
+        let percent_return: Vec<_> = percent_return.f64().unwrap().into_no_null_iter().collect();
+        assert_eq!(percent_return, vec![0.0, -0.5, 0.5]);
+    }
+}
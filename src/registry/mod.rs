@@ -0,0 +1,459 @@
+//! What every indicator of a batch takes and gives.
+//!
+//! The registry lists each `IndicatorSpec` kind with its category, input
+//! columns, parameters and their valid values, the output columns and the
+//! warm-up rows of its default spec. It is what a form over the indicators
+//! is built from, and `check` uses it to reject a strategy config with
+//! invalid parameters before any indicator runs.
+
+use crate::accessor::aliases::Input;
+use crate::batch::spec::ParameterValue;
+use crate::batch::IndicatorSpec;
+use crate::candles::cdl_doji::DojiOptions;
+use crate::candles::cdl_pattern::CandlePattern;
+use crate::candles::cdl_z::CdlZOptions;
+use crate::momentum::rsi::RsiOptions;
+use crate::overlap::dema::DemaOptions;
+use crate::overlap::ema::EmaOptions;
+use crate::overlap::linreg::LinregOptions;
+use crate::overlap::rma::RmaOptions;
+use crate::overlap::sma::SmaOptions;
+use crate::utils::error::{CommandError, CommandResult};
+use crate::volatility::atr::AtrOptions;
+use crate::volume::aobv::AobvOptions;
+use std::sync::OnceLock;
+
+
+/// The module an indicator belongs to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Category {
+    Overlap,
+    Momentum,
+    Volatility,
+    Volume,
+    Statistics,
+    Performance,
+    Candles,
+}
+
+
+/// The type of a parameter, `Choice` with its valid values.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ParameterType {
+    Integer,
+    Float,
+    Boolean,
+    Choice(&'static [&'static str]),
+    Fill,
+    Patterns,
+    Settings,
+}
+
+
+/// The values a parameter takes, as the indicator validates them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Valid {
+    Any,
+    AtLeast(i64),
+    Positive,
+    Between(f64, f64),
+    /// Not negative and below the `length` of the same spec.
+    BelowLength,
+    PositiveNotOne,
+}
+
+
+impl Valid {
+    /// Whether `value` is valid, `length` the effective length of the spec.
+    pub fn holds(&self, value: &ParameterValue, length: Option<i64>) -> bool {
+        match (self, value) {
+            (_, ParameterValue::Unset) | (Valid::Any, _) => true,
+            (Valid::AtLeast(min), ParameterValue::Integer(value)) => value >= min,
+            (Valid::Positive, ParameterValue::Float(value)) => *value > 0.0,
+            (Valid::Between(low, high), ParameterValue::Float(value)) => (*low..=*high).contains(value),
+            (Valid::BelowLength, ParameterValue::Integer(value)) => {
+                *value >= 0 && length.is_none_or(|length| *value < length)
+            },
+            (Valid::PositiveNotOne, ParameterValue::Float(value)) => *value > 0.0 && *value != 1.0,
+            _ => false,
+        }
+    }
+
+    /// The valid values in words, as in the error of the indicator.
+    pub fn expected(&self) -> String {
+        match self {
+            Valid::Any => "any value".to_string(),
+            Valid::AtLeast(min) => format!("at least {}", min),
+            Valid::Positive => "a positive number".to_string(),
+            Valid::Between(low, high) => format!("between {} and {}", low, high),
+            Valid::BelowLength => "below length".to_string(),
+            Valid::PositiveNotOne => "positive and not 1".to_string(),
+        }
+    }
+}
+
+
+/// A parameter of an indicator with the value it takes when unset.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Parameter {
+    pub name: &'static str,
+    pub kind: ParameterType,
+    pub default: ParameterValue,
+    pub valid: Valid,
+}
+
+
+/// An indicator kind: its parameters in the order of
+/// `IndicatorSpec::parameters`, and the outputs and warm-up of its default
+/// `spec`, the rows before every output has a converged value as in
+/// `IndicatorSpec::lookback`.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Indicator {
+    pub kind: &'static str,
+    pub title: &'static str,
+    pub category: Category,
+    pub inputs: &'static [Input],
+    pub parameters: Vec<Parameter>,
+    pub outputs: Vec<String>,
+    pub warm_up: usize,
+    pub spec: IndicatorSpec,
+}
+
+
+impl Indicator {
+    pub fn parameter(&self, name: &str) -> Option<&Parameter> {
+        self.parameters.iter().find(|parameter| parameter.name == name)
+    }
+}
+
+
+fn integer(name: &'static str, default: i64, valid: Valid) -> Parameter {
+    Parameter { name, kind: ParameterType::Integer, default: ParameterValue::Integer(default), valid }
+}
+
+
+fn float(name: &'static str, default: f64, valid: Valid) -> Parameter {
+    Parameter { name, kind: ParameterType::Float, default: ParameterValue::Float(default), valid }
+}
+
+
+fn boolean(name: &'static str) -> Parameter {
+    Parameter { name, kind: ParameterType::Boolean, default: ParameterValue::Boolean(false), valid: Valid::Any }
+}
+
+
+fn choice(name: &'static str, choices: &'static [&'static str], default: &str) -> Parameter {
+    Parameter {
+        name,
+        kind: ParameterType::Choice(choices),
+        default: ParameterValue::Text(default.to_string()),
+        valid: Valid::Any,
+    }
+}
+
+
+fn entry(
+    spec: IndicatorSpec,
+    title: &'static str,
+    category: Category,
+    mut parameters: Vec<Parameter>,
+    outputs: &[&str]
+) -> Indicator {
+    // every indicator is shifted and filled the same way
+    parameters.push(integer("offset", 0, Valid::Any));
+    parameters.push(Parameter { name: "fill", kind: ParameterType::Fill, default: ParameterValue::Unset, valid: Valid::Any });
+    Indicator {
+        kind: spec.kind(),
+        title,
+        category,
+        inputs: spec.inputs(),
+        parameters,
+        outputs: outputs.iter().map(|output| output.to_string()).collect(),
+        warm_up: spec.lookback(),
+        spec,
+    }
+}
+
+
+fn build() -> Vec<Indicator> {
+    use Category::*;
+    let length = |default| integer("length", default, Valid::AtLeast(1));
+    let window = |default, min| integer("length", default, Valid::AtLeast(min));
+    let drift = || integer("drift", 1, Valid::AtLeast(1));
+    let ddof = || integer("ddof", 1, Valid::BelowLength);
+    let patterns: Vec<String> = CandlePattern::ALL.iter().map(|pattern| format!("CDL_{}", pattern.name())).collect();
+    let patterns: Vec<&str> = patterns.iter().map(|name| name.as_str()).collect();
+
+    vec![
+        entry(IndicatorSpec::Sma(SmaOptions::default()), "Simple Moving Average", Overlap,
+            vec![length(10)], &["SMA_10"]),
+        entry(IndicatorSpec::Ema(EmaOptions::default()), "Exponential Moving Average", Overlap,
+            vec![length(10), boolean("adjust"), choice("mamode", &["sma", "ema"], "sma")], &["EMA_10"]),
+        entry(IndicatorSpec::Dema(DemaOptions::default()), "Double Exponential Moving Average", Overlap,
+            vec![length(10)], &["DEMA_10"]),
+        entry(IndicatorSpec::Rma(RmaOptions::default()), "Wilder's Moving Average", Overlap,
+            vec![length(10)], &["RMA_10"]),
+        entry(IndicatorSpec::Linreg(LinregOptions::default()), "Linear Regression", Overlap,
+            vec![
+                window(14, 2),
+                boolean("angle"),
+                boolean("degrees"),
+                boolean("intercept"),
+                boolean("r"),
+                boolean("slope"),
+                boolean("tsf"),
+            ], &["LR_14"]),
+        entry(
+            IndicatorSpec::Slope { length: None, as_angle: false, to_degrees: false, offset: None, fill: None },
+            "Slope", Momentum,
+            vec![window(14, 2), boolean("as_angle"), boolean("to_degrees")], &["SLOPE_14"]),
+        entry(IndicatorSpec::Rsi(RsiOptions::default()), "Relative Strength Index", Momentum,
+            vec![length(14), float("scalar", 100.0, Valid::Any), drift()], &["RSI_14"]),
+        entry(IndicatorSpec::TrueRange { drift: None, offset: None, fill: None }, "True Range", Volatility,
+            vec![drift()], &["TRUERANGE_1"]),
+        entry(IndicatorSpec::Atr(AtrOptions::default()), "Average True Range", Volatility,
+            vec![length(14), choice("mamode", &["rma", "ema", "sma"], "rma"), drift(), boolean("percent")],
+            &["ATRr_14"]),
+        entry(IndicatorSpec::Obv { offset: None, fill: None }, "On Balance Volume", Volume,
+            vec![], &["OBV"]),
+        entry(IndicatorSpec::Nvi { length: None, initial: None, offset: None, fill: None },
+            "Negative Volume Index", Volume,
+            vec![length(1), float("initial", 1000.0, Valid::Positive)], &["NVI_1"]),
+        entry(IndicatorSpec::Pvi { length: None, initial: None, offset: None, fill: None },
+            "Positive Volume Index", Volume,
+            vec![length(1), float("initial", 1000.0, Valid::Positive)], &["PVI_1"]),
+        entry(IndicatorSpec::Aobv(AobvOptions::default()), "Archer On Balance Volume", Volume,
+            vec![
+                integer("fast", 4, Valid::AtLeast(1)),
+                integer("slow", 12, Valid::AtLeast(1)),
+                integer("max_lookback", 2, Valid::AtLeast(1)),
+                integer("min_lookback", 2, Valid::AtLeast(1)),
+                choice("mamode", &["ema", "sma"], "ema"),
+                integer("run_length", 2, Valid::AtLeast(1)),
+            ],
+            &["OBV", "OBV_min_2", "OBV_max_2", "OBVe_4", "OBVe_12", "AOBV_LR_2", "AOBV_SR_2"]),
+        entry(IndicatorSpec::Zscore { length: None, std: None, offset: None, fill: None }, "Z Score", Statistics,
+            vec![window(30, 2), float("std", 1.0, Valid::Positive)], &["ZS_30"]),
+        entry(IndicatorSpec::Stdev { length: None, ddof: None, offset: None, fill: None },
+            "Standard Deviation", Statistics,
+            vec![window(30, 2), ddof()], &["STDEV_30"]),
+        entry(IndicatorSpec::Variance { length: None, ddof: None, offset: None, fill: None }, "Variance", Statistics,
+            vec![window(30, 2), ddof()], &["VAR_30"]),
+        entry(IndicatorSpec::Skew { length: None, offset: None, fill: None }, "Skew", Statistics,
+            vec![window(30, 3)], &["SKEW_30"]),
+        entry(IndicatorSpec::Kurtosis { length: None, offset: None, fill: None }, "Kurtosis", Statistics,
+            vec![window(30, 4)], &["KURT_30"]),
+        entry(IndicatorSpec::Quantile { length: None, q: None, offset: None, fill: None }, "Quantile", Statistics,
+            vec![length(30), float("q", 0.5, Valid::Between(0.0, 1.0))], &["QTL_30_0.5"]),
+        entry(IndicatorSpec::Median { length: None, offset: None, fill: None }, "Median", Statistics,
+            vec![length(30)], &["MEDIAN_30"]),
+        entry(IndicatorSpec::Mad { length: None, offset: None, fill: None }, "Mean Absolute Deviation", Statistics,
+            vec![length(30)], &["MAD_30"]),
+        // the probabilities and their entropy are two windows of `length`
+        entry(IndicatorSpec::Entropy { length: None, base: None, offset: None, fill: None }, "Entropy", Statistics,
+            vec![length(10), float("base", 2.0, Valid::PositiveNotOne)], &["ENTP_10"]),
+        entry(IndicatorSpec::LogReturn { length: None, cumulative: false, offset: None, fill: None },
+            "Log Return", Performance,
+            vec![length(1), boolean("cumulative")], &["LOGRET_1"]),
+        entry(IndicatorSpec::PercentReturn { length: None, cumulative: false, offset: None, fill: None },
+            "Percent Return", Performance,
+            vec![length(1), boolean("cumulative")], &["PCTRET_1"]),
+        entry(IndicatorSpec::Drawdown { offset: None, fill: None }, "Drawdown", Performance,
+            vec![], &["DD", "DD_PCT", "DD_LOG"]),
+        entry(IndicatorSpec::Ha { offset: None, fill: None }, "Heikin-Ashi", Candles,
+            vec![], &["HA_open", "HA_high", "HA_low", "HA_close"]),
+        entry(IndicatorSpec::CdlDoji(DojiOptions::default()), "Doji", Candles,
+            vec![
+                length(10),
+                float("factor", 10.0, Valid::Positive),
+                integer("scalar", 100, Valid::Any),
+                boolean("naive"),
+            ], &["CDL_DOJI_10_0.1"]),
+        entry(IndicatorSpec::CdlInside { asbool: false, offset: None, fill: None }, "Inside Bar", Candles,
+            vec![boolean("asbool")], &["CDL_INSIDE"]),
+        entry(IndicatorSpec::CdlZ(CdlZOptions::default()), "Z Score Candles", Candles,
+            vec![window(30, 2), boolean("full"), ddof()],
+            &["open_Z_30_1", "high_Z_30_1", "low_Z_30_1", "close_Z_30_1"]),
+        // no names is every pattern, each with its own penetration unless set
+        entry(IndicatorSpec::CdlPattern { names: vec![], options: Box::default() }, "Candle Patterns", Candles,
+            vec![
+                Parameter {
+                    name: "names",
+                    kind: ParameterType::Patterns,
+                    default: ParameterValue::Patterns(vec![]),
+                    valid: Valid::Any,
+                },
+                Parameter {
+                    name: "settings",
+                    kind: ParameterType::Settings,
+                    default: ParameterValue::Settings(Box::default()),
+                    valid: Valid::Any,
+                },
+                Parameter {
+                    name: "penetration",
+                    kind: ParameterType::Float,
+                    default: ParameterValue::Unset,
+                    valid: Valid::Any,
+                },
+            ], &patterns),
+    ]
+}
+
+
+/// Every indicator kind of `IndicatorSpec`, in the order of its variants.
+pub fn indicators() -> &'static [Indicator] {
+    static INDICATORS: OnceLock<Vec<Indicator>> = OnceLock::new();
+    INDICATORS.get_or_init(build)
+}
+
+
+/// The indicator of `kind`, e.g. "sma".
+pub fn indicator(kind: &str) -> Option<&'static Indicator> {
+    indicators().iter().find(|indicator| indicator.kind == kind)
+}
+
+
+fn text(value: &ParameterValue) -> String {
+    match value {
+        ParameterValue::Integer(value) => value.to_string(),
+        ParameterValue::Float(value) => value.to_string(),
+        ParameterValue::Boolean(value) => value.to_string(),
+        ParameterValue::Text(value) => value.clone(),
+        value => format!("{:?}", value),
+    }
+}
+
+
+/// Checks the parameters of `spec` are valid for its indicator, whatever the
+/// validation mode, with the error the indicator gives for the first invalid
+/// one. A moving average mode the indicator does not know is an error here,
+/// where the indicator falls back to its default.
+pub fn check(spec: &IndicatorSpec) -> CommandResult<()> {
+    let indicator = indicator(spec.kind()).expect("every spec kind is registered");
+    let parameters = spec.parameters();
+    let length = parameters
+        .iter()
+        .find(|(name, _)| *name == "length")
+        .map(|(_, value)| value)
+        .filter(|value| **value != ParameterValue::Unset)
+        .or_else(|| indicator.parameter("length").map(|parameter| &parameter.default));
+    let length = match length {
+        Some(ParameterValue::Integer(length)) => Some(*length),
+        _ => None,
+    };
+
+    for (name, value) in &parameters {
+        let Some(parameter) = indicator.parameter(name) else { continue };
+        let expected = match (parameter.kind, value) {
+            (ParameterType::Choice(choices), ParameterValue::Text(text)) if !choices.contains(&text.as_str()) => {
+                format!("one of {}", choices.join(", "))
+            },
+            _ if !parameter.valid.holds(value, length) => parameter.valid.expected(),
+            _ => continue,
+        };
+        return Err(CommandError::InvalidParameter {
+            name: name.to_string(),
+            value: text(value),
+            expected,
+        });
+    }
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::batch::{evaluate, Bars};
+    use crate::utils::data_loader::{
+        csv_to_dataframe,
+        set_column_names,
+        combine_date_time,
+        convert_to_naive_datetime
+    };
+    use super::*;
+
+    fn load() -> Bars {
+        let mut df = csv_to_dataframe(
+            "data/AUDNZD1.csv",
+            false
+        ).unwrap();
+        let _ = set_column_names(
+            &mut df,
+            vec!["date", "time", "open", "high", "low", "close", "volume"]
+        );
+        let df = combine_date_time(&df).unwrap();
+        let df = convert_to_naive_datetime(
+            &df,
+            "%Y.%m.%d %H:%M"
+        ).unwrap();
+        Bars::new([Input::Open, Input::High, Input::Low, Input::Close, Input::Volume].map(|input| {
+            (input, df.column(input.name()).unwrap().as_materialized_series().clone())
+        }))
+    }
+
+    #[test]
+    fn test_indicators() {
+        let bars = load();
+        assert_eq!(indicators().len(), 30);
+        for indicator in indicators() {
+            let names: Vec<&str> = indicator.spec.parameters().into_iter().map(|(name, _)| name).collect();
+            let registered: Vec<&str> = indicator.parameters.iter().map(|parameter| parameter.name).collect();
+            assert_eq!(names, registered, "{}", indicator.kind);
+            assert!(check(&indicator.spec).is_ok(), "{}", indicator.kind);
+
+            let df = evaluate(&bars, std::slice::from_ref(&indicator.spec)).unwrap();
+            let outputs: Vec<&str> = df.get_column_names().into_iter().map(|name| name.as_str()).collect();
+            assert_eq!(outputs, indicator.outputs, "{}", indicator.kind);
+            // the missing rows are within the warm-up, the rest are unconverged
+            let missing = df
+                .get_columns()
+                .iter()
+                .map(|column| column.is_null().into_iter().take_while(|null| *null == Some(true)).count())
+                .max()
+                .unwrap();
+            assert!(missing <= indicator.warm_up, "{}", indicator.kind);
+            assert_eq!(indicator.warm_up, indicator.spec.lookback(), "{}", indicator.kind);
+        }
+        assert_eq!(indicator("dema").unwrap().warm_up, 18);
+        assert_eq!(indicator("aobv").unwrap().warm_up, 13);
+        assert_eq!(indicator("atr").unwrap().category, Category::Volatility);
+        assert_eq!(indicator("cdl_pattern").unwrap().outputs.len(), CandlePattern::ALL.len());
+        assert!(indicator("vp").is_none());
+    }
+
+    #[test]
+    fn test_check() {
+        let bars = load();
+        let invalid = [
+            IndicatorSpec::Sma(SmaOptions { length: Some(0), ..Default::default() }),
+            IndicatorSpec::Slope { length: Some(1), as_angle: false, to_degrees: false, offset: None, fill: None },
+            IndicatorSpec::Rsi(RsiOptions { drift: Some(0), ..Default::default() }),
+            IndicatorSpec::Variance { length: Some(5), ddof: Some(5), offset: None, fill: None },
+            IndicatorSpec::Quantile { length: None, q: Some(1.5), offset: None, fill: None },
+            IndicatorSpec::Entropy { length: None, base: Some(1.0), offset: None, fill: None },
+            IndicatorSpec::CdlDoji(DojiOptions { factor: Some(-1.0), ..Default::default() }),
+            IndicatorSpec::CdlZ(CdlZOptions { ddof: Some(30), ..Default::default() }),
+        ];
+        // the registry rejects what the indicators reject, with their error
+        for spec in invalid {
+            let checked = check(&spec).unwrap_err();
+            let evaluated = evaluate(&bars, std::slice::from_ref(&spec)).unwrap_err();
+            assert_eq!(format!("{:?}", checked), format!("{:?}", evaluated), "{}", spec.kind());
+        }
+
+        let stdev = IndicatorSpec::Stdev { length: Some(10), ddof: Some(9), offset: None, fill: None };
+        assert!(check(&stdev).is_ok());
+        let atr = IndicatorSpec::Atr(AtrOptions { mamode: Some("wma".to_string()), ..Default::default() });
+        assert!(matches!(
+            check(&atr),
+            Err(CommandError::InvalidParameter { name, value, expected })
+                if name == "mamode" && value == "wma" && expected == "one of rma, ema, sma"
+        ));
+    }
+}
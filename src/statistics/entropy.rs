@@ -0,0 +1,86 @@
+use crate::utils::core::{get_offset, fill_missing, FillPolicy};
+use crate::utils::missing::prepare;
+use crate::utils::error::CommandResult;
+use crate::utils::validate;
+use polars::prelude::*;
+
+
+pub fn entropy(
+    close: &Series,
+    length: Option<i32>,
+    base: Option<f64>,
+    offset: Option<i32>,
+    fill: Option<FillPolicy>
+) -> CommandResult<Series> {
+    let prepared = prepare(&[close])?;
+    let close = &prepared.inputs[0];
+    let length = validate::length("length", length, 1, 10)?;
+    validate::min_rows(close.len(), length as usize)?;
+    let base = validate::parameter("base", base, 2.0, |base| base > 0.0 && base != 1.0, "positive and not 1")?;
+    let offset = get_offset(offset);
+    let close = close.cast(&DataType::Float64)?;
+
+    let window = RollingOptionsFixedWindow {
+        window_size: length as usize,
+        min_periods: length as usize,
+        ..Default::default()
+    };
+
+    // share of each close in its window, then -p * log(p) summed over the window
+    let total = close.rolling_sum(window.clone())?;
+    let p = match &close / &total {
+        Ok(p) => p,
+        Err(_) => return Err("Failed to calculate probabilities".into())
+    };
+    let log_base = base.ln();
+    let information = p
+        .f64()?
+        .apply_values(|p| -p * p.ln() / log_base)
+        .into_series();
+    let entropy = information.rolling_sum(window)?;
+
+    let entropy = prepared.restore(entropy, offset)?;
+
+    let entropy = fill_missing(entropy, fill)?;
+
+    Ok(entropy.with_name(format!("ENTP_{}", length).into()))
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::utils::data_loader::{
+        csv_to_dataframe, 
+        set_column_names, 
+        combine_date_time, 
+        convert_to_naive_datetime
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_entropy() {
+        let mut df = csv_to_dataframe(
+            "data/AUDNZD1.csv", 
+            false
+        ).unwrap();
+        let _ = set_column_names(
+            &mut df, 
+            vec!["date", "time", "open", "high", "low", "close", "volume"]
+        );
+        let df = combine_date_time(&df).unwrap();
+        let df = convert_to_naive_datetime(
+            &df, 
+            "%Y.%m.%d %H:%M"
+        ).unwrap();
+
+        let close = df.column("close").unwrap().as_series().unwrap();
+        let entropy = entropy(close, None, None, None, None).unwrap();
+        assert_eq!(entropy.name().as_str(), "ENTP_10");
+        let entropy = entropy.f64().unwrap();
+        assert_eq!(entropy.get(17), None);
+        // nearly equal closes are close to the maximum of log2(10)
+        assert!((entropy.get(18).unwrap() - 10f64.log2()).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,93 @@
+use crate::utils::core::{get_offset, rolling_apply, fill_missing, FillPolicy};
+use crate::utils::missing::prepare;
+use crate::utils::error::CommandResult;
+use crate::utils::validate;
+use polars::prelude::*;
+
+
+/// Bias corrected sample excess kurtosis of a window, as computed by pandas.
+fn sample_kurtosis(window: &[f64]) -> f64 {
+    let n = window.len() as f64;
+    if n < 4.0 {
+        return f64::NAN;
+    }
+    let mean = window.iter().sum::<f64>() / n;
+    let (m2, m4) = window.iter().fold((0.0, 0.0), |(m2, m4), x| {
+        let d = (x - mean) * (x - mean);
+        (m2 + d, m4 + d * d)
+    });
+    // pandas gives NaN as well when the window has no spread
+    if m2 == 0.0 {
+        return f64::NAN;
+    }
+    let a = (n + 1.0) * n * (n - 1.0) / ((n - 2.0) * (n - 3.0));
+    let b = 3.0 * (n - 1.0) * (n - 1.0) / ((n - 2.0) * (n - 3.0));
+    a * m4 / (m2 * m2) - b
+}
+
+
+pub fn kurtosis(
+    close: &Series,
+    length: Option<i32>,
+    offset: Option<i32>,
+    fill: Option<FillPolicy>
+) -> CommandResult<Series> {
+    let prepared = prepare(&[close])?;
+    let close = &prepared.inputs[0];
+    let length = validate::length("length", length, 4, 30)?;
+    validate::min_rows(close.len(), length as usize)?;
+    let offset = get_offset(offset);
+
+    let kurtosis = rolling_apply(close, length as usize, sample_kurtosis)?;
+
+    let kurtosis = prepared.restore(kurtosis, offset)?;
+
+    let kurtosis = fill_missing(kurtosis, fill)?;
+
+    Ok(kurtosis.with_name(format!("KURT_{}", length).into()))
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::utils::data_loader::{
+        csv_to_dataframe, 
+        set_column_names, 
+        combine_date_time, 
+        convert_to_naive_datetime
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_kurtosis() {
+        let mut df = csv_to_dataframe(
+            "data/AUDNZD1.csv", 
+            false
+        ).unwrap();
+        let _ = set_column_names(
+            &mut df, 
+            vec!["date", "time", "open", "high", "low", "close", "volume"]
+        );
+        let df = combine_date_time(&df).unwrap();
+        let df = convert_to_naive_datetime(
+            &df, 
+            "%Y.%m.%d %H:%M"
+        ).unwrap();
+
+        let close = df.column("close").unwrap().as_series().unwrap();
+        let kurtosis = kurtosis(close, None, None, None).unwrap();
+        assert_eq!(kurtosis.name().as_str(), "KURT_30");
+        assert_eq!(kurtosis.f64().unwrap().get(28), None);
+        assert!(kurtosis.f64().unwrap().get(29).is_some());
+    }
+
+    #[test]
+    fn test_sample_kurtosis() {
+        // pd.Series([1, 2, 3, 10, 4]).kurt()
+        let kurtosis = sample_kurtosis(&[1.0, 2.0, 3.0, 10.0, 4.0]);
+        assert!((kurtosis - 3.152).abs() < 1e-12);
+        assert!(sample_kurtosis(&[2.0, 2.0, 2.0, 2.0]).is_nan());
+    }
+}
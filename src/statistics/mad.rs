@@ -0,0 +1,74 @@
+use crate::utils::core::{get_offset, rolling_apply, fill_missing, FillPolicy};
+use crate::utils::missing::prepare;
+use crate::utils::error::CommandResult;
+use crate::utils::validate;
+use polars::prelude::*;
+
+
+/// Mean absolute deviation of a window around its mean.
+fn mean_absolute_deviation(window: &[f64]) -> f64 {
+    let n = window.len() as f64;
+    let mean = window.iter().sum::<f64>() / n;
+    window.iter().map(|x| (x - mean).abs()).sum::<f64>() / n
+}
+
+
+pub fn mad(
+    close: &Series,
+    length: Option<i32>,
+    offset: Option<i32>,
+    fill: Option<FillPolicy>
+) -> CommandResult<Series> {
+    let prepared = prepare(&[close])?;
+    let close = &prepared.inputs[0];
+    let length = validate::length("length", length, 1, 30)?;
+    validate::min_rows(close.len(), length as usize)?;
+    let offset = get_offset(offset);
+
+    let mad = rolling_apply(close, length as usize, mean_absolute_deviation)?;
+
+    let mad = prepared.restore(mad, offset)?;
+
+    let mad = fill_missing(mad, fill)?;
+
+    Ok(mad.with_name(format!("MAD_{}", length).into()))
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::utils::data_loader::{
+        csv_to_dataframe, 
+        set_column_names, 
+        combine_date_time, 
+        convert_to_naive_datetime
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_mad() {
+        let mut df = csv_to_dataframe(
+            "data/AUDNZD1.csv", 
+            false
+        ).unwrap();
+        let _ = set_column_names(
+            &mut df, 
+            vec!["date", "time", "open", "high", "low", "close", "volume"]
+        );
+        let df = combine_date_time(&df).unwrap();
+        let df = convert_to_naive_datetime(
+            &df, 
+            "%Y.%m.%d %H:%M"
+        ).unwrap();
+
+        let close = df.column("close").unwrap().as_series().unwrap();
+        let mad = mad(close, Some(3), None, None).unwrap();
+        assert_eq!(mad.name().as_str(), "MAD_3");
+        let window = [1.08086, 1.08069, 1.08077];
+        let mean = window.iter().sum::<f64>() / 3.0;
+        let expected = window.iter().map(|x| (x - mean).abs()).sum::<f64>() / 3.0;
+        assert!((mad.f64().unwrap().get(2).unwrap() - expected).abs() < 1e-12);
+    }
+}
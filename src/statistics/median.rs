@@ -0,0 +1,57 @@
+use crate::utils::core::{fill_missing, FillPolicy};
+use crate::statistics::quantile::quantile;
+use crate::utils::error::CommandResult;
+use crate::utils::validate;
+use polars::prelude::*;
+
+
+pub fn median(
+    close: &Series,
+    length: Option<i32>,
+    offset: Option<i32>,
+    fill: Option<FillPolicy>
+) -> CommandResult<Series> {
+    let length = validate::length("length", length, 1, 30)?;
+
+    let median = quantile(close, Some(length), Some(0.5), offset, None)?;
+
+    let median = fill_missing(median, fill)?;
+
+    Ok(median.with_name(format!("MEDIAN_{}", length).into()))
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::utils::data_loader::{
+        csv_to_dataframe, 
+        set_column_names, 
+        combine_date_time, 
+        convert_to_naive_datetime
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_median() {
+        let mut df = csv_to_dataframe(
+            "data/AUDNZD1.csv", 
+            false
+        ).unwrap();
+        let _ = set_column_names(
+            &mut df, 
+            vec!["date", "time", "open", "high", "low", "close", "volume"]
+        );
+        let df = combine_date_time(&df).unwrap();
+        let df = convert_to_naive_datetime(
+            &df, 
+            "%Y.%m.%d %H:%M"
+        ).unwrap();
+
+        let close = df.column("close").unwrap().as_series().unwrap();
+        let median = median(close, Some(3), None, None).unwrap();
+        assert_eq!(median.name().as_str(), "MEDIAN_3");
+        assert_eq!(median.f64().unwrap().get(2), Some(1.08077));
+    }
+}
@@ -0,0 +1,9 @@
+pub mod zscore;
+pub mod stdev;
+pub mod variance;
+pub mod skew;
+pub mod kurtosis;
+pub mod quantile;
+pub mod median;
+pub mod mad;
+pub mod entropy;
\ No newline at end of file
@@ -0,0 +1,78 @@
+use crate::utils::core::{get_offset, fill_missing, FillPolicy};
+use crate::utils::missing::prepare;
+use crate::utils::error::CommandResult;
+use crate::utils::validate;
+use polars::prelude::*;
+
+
+pub fn quantile(
+    close: &Series,
+    length: Option<i32>,
+    q: Option<f64>,
+    offset: Option<i32>,
+    fill: Option<FillPolicy>
+) -> CommandResult<Series> {
+    let prepared = prepare(&[close])?;
+    let close = &prepared.inputs[0];
+    let length = validate::length("length", length, 1, 30)?;
+    validate::min_rows(close.len(), length as usize)?;
+    let q = validate::parameter("q", q, 0.5, |q| (0.0..=1.0).contains(&q), "between 0 and 1")?;
+    let offset = get_offset(offset);
+    let close = close.cast(&DataType::Float64)?;
+
+    // linear interpolation between the closest ranks, like pandas
+    let quantile = close.rolling_quantile(RollingOptionsFixedWindow {
+        window_size: length as usize,
+        min_periods: length as usize,
+        fn_params: Some(RollingFnParams::Quantile(RollingQuantileParams {
+            prob: q,
+            method: QuantileMethod::Linear,
+        })),
+        ..Default::default()
+    })?;
+
+    let quantile = prepared.restore(quantile, offset)?;
+
+    let quantile = fill_missing(quantile, fill)?;
+
+    Ok(quantile.with_name(format!("QTL_{}_{}", length, q).into()))
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::utils::data_loader::{
+        csv_to_dataframe, 
+        set_column_names, 
+        combine_date_time, 
+        convert_to_naive_datetime
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_quantile() {
+        let mut df = csv_to_dataframe(
+            "data/AUDNZD1.csv", 
+            false
+        ).unwrap();
+        let _ = set_column_names(
+            &mut df, 
+            vec!["date", "time", "open", "high", "low", "close", "volume"]
+        );
+        let df = combine_date_time(&df).unwrap();
+        let df = convert_to_naive_datetime(
+            &df, 
+            "%Y.%m.%d %H:%M"
+        ).unwrap();
+
+        let close = df.column("close").unwrap().as_series().unwrap();
+        let quantile = quantile(close, Some(3), Some(0.25), None, None).unwrap();
+        assert_eq!(quantile.name().as_str(), "QTL_3_0.25");
+        let quantile = quantile.f64().unwrap();
+        // sorted window 1.08069, 1.08077, 1.08086 -> halfway between the first two
+        let expected = 1.08069 + 0.5 * (1.08077 - 1.08069);
+        assert!((quantile.get(2).unwrap() - expected).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,92 @@
+use crate::utils::core::{get_offset, rolling_apply, fill_missing, FillPolicy};
+use crate::utils::missing::prepare;
+use crate::utils::error::CommandResult;
+use crate::utils::validate;
+use polars::prelude::*;
+
+
+/// Bias corrected sample skewness of a window, as computed by pandas.
+fn sample_skew(window: &[f64]) -> f64 {
+    let n = window.len() as f64;
+    if n < 3.0 {
+        return f64::NAN;
+    }
+    let mean = window.iter().sum::<f64>() / n;
+    let (m2, m3) = window.iter().fold((0.0, 0.0), |(m2, m3), x| {
+        let d = x - mean;
+        (m2 + d * d, m3 + d * d * d)
+    });
+    let (m2, m3) = (m2 / n, m3 / n);
+    // undefined without any spread, pandas gives NaN too
+    if m2 == 0.0 {
+        return f64::NAN;
+    }
+    (n * (n - 1.0)).sqrt() / (n - 2.0) * m3 / m2.powf(1.5)
+}
+
+
+pub fn skew(
+    close: &Series,
+    length: Option<i32>,
+    offset: Option<i32>,
+    fill: Option<FillPolicy>
+) -> CommandResult<Series> {
+    let prepared = prepare(&[close])?;
+    let close = &prepared.inputs[0];
+    let length = validate::length("length", length, 3, 30)?;
+    validate::min_rows(close.len(), length as usize)?;
+    let offset = get_offset(offset);
+
+    let skew = rolling_apply(close, length as usize, sample_skew)?;
+
+    let skew = prepared.restore(skew, offset)?;
+
+    let skew = fill_missing(skew, fill)?;
+
+    Ok(skew.with_name(format!("SKEW_{}", length).into()))
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::utils::data_loader::{
+        csv_to_dataframe, 
+        set_column_names, 
+        combine_date_time, 
+        convert_to_naive_datetime
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_skew() {
+        let mut df = csv_to_dataframe(
+            "data/AUDNZD1.csv", 
+            false
+        ).unwrap();
+        let _ = set_column_names(
+            &mut df, 
+            vec!["date", "time", "open", "high", "low", "close", "volume"]
+        );
+        let df = combine_date_time(&df).unwrap();
+        let df = convert_to_naive_datetime(
+            &df, 
+            "%Y.%m.%d %H:%M"
+        ).unwrap();
+
+        let close = df.column("close").unwrap().as_series().unwrap();
+        let skew = skew(close, None, None, None).unwrap();
+        assert_eq!(skew.name().as_str(), "SKEW_30");
+        assert_eq!(skew.f64().unwrap().get(28), None);
+        assert!(skew.f64().unwrap().get(29).is_some());
+    }
+
+    #[test]
+    fn test_sample_skew() {
+        // pd.Series([1, 2, 3, 10]).skew()
+        let skew = sample_skew(&[1.0, 2.0, 3.0, 10.0]);
+        assert!((skew - 1.763_632_614_803_888).abs() < 1e-12);
+        assert!(sample_skew(&[2.0, 2.0, 2.0]).is_nan());
+    }
+}
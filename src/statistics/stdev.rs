@@ -0,0 +1,67 @@
+use crate::utils::core::{fill_missing, FillPolicy};
+use crate::statistics::variance::variance;
+use crate::utils::error::CommandResult;
+use crate::utils::validate;
+use polars::prelude::*;
+
+
+pub fn stdev(
+    close: &Series,
+    length: Option<i32>,
+    ddof: Option<i32>,
+    offset: Option<i32>,
+    fill: Option<FillPolicy>
+) -> CommandResult<Series> {
+    let length = validate::length("length", length, 2, 30)?;
+
+    let variance = variance(close, Some(length), ddof, offset, None)?;
+
+    let stdev: Series = variance
+        .f64()?
+        .apply_values(f64::sqrt)
+        .into_series();
+
+    let stdev = fill_missing(stdev, fill)?;
+
+    Ok(stdev.with_name(format!("STDEV_{}", length).into()))
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::utils::data_loader::{
+        csv_to_dataframe, 
+        set_column_names, 
+        combine_date_time, 
+        convert_to_naive_datetime
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_stdev() {
+        let mut df = csv_to_dataframe(
+            "data/AUDNZD1.csv", 
+            false
+        ).unwrap();
+        let _ = set_column_names(
+            &mut df, 
+            vec!["date", "time", "open", "high", "low", "close", "volume"]
+        );
+        let df = combine_date_time(&df).unwrap();
+        let df = convert_to_naive_datetime(
+            &df, 
+            "%Y.%m.%d %H:%M"
+        ).unwrap();
+
+        let close = df.column("close").unwrap().as_series().unwrap();
+        let stdev = stdev(close, Some(3), Some(0), None, None).unwrap();
+        assert_eq!(stdev.name().as_str(), "STDEV_3");
+        let stdev = stdev.f64().unwrap();
+        let window = [1.08086, 1.08069, 1.08077];
+        let mean = window.iter().sum::<f64>() / 3.0;
+        let expected = (window.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / 3.0).sqrt();
+        assert!((stdev.get(2).unwrap() - expected).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,82 @@
+use crate::utils::core::{get_offset, fill_missing, FillPolicy};
+use crate::utils::missing::prepare;
+use crate::utils::error::CommandResult;
+use crate::utils::validate;
+use polars::prelude::*;
+
+
+pub fn variance(
+    close: &Series,
+    length: Option<i32>,
+    ddof: Option<i32>,
+    offset: Option<i32>,
+    fill: Option<FillPolicy>
+) -> CommandResult<Series> {
+    let prepared = prepare(&[close])?;
+    let close = &prepared.inputs[0];
+    let length = validate::length("length", length, 2, 30)?;
+    validate::min_rows(close.len(), length as usize)?;
+    let ddof = validate::parameter("ddof", ddof, 1, |ddof| ddof >= 0 && ddof < length, "below length")?;
+    let offset = get_offset(offset);
+    let close = close.cast(&DataType::Float64)?;
+
+    // the variance does not change when the values are centred on the first
+    // one, which keeps the rolling sums accurate for prices that barely move
+    // relative to their level
+    let center = close.f64()?.into_iter().flatten().next().unwrap_or(0.0);
+    let close = close - center;
+
+    let variance = close.rolling_var(RollingOptionsFixedWindow {
+        window_size: length as usize,
+        min_periods: length as usize,
+        fn_params: Some(RollingFnParams::Var(RollingVarParams { ddof: ddof as u8 })),
+        ..Default::default()
+    })?;
+
+    let variance = prepared.restore(variance, offset)?;
+
+    let variance = fill_missing(variance, fill)?;
+
+    Ok(variance.with_name(format!("VAR_{}", length).into()))
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::utils::data_loader::{
+        csv_to_dataframe, 
+        set_column_names, 
+        combine_date_time, 
+        convert_to_naive_datetime
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_variance() {
+        let mut df = csv_to_dataframe(
+            "data/AUDNZD1.csv", 
+            false
+        ).unwrap();
+        let _ = set_column_names(
+            &mut df, 
+            vec!["date", "time", "open", "high", "low", "close", "volume"]
+        );
+        let df = combine_date_time(&df).unwrap();
+        let df = convert_to_naive_datetime(
+            &df, 
+            "%Y.%m.%d %H:%M"
+        ).unwrap();
+
+        let close = df.column("close").unwrap().as_series().unwrap();
+        let variance = variance(close, Some(3), None, None, None).unwrap();
+        assert_eq!(variance.name().as_str(), "VAR_3");
+        let variance = variance.f64().unwrap();
+        assert_eq!(variance.get(1), None);
+        let window = [1.08086, 1.08069, 1.08077];
+        let mean = window.iter().sum::<f64>() / 3.0;
+        let expected = window.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / 2.0;
+        assert!((variance.get(2).unwrap() - expected).abs() < 1e-12);
+    }
+}
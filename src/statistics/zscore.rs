@@ -0,0 +1,82 @@
+use crate::overlap::sma::{sma, SmaOptions};
+use crate::statistics::stdev::stdev;
+use crate::utils::core::{get_offset, fill_missing, FillPolicy};
+use crate::utils::missing::prepare;
+use crate::utils::error::CommandResult;
+use crate::utils::validate;
+use polars::prelude::*;
+
+
+pub fn zscore(
+    close: &Series,
+    length: Option<i32>,
+    std: Option<f64>,
+    offset: Option<i32>,
+    fill: Option<FillPolicy>
+) -> CommandResult<Series> {
+    let prepared = prepare(&[close])?;
+    let close = &prepared.inputs[0];
+    let length = validate::length("length", length, 2, 30)?;
+    validate::min_rows(close.len(), length as usize)?;
+    let std = validate::positive("std", std, 1.0)?;
+    let offset = get_offset(offset);
+    let close = close.cast(&DataType::Float64)?;
+
+    let mean = sma(&close, SmaOptions {
+        length: Some(length),
+        ..Default::default()
+    })?;
+    let stdev = stdev(&close, Some(length), None, None, None)? * std;
+
+    let zscore = match &close - &mean {
+        Ok(diff) => match diff / stdev {
+            Ok(zscore) => zscore,
+            Err(_) => return Err("Failed to calculate z-score".into())
+        },
+        Err(_) => return Err("Failed to calculate difference".into())
+    };
+
+    let zscore = prepared.restore(zscore, offset)?;
+
+    let zscore = fill_missing(zscore, fill)?;
+
+    Ok(zscore.with_name(format!("ZS_{}", length).into()))
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::utils::data_loader::{
+        csv_to_dataframe, 
+        set_column_names, 
+        combine_date_time, 
+        convert_to_naive_datetime
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_zscore() {
+        let mut df = csv_to_dataframe(
+            "data/AUDNZD1.csv", 
+            false
+        ).unwrap();
+        let _ = set_column_names(
+            &mut df, 
+            vec!["date", "time", "open", "high", "low", "close", "volume"]
+        );
+        let df = combine_date_time(&df).unwrap();
+        let df = convert_to_naive_datetime(
+            &df, 
+            "%Y.%m.%d %H:%M"
+        ).unwrap();
+
+        let close = df.column("close").unwrap().as_series().unwrap();
+        let zscore = zscore(close, Some(30), None, None, None).unwrap();
+        assert_eq!(zscore.name().as_str(), "ZS_30");
+        let zscore = zscore.f64().unwrap();
+        assert_eq!(zscore.get(28), None);
+        assert!(zscore.get(29).unwrap().is_finite());
+    }
+}
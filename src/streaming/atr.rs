@@ -0,0 +1,186 @@
+use crate::streaming::{history, Leading};
+use crate::streaming::kernels::{Ewm, RollingMean};
+use crate::streaming::true_range::TrueRangeState;
+use crate::utils::error::{CommandError, CommandResult};
+use crate::utils::missing::present;
+use crate::utils::validate;
+use crate::volatility::atr::AtrOptions;
+use polars::prelude::*;
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+enum Average {
+    Ewm(Ewm),
+    Sma(RollingMean),
+}
+
+
+/// Live state of [`atr`](crate::volatility::atr::atr).
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AtrState {
+    percent: bool,
+    true_range: TrueRangeState,
+    average: Average,
+    leading: Leading,
+}
+
+
+impl AtrState {
+    /// Fails on the options the batch indicator rejects.
+    pub fn new(options: AtrOptions) -> CommandResult<Self> {
+        let length = validate::length("length", options.length, 1, 14)?;
+
+        // the same averages the batch version delegates to
+        let average = match options.mamode.as_deref() {
+            Some("ema") => Average::Ewm(Ewm::new(2.0 / (length as f64 + 1.0), false, 0)),
+            Some("sma") => Average::Sma(RollingMean::new(length as usize, length as usize)),
+            _ => Average::Ewm(Ewm::new(1.0 / length as f64, true, length as usize)),
+        };
+
+        Ok(AtrState {
+            percent: options.percent,
+            true_range: TrueRangeState::new(options.drift)?,
+            average,
+            leading: Leading::new(),
+        })
+    }
+
+    /// Feeds the next bar and returns the ATR at that bar, NaN prices are
+    /// missing.
+    pub fn update(&mut self, high: f64, low: f64, close: f64) -> Option<f64> {
+        let (high, low, close) = (present(high), present(low), present(close));
+        let true_range = self.true_range.next(high, low, close);
+
+        let atr = match &mut self.average {
+            Average::Ewm(ewm) => ewm.update(true_range),
+            Average::Sma(sma) => sma.update(true_range),
+        };
+
+        let atr = if self.percent {
+            atr.zip(close).map(|(atr, close)| atr * 100.0 / close)
+        } else {
+            atr
+        };
+        self.leading.apply(atr)
+    }
+
+    /// Replays historical bars and returns the ATR at the last one.
+    pub fn warm_up(
+        &mut self,
+        high: &Series,
+        low: &Series,
+        close: &Series
+    ) -> CommandResult<Option<f64>> {
+        let (high, low, close) = (history(high)?, history(low)?, history(close)?);
+        if let Some(got) = [low.len(), close.len()].into_iter().find(|&len| len != high.len()) {
+            return Err(CommandError::LengthMismatch { expected: high.len(), got });
+        }
+        Ok((0..close.len())
+            .fold(None, |_, i| self.update(high[i], low[i], close[i])))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::streaming::tests::{assert_identical, column, load, with_nan};
+    use crate::utils::missing::{with_missing_policy, MissingPolicy, WarmUpValue};
+    use crate::volatility::atr::{atr, AtrOptions};
+
+    use super::*;
+
+    #[test]
+    fn test_atr_state() {
+        let df = load();
+        let high = column(&df, "high");
+        let low = column(&df, "low");
+        let close = column(&df, "close");
+
+        for mamode in ["rma", "ema", "sma"] {
+            for percent in [false, true] {
+                let options = AtrOptions {
+                    mamode: Some(mamode.to_string()),
+                    percent,
+                    ..Default::default()
+                };
+                let batch = atr(&high, &low, &close, options.clone()).unwrap();
+                let mut state = AtrState::new(options).unwrap();
+                let streamed: Vec<Option<f64>> = (0..close.len())
+                    .map(|i| state.update(
+                        high.f64().unwrap().get(i).unwrap(),
+                        low.f64().unwrap().get(i).unwrap(),
+                        close.f64().unwrap().get(i).unwrap(),
+                    ))
+                    .collect();
+                assert_identical(&batch, &streamed);
+            }
+        }
+    }
+
+    #[test]
+    fn test_atr_state_nan() {
+        let df = load();
+        let high = with_nan(&column(&df, "high"), &[5, 700]);
+        let low = column(&df, "low");
+        let close = with_nan(&column(&df, "close"), &[300, 301]);
+
+        for mamode in ["rma", "ema", "sma"] {
+            for percent in [false, true] {
+                let options = AtrOptions {
+                    mamode: Some(mamode.to_string()),
+                    percent,
+                    ..Default::default()
+                };
+                let batch = atr(&high, &low, &close, options.clone()).unwrap();
+                let mut state = AtrState::new(options).unwrap();
+                let streamed: Vec<Option<f64>> = (0..close.len())
+                    .map(|i| state.update(
+                        high.f64().unwrap().get(i).unwrap(),
+                        low.f64().unwrap().get(i).unwrap(),
+                        close.f64().unwrap().get(i).unwrap(),
+                    ))
+                    .collect();
+                assert_identical(&batch, &streamed);
+            }
+        }
+    }
+
+    #[test]
+    fn test_atr_state_nan_warm_up() {
+        let df = load();
+        let high = column(&df, "high");
+        let low = column(&df, "low");
+        let close = column(&df, "close");
+
+        let policy = MissingPolicy { warm_up: WarmUpValue::NaN, ..Default::default() };
+        with_missing_policy(policy, || {
+            let batch = atr(&high, &low, &close, AtrOptions::default()).unwrap();
+            assert!(batch.f64().unwrap().get(0).unwrap().is_nan());
+            let mut state = AtrState::new(AtrOptions::default()).unwrap();
+            let streamed: Vec<Option<f64>> = (0..close.len())
+                .map(|i| state.update(
+                    high.f64().unwrap().get(i).unwrap(),
+                    low.f64().unwrap().get(i).unwrap(),
+                    close.f64().unwrap().get(i).unwrap(),
+                ))
+                .collect();
+            assert_identical(&batch, &streamed);
+        });
+    }
+
+    #[test]
+    fn test_atr_state_warm_up() {
+        let df = load();
+        let high = column(&df, "high");
+        let low = column(&df, "low");
+        let close = column(&df, "close");
+        let batch = atr(&high, &low, &close, AtrOptions::default()).unwrap();
+
+        let mut state = AtrState::new(AtrOptions::default()).unwrap();
+        let last = state.warm_up(&high, &low, &close).unwrap();
+        assert_eq!(last, batch.f64().unwrap().get(close.len() - 1));
+    }
+}
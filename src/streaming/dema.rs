@@ -0,0 +1,106 @@
+use crate::overlap::dema::DemaOptions;
+use crate::overlap::ema::EmaOptions;
+use crate::streaming::ema::EmaState;
+use crate::streaming::{history, Leading};
+use crate::streaming::kernels::Ewm;
+use crate::utils::error::CommandResult;
+use crate::utils::missing::present;
+use crate::utils::validate;
+use polars::prelude::*;
+
+
+/// Live state of [`dema`](crate::overlap::dema::dema).
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DemaState {
+    ema1: EmaState,
+    /// The EMA of the first EMA, its "sma" seed is the first EMA value itself.
+    ema2: Ewm,
+    leading: Leading,
+}
+
+
+impl DemaState {
+    /// Fails on the options the batch indicator rejects.
+    pub fn new(options: DemaOptions) -> CommandResult<Self> {
+        let length = validate::length("length", options.length, 1, 10)?;
+
+        let ema_options = EmaOptions {
+            length: Some(length),
+            ..Default::default()
+        };
+
+        Ok(DemaState {
+            ema1: EmaState::new(ema_options.clone())?,
+            ema2: Ewm::new(2.0 / (length as f64 + 1.0), ema_options.adjust, 0),
+            leading: Leading::new(),
+        })
+    }
+
+    /// Feeds the next price and returns the DEMA at that bar, a NaN price is
+    /// missing.
+    pub fn update(&mut self, price: f64) -> Option<f64> {
+        let ema1 = self.ema1.next(present(price));
+        let ema2 = self.ema2.update(ema1);
+        let dema = match (ema1, ema2) {
+            (Some(ema1), Some(ema2)) => Some(ema1 * 2.0 - ema2),
+            _ => None
+        };
+        self.leading.apply(dema)
+    }
+
+    /// Replays historical prices and returns the DEMA at the last one.
+    pub fn warm_up(&mut self, close: &Series) -> CommandResult<Option<f64>> {
+        Ok(history(close)?
+            .into_iter()
+            .fold(None, |_, price| self.update(price)))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::overlap::dema::{dema, DemaOptions};
+    use crate::streaming::tests::{assert_identical, column, load, with_nan};
+
+    use super::*;
+
+    #[test]
+    fn test_dema_state() {
+        let df = load();
+        let close = column(&df, "close");
+
+        let batch = dema(&close, DemaOptions::default()).unwrap();
+        let mut state = DemaState::new(DemaOptions::default()).unwrap();
+        let streamed: Vec<Option<f64>> = close
+            .f64()
+            .unwrap()
+            .into_no_null_iter()
+            .map(|price| state.update(price))
+            .collect();
+        assert_identical(&batch, &streamed);
+    }
+
+    #[test]
+    fn test_dema_state_nan() {
+        let close = with_nan(&column(&load(), "close"), &[3, 15, 500, 501]);
+
+        let batch = dema(&close, DemaOptions::default()).unwrap();
+        let mut state = DemaState::new(DemaOptions::default()).unwrap();
+        let streamed: Vec<Option<f64>> = close
+            .f64()
+            .unwrap()
+            .into_no_null_iter()
+            .map(|price| state.update(price))
+            .collect();
+        assert_identical(&batch, &streamed);
+        assert_eq!(streamed[500], None);
+        assert!(streamed[502].is_some());
+
+        // replayed history with the same gaps ends on the same value
+        let mut state = DemaState::new(DemaOptions::default()).unwrap();
+        let last = state.warm_up(&close.slice(0, 503)).unwrap();
+        assert_eq!(last, streamed[502]);
+    }
+}
@@ -0,0 +1,159 @@
+use crate::overlap::ema::EmaOptions;
+use crate::streaming::{history, Leading};
+use crate::streaming::kernels::Ewm;
+use crate::utils::error::CommandResult;
+use crate::utils::math::mean;
+use crate::utils::missing::present;
+use crate::utils::validate;
+use polars::prelude::*;
+
+
+/// Live state of [`ema`](crate::overlap::ema::ema).
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EmaState {
+    length: usize,
+    /// The first `length` prices while the "sma" seed is being collected.
+    seed: Option<Vec<Option<f64>>>,
+    ewm: Ewm,
+    leading: Leading,
+}
+
+
+impl EmaState {
+    /// Fails on the options the batch indicator rejects.
+    pub fn new(options: EmaOptions) -> CommandResult<Self> {
+        let length = validate::length("length", options.length, 1, 10)?;
+
+        let seed = options.seeded().then(|| Vec::with_capacity(length as usize));
+
+        Ok(EmaState {
+            length: length as usize,
+            seed,
+            ewm: Ewm::new(2.0 / (length as f64 + 1.0), options.adjust, 0),
+            leading: Leading::new(),
+        })
+    }
+
+    /// Feeds the next price and returns the EMA at that bar, a NaN price is
+    /// missing.
+    pub fn update(&mut self, price: f64) -> Option<f64> {
+        let ema = self.next(present(price));
+        self.leading.apply(ema)
+    }
+
+    /// The EMA at the next bar with plain nulls for the warm-up.
+    pub(super) fn next(&mut self, price: Option<f64>) -> Option<f64> {
+        let value = match self.seed.as_mut() {
+            Some(seed) => {
+                seed.push(price);
+                if seed.len() < self.length {
+                    None
+                } else {
+                    // the mean of the first length prices, computed as the batch does
+                    let mean = mean(seed.iter().copied().flatten());
+                    self.seed = None;
+                    mean
+                }
+            },
+            None => price,
+        };
+        self.ewm.update(value)
+    }
+
+    /// Replays historical prices and returns the EMA at the last one.
+    pub fn warm_up(&mut self, close: &Series) -> CommandResult<Option<f64>> {
+        Ok(history(close)?
+            .into_iter()
+            .fold(None, |_, price| self.update(price)))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::overlap::ema::{ema, EmaOptions};
+    use crate::streaming::tests::{assert_identical, column, load, with_nan};
+
+    use super::*;
+
+    #[test]
+    fn test_ema_state() {
+        let df = load();
+        let close = column(&df, "close");
+
+        for mamode in ["sma", "ema"] {
+            for adjust in [false, true] {
+                let options = EmaOptions {
+                    mamode: Some(mamode.to_string()),
+                    adjust,
+                    ..Default::default()
+                };
+                let batch = ema(&close, options.clone()).unwrap();
+                let mut state = EmaState::new(options).unwrap();
+                let streamed: Vec<Option<f64>> = close
+                    .f64()
+                    .unwrap()
+                    .into_no_null_iter()
+                    .map(|price| state.update(price))
+                    .collect();
+                assert_identical(&batch, &streamed);
+            }
+        }
+    }
+
+    #[test]
+    fn test_ema_state_nan() {
+        let close = Series::new("close".into(), [1.0, 2.0, 3.0, f64::NAN, 5.0, 6.0, 7.0]);
+        let options = EmaOptions {
+            length: Some(2),
+            mamode: Some("ema".to_string()),
+            ..Default::default()
+        };
+        let batch = ema(&close, options.clone()).unwrap();
+        let mut state = EmaState::new(options).unwrap();
+        let streamed: Vec<Option<f64>> = close
+            .f64()
+            .unwrap()
+            .into_no_null_iter()
+            .map(|price| state.update(price))
+            .collect();
+        assert_identical(&batch, &streamed);
+
+        // the gap stays missing and the average carries on after it
+        let expected = [Some(1.0), Some(1.667), Some(2.556), None, Some(4.651), Some(5.55), Some(6.517)];
+        for (value, expected) in streamed.iter().zip(expected) {
+            assert_eq!(value.map(|v| (v * 1000.0).round() / 1000.0), expected);
+        }
+
+        // a tick inside the seed and ones after it, both modes
+        let close = with_nan(&column(&load(), "close"), &[3, 500, 501]);
+        for mamode in ["sma", "ema"] {
+            let options = EmaOptions { mamode: Some(mamode.to_string()), ..Default::default() };
+            let batch = ema(&close, options.clone()).unwrap();
+            let mut state = EmaState::new(options).unwrap();
+            let streamed: Vec<Option<f64>> = close
+                .f64()
+                .unwrap()
+                .into_no_null_iter()
+                .map(|price| state.update(price))
+                .collect();
+            assert_identical(&batch, &streamed);
+        }
+    }
+
+    #[test]
+    fn test_ema_state_warm_up() {
+        let df = load();
+        let close = column(&df, "close");
+        let batch = ema(&close, EmaOptions::default()).unwrap();
+
+        let mut state = EmaState::new(EmaOptions::default()).unwrap();
+        let last = state.warm_up(&close.slice(0, 4000)).unwrap();
+        assert_eq!(last, batch.f64().unwrap().get(3999));
+
+        let price = close.f64().unwrap().get(4000).unwrap();
+        assert_eq!(state.update(price), batch.f64().unwrap().get(4000));
+    }
+}
@@ -0,0 +1,141 @@
+use std::collections::VecDeque;
+
+
+/// Incremental twin of the polars `ewm_mean` kernel (with `ignore_nulls`
+/// off), doing the same operations in the same order so every value matches
+/// the batch result bit for bit.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Ewm {
+    adjust: bool,
+    alpha: f64,
+    min_periods: usize,
+    old_wt: f64,
+    weighted_avg: Option<f64>,
+    non_null_count: usize,
+    started: bool,
+}
+
+
+impl Ewm {
+    pub(crate) fn new(alpha: f64, adjust: bool, min_periods: usize) -> Self {
+        Ewm {
+            adjust,
+            alpha,
+            min_periods,
+            old_wt: 1.0,
+            weighted_avg: None,
+            non_null_count: 0,
+            started: false,
+        }
+    }
+
+    pub(crate) fn update(&mut self, value: Option<f64>) -> Option<f64> {
+        let new_wt = if self.adjust { 1.0 } else { self.alpha };
+        let old_wt_factor = 1.0 - self.alpha;
+
+        if value.is_some() {
+            self.non_null_count += 1;
+        }
+
+        match self.weighted_avg {
+            Some(avg) if self.started => {
+                self.old_wt *= old_wt_factor;
+                if let Some(x) = value {
+                    if avg != x {
+                        self.weighted_avg = Some(
+                            (self.old_wt * avg + new_wt * x) / (self.old_wt + new_wt)
+                        );
+                    }
+                    self.old_wt = if self.adjust { self.old_wt + new_wt } else { 1.0 };
+                }
+            },
+            _ => self.weighted_avg = value,
+        }
+        self.started = true;
+
+        match value {
+            Some(_) if self.non_null_count >= self.min_periods => self.weighted_avg,
+            _ => None
+        }
+    }
+}
+
+
+/// Incremental twin of the polars rolling mean over data with nulls, which
+/// keeps a running sum and recomputes it when a non-finite value leaves.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct RollingMean {
+    window: usize,
+    min_periods: usize,
+    values: VecDeque<Option<f64>>,
+    sum: Option<f64>,
+    null_count: usize,
+}
+
+
+impl RollingMean {
+    pub(crate) fn new(window: usize, min_periods: usize) -> Self {
+        RollingMean {
+            window,
+            min_periods,
+            values: VecDeque::with_capacity(window),
+            sum: None,
+            null_count: 0,
+        }
+    }
+
+    pub(crate) fn update(&mut self, value: Option<f64>) -> Option<f64> {
+        // a window of one never overlaps the previous one
+        let mut recompute = self.window == 1;
+
+        if self.values.len() == self.window {
+            match self.values.pop_front().flatten() {
+                Some(leaving) if leaving.is_finite() => {
+                    self.sum = self.sum.map(|sum| sum - leaving);
+                },
+                Some(_) => recompute = true,
+                None => {
+                    self.null_count -= 1;
+                    if self.sum.is_none() {
+                        recompute = true;
+                    }
+                }
+            }
+        }
+        self.values.push_back(value);
+
+        if recompute {
+            self.sum = None;
+            self.null_count = 0;
+            for value in self.values.iter() {
+                match value {
+                    Some(value) => {
+                        self.sum = Some(match self.sum {
+                            Some(sum) => value + sum,
+                            None => *value,
+                        });
+                    },
+                    None => self.null_count += 1,
+                }
+            }
+        } else {
+            match value {
+                Some(value) => {
+                    self.sum = Some(match self.sum {
+                        Some(sum) => sum + value,
+                        None => value,
+                    });
+                },
+                None => self.null_count += 1,
+            }
+        }
+
+        let count = self.values.len() - self.null_count;
+        if count < self.min_periods {
+            return None;
+        }
+        self.sum.map(|sum| sum / count as f64)
+    }
+}
@@ -0,0 +1,171 @@
+//! Incremental indicator state for live bars.
+//!
+//! Each state is built by `new`, which rejects the options the batch indicator
+//! rejects under the validation mode in effect, and is fed one bar at a time
+//! through `update`. It returns the same value, bit for bit, as the batch
+//! indicator over the full history would at that bar, warm-up values included
+//! as the missing value policy in effect at construction writes them. `warm_up` replays historical bars before the
+//! live feed starts. Offsets and fills are not applied, a live value has no
+//! later bar to be shifted to or filled from. A NaN input is a missing value
+//! as in the batch indicators, where it is a gap under the `Propagate`
+//! interior policy: the outputs it touches are missing and the state carries
+//! on after it.
+//! With the `serde` feature the states can be serialized and restored, the
+//! format has to round-trip floats exactly for the values to stay identical.
+
+mod kernels;
+pub mod ema;
+pub mod dema;
+pub mod true_range;
+pub mod atr;
+pub mod rsi;
+
+pub use ema::EmaState;
+pub use dema::DemaState;
+pub use true_range::TrueRangeState;
+pub use atr::AtrState;
+pub use rsi::RsiState;
+
+use crate::utils::error::CommandResult;
+use crate::utils::missing::{missing_policy, WarmUpValue};
+use polars::prelude::*;
+
+
+/// Writes the outputs before the first value, and undefined (NaN) results,
+/// the way the batch indicators write them under the missing value policy.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Leading {
+    value: WarmUpValue,
+    done: bool,
+}
+
+
+impl Leading {
+    fn new() -> Self {
+        Leading {
+            value: missing_policy().warm_up,
+            done: false,
+        }
+    }
+
+    fn apply(&mut self, output: Option<f64>) -> Option<f64> {
+        let missing = match self.value {
+            WarmUpValue::Null => None,
+            WarmUpValue::NaN => Some(f64::NAN),
+        };
+        if !self.done {
+            if output.is_none_or(f64::is_nan) {
+                return missing;
+            }
+            self.done = true;
+        }
+        match output {
+            Some(value) if value.is_nan() => missing,
+            output => output,
+        }
+    }
+}
+
+
+/// The values of a historical series, nulls as NaN so they are replayed as
+/// the missing values they are.
+fn history(series: &Series) -> CommandResult<Vec<f64>> {
+    let series = series.cast(&DataType::Float64)?;
+    Ok(series
+        .f64()?
+        .into_iter()
+        .map(|value| value.unwrap_or(f64::NAN))
+        .collect())
+}
+
+
+#[cfg(test)]
+pub(crate) mod tests {
+
+    use crate::utils::data_loader::{
+        csv_to_dataframe,
+        set_column_names,
+        combine_date_time,
+        convert_to_naive_datetime
+    };
+    use polars::prelude::*;
+
+    pub(crate) fn load() -> DataFrame {
+        let mut df = csv_to_dataframe(
+            "data/AUDNZD1.csv",
+            false
+        ).unwrap();
+        let _ = set_column_names(
+            &mut df,
+            vec!["date", "time", "open", "high", "low", "close", "volume"]
+        );
+        let df = combine_date_time(&df).unwrap();
+        convert_to_naive_datetime(
+            &df,
+            "%Y.%m.%d %H:%M"
+        ).unwrap()
+    }
+
+    pub(crate) fn column(df: &DataFrame, name: &str) -> Series {
+        df.column(name).unwrap().as_series().unwrap().slice(0, 5000)
+    }
+
+    #[test]
+    fn test_state_validation() {
+        use crate::momentum::rsi::RsiOptions;
+        use crate::overlap::dema::DemaOptions;
+        use crate::overlap::ema::EmaOptions;
+        use crate::utils::error::CommandError;
+        use crate::utils::validate::{with_validation, Validation};
+        use crate::volatility::atr::AtrOptions;
+        use super::*;
+
+        let invalid = |result: CommandResult<()>| matches!(result, Err(CommandError::InvalidParameter { .. }));
+        assert!(invalid(EmaState::new(EmaOptions { length: Some(0), ..Default::default() }).map(|_| ())));
+        assert!(invalid(DemaState::new(DemaOptions { length: Some(-1), ..Default::default() }).map(|_| ())));
+        assert!(invalid(TrueRangeState::new(Some(0)).map(|_| ())));
+        assert!(invalid(AtrState::new(AtrOptions { drift: Some(0), ..Default::default() }).map(|_| ())));
+        assert!(invalid(RsiState::new(RsiOptions { length: Some(0), ..Default::default() }).map(|_| ())));
+
+        // leniently the default length is used, as in the batch indicator
+        let lenient = with_validation(Validation::Lenient, || {
+            EmaState::new(EmaOptions { length: Some(0), ..Default::default() })
+        });
+        let default = EmaState::new(EmaOptions::default());
+        assert_eq!(lenient.unwrap(), default.unwrap());
+    }
+
+    /// `series` with NaN ticks at `rows`.
+    pub(crate) fn with_nan(series: &Series, rows: &[usize]) -> Series {
+        let values: Series = series
+            .f64()
+            .unwrap()
+            .into_no_null_iter()
+            .enumerate()
+            .map(|(i, value)| if rows.contains(&i) { f64::NAN } else { value })
+            .collect();
+        values.with_name(series.name().clone())
+    }
+
+    #[test]
+    fn test_history_missing() {
+        let series = Series::new("close".into(), [Some(1.0), None, Some(f64::NAN)]);
+        let values = super::history(&series).unwrap();
+        assert_eq!(values[0], 1.0);
+        assert!(values[1].is_nan() && values[2].is_nan());
+    }
+
+    /// Asserts the streamed values equal the batch series bit for bit.
+    pub(crate) fn assert_identical(batch: &Series, streamed: &[Option<f64>]) {
+        let batch = batch.f64().unwrap();
+        assert_eq!(batch.len(), streamed.len());
+        for (i, (expected, actual)) in batch.into_iter().zip(streamed).enumerate() {
+            assert_eq!(
+                expected.map(f64::to_bits),
+                actual.map(f64::to_bits),
+                "bar {}: batch {:?}, streamed {:?}", i, expected, actual
+            );
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use crate::momentum::rsi::RsiOptions;
+use crate::streaming::{history, Leading};
+use crate::streaming::kernels::Ewm;
+use crate::utils::error::CommandResult;
+use crate::utils::missing::present;
+use crate::utils::validate;
+use polars::prelude::*;
+use std::collections::VecDeque;
+
+
+/// Live state of [`rsi`](crate::momentum::rsi::rsi).
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RsiState {
+    scalar: f64,
+    drift: usize,
+    /// The last `drift` closes, oldest first.
+    closes: VecDeque<Option<f64>>,
+    positive: Ewm,
+    negative: Ewm,
+    leading: Leading,
+}
+
+
+impl RsiState {
+    /// Fails on the options the batch indicator rejects.
+    pub fn new(options: RsiOptions) -> CommandResult<Self> {
+        let length = validate::length("length", options.length, 1, 14)?;
+        let drift = validate::length("drift", options.drift, 1, 1)? as usize;
+        let rma = Ewm::new(1.0 / length as f64, true, length as usize);
+
+        Ok(RsiState {
+            scalar: options.scalar.unwrap_or(100.0),
+            drift,
+            closes: VecDeque::with_capacity(drift + 1),
+            positive: rma.clone(),
+            negative: rma,
+            leading: Leading::new(),
+        })
+    }
+
+    /// Feeds the next close and returns the RSI at that bar, a NaN close is
+    /// missing.
+    pub fn update(&mut self, close: f64) -> Option<f64> {
+        let close = present(close);
+        let prev_close = if self.closes.len() == self.drift {
+            self.closes.pop_front().flatten()
+        } else {
+            None
+        };
+        self.closes.push_back(close);
+
+        let change = close.zip(prev_close).map(|(close, prev_close)| close - prev_close);
+        let positive = self.positive.update(change.map(|v| if v < 0.0 { 0.0 } else { v }));
+        let negative = self.negative.update(change.map(|v| if v > 0.0 { 0.0 } else { v }));
+
+        let rsi = match (positive, negative) {
+            (Some(positive), Some(negative)) => {
+                Some(positive / (positive + negative.abs()) * self.scalar)
+            },
+            _ => None
+        };
+        self.leading.apply(rsi)
+    }
+
+    /// Replays historical closes and returns the RSI at the last one.
+    pub fn warm_up(&mut self, close: &Series) -> CommandResult<Option<f64>> {
+        Ok(history(close)?
+            .into_iter()
+            .fold(None, |_, close| self.update(close)))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::momentum::rsi::{rsi, RsiOptions};
+    use crate::streaming::tests::{assert_identical, column, load, with_nan};
+
+    use super::*;
+
+    #[test]
+    fn test_rsi_state() {
+        let df = load();
+        let close = column(&df, "close");
+
+        for drift in [1, 2] {
+            let options = RsiOptions {
+                drift: Some(drift),
+                ..Default::default()
+            };
+            let batch = rsi(&close, options.clone()).unwrap();
+            let mut state = RsiState::new(options).unwrap();
+            let streamed: Vec<Option<f64>> = close
+                .f64()
+                .unwrap()
+                .into_no_null_iter()
+                .map(|close| state.update(close))
+                .collect();
+            assert_identical(&batch, &streamed);
+        }
+    }
+
+    #[test]
+    fn test_rsi_state_nan() {
+        let close = with_nan(&column(&load(), "close"), &[3, 500, 501]);
+
+        for drift in [1, 2] {
+            let options = RsiOptions {
+                drift: Some(drift),
+                ..Default::default()
+            };
+            let batch = rsi(&close, options.clone()).unwrap();
+            let mut state = RsiState::new(options).unwrap();
+            let streamed: Vec<Option<f64>> = close
+                .f64()
+                .unwrap()
+                .into_no_null_iter()
+                .map(|close| state.update(close))
+                .collect();
+            assert_identical(&batch, &streamed);
+            assert!(streamed[600].is_some());
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_rsi_state_restore() {
+        let df = load();
+        let close = column(&df, "close");
+        let batch = rsi(&close, RsiOptions::default()).unwrap();
+
+        let mut state = RsiState::new(RsiOptions::default()).unwrap();
+        state.warm_up(&close.slice(0, 4000)).unwrap();
+        let saved = serde_json::to_string(&state).unwrap();
+
+        let mut restored: RsiState = serde_json::from_str(&saved).unwrap();
+        assert_eq!(restored, state);
+        let price = close.f64().unwrap().get(4000).unwrap();
+        assert_eq!(restored.update(price), batch.f64().unwrap().get(4000));
+    }
+}
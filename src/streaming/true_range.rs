@@ -0,0 +1,132 @@
+use crate::streaming::{history, Leading};
+use crate::utils::error::{CommandError, CommandResult};
+use crate::utils::missing::present;
+use crate::utils::validate;
+use polars::prelude::*;
+use std::collections::VecDeque;
+
+
+/// Live state of [`true_range`](crate::volatility::true_range::true_range).
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TrueRangeState {
+    drift: usize,
+    /// The last `drift` closes, oldest first.
+    closes: VecDeque<Option<f64>>,
+    leading: Leading,
+}
+
+
+impl TrueRangeState {
+    /// Fails on the drift the batch indicator rejects.
+    pub fn new(drift: Option<i32>) -> CommandResult<Self> {
+        let drift = validate::length("drift", drift, 1, 1)? as usize;
+        Ok(TrueRangeState {
+            drift,
+            closes: VecDeque::with_capacity(drift + 1),
+            leading: Leading::new(),
+        })
+    }
+
+    /// Feeds the next bar and returns its true range, the first `drift`
+    /// bars are the warm-up. NaN prices are missing.
+    pub fn update(&mut self, high: f64, low: f64, close: f64) -> Option<f64> {
+        let true_range = self.next(present(high), present(low), present(close));
+        self.leading.apply(true_range)
+    }
+
+    /// The true range of the next bar with plain nulls for the warm-up.
+    pub(super) fn next(
+        &mut self,
+        high: Option<f64>,
+        low: Option<f64>,
+        close: Option<f64>
+    ) -> Option<f64> {
+        let prev_close = if self.closes.len() == self.drift {
+            self.closes.pop_front().flatten()
+        } else {
+            None
+        };
+        self.closes.push_back(close);
+
+        let (high, low, prev_close) = (high?, low?, prev_close?);
+
+        let mut high_low_range = high - low;
+        if high_low_range == 0.0 {
+            high_low_range += f64::EPSILON;
+        }
+
+        let true_range = high_low_range.abs()
+            .max((high - prev_close).abs())
+            .max((prev_close - low).abs());
+        Some(true_range)
+    }
+
+    /// Replays historical bars and returns the true range at the last one.
+    pub fn warm_up(
+        &mut self,
+        high: &Series,
+        low: &Series,
+        close: &Series
+    ) -> CommandResult<Option<f64>> {
+        let (high, low, close) = (history(high)?, history(low)?, history(close)?);
+        if let Some(got) = [low.len(), close.len()].into_iter().find(|&len| len != high.len()) {
+            return Err(CommandError::LengthMismatch { expected: high.len(), got });
+        }
+        Ok((0..close.len())
+            .fold(None, |_, i| self.update(high[i], low[i], close[i])))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::streaming::tests::{assert_identical, column, load, with_nan};
+    use crate::volatility::true_range::true_range;
+
+    use super::*;
+
+    #[test]
+    fn test_true_range_state() {
+        let df = load();
+        let high = column(&df, "high");
+        let low = column(&df, "low");
+        let close = column(&df, "close");
+
+        for drift in [1, 3] {
+            let batch = true_range(&high, &low, &close, Some(drift), None, None).unwrap();
+            let mut state = TrueRangeState::new(Some(drift)).unwrap();
+            let streamed: Vec<Option<f64>> = (0..close.len())
+                .map(|i| state.update(
+                    high.f64().unwrap().get(i).unwrap(),
+                    low.f64().unwrap().get(i).unwrap(),
+                    close.f64().unwrap().get(i).unwrap(),
+                ))
+                .collect();
+            assert_identical(&batch, &streamed);
+        }
+    }
+
+    #[test]
+    fn test_true_range_state_nan() {
+        let df = load();
+        let high = with_nan(&column(&df, "high"), &[10]);
+        let low = column(&df, "low");
+        let close = with_nan(&column(&df, "close"), &[20, 21]);
+
+        for drift in [1, 3] {
+            let batch = true_range(&high, &low, &close, Some(drift), None, None).unwrap();
+            let mut state = TrueRangeState::new(Some(drift)).unwrap();
+            let streamed: Vec<Option<f64>> = (0..close.len())
+                .map(|i| state.update(
+                    high.f64().unwrap().get(i).unwrap(),
+                    low.f64().unwrap().get(i).unwrap(),
+                    close.f64().unwrap().get(i).unwrap(),
+                ))
+                .collect();
+            assert_identical(&batch, &streamed);
+            assert_eq!(streamed[10], None);
+        }
+    }
+}
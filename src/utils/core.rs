@@ -1,6 +1,5 @@
 use polars::prelude::*;
-use crate::utils::error::CommandResult;
-use polars::export::chrono::{DateTime, Utc};
+use crate::utils::error::{CommandError, CommandResult};
 
 pub fn get_drift(drift: Option<i32>) -> i32 {
     match drift {
@@ -10,52 +9,247 @@ pub fn get_drift(drift: Option<i32>) -> i32 {
 }
 
 
-pub fn get_offset(x: Option<i32>) -> i32 {
+pub fn get_length(x: Option<i32>, default: i32) -> i32 {
     match x {
-        Some(val) if val.is_positive() => val,
-        _ => 0,
+        Some(val) if val > 0 => val,
+        _ => default,
+    }
+}
+
+
+/// The bars to shift an output by, negative offsets shift it backward.
+pub fn get_offset(x: Option<i32>) -> i32 {
+    x.unwrap_or(0)
+}
+
+
+/// The leading rows of an output with `lookback` unstable rows once shifted
+/// by `offset`, fewer when shifted backward.
+pub(crate) fn shifted_lookback(lookback: usize, offset: Option<i32>) -> usize {
+    (lookback as i64 + get_offset(offset) as i64).max(0) as usize
+}
+
+
+/// How the missing values of an indicator are filled, pandas-ta's `fillna`
+/// value or `fill_method`. NaN counts as missing, as it does in pandas.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "FillForm", into = "FillForm"))]
+pub enum FillPolicy {
+    Value(f64),
+    Forward,
+    Backward,
+    Zero,
+    Mean,
+}
+
+
+/// The serde form of `FillPolicy`: a method by name or `{"Value": 0.5}`.
+/// Derived, YAML would write the value as a `!Value` tag, which does not
+/// read back inside the internally tagged `IndicatorSpec`.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+enum FillForm {
+    Value {
+        #[serde(rename = "Value")]
+        value: f64,
+    },
+    Method(FillMethod),
+}
+
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+enum FillMethod {
+    Forward,
+    Backward,
+    Zero,
+    Mean,
+}
+
+
+#[cfg(feature = "serde")]
+impl From<FillForm> for FillPolicy {
+    fn from(form: FillForm) -> Self {
+        match form {
+            FillForm::Value { value } => FillPolicy::Value(value),
+            FillForm::Method(FillMethod::Forward) => FillPolicy::Forward,
+            FillForm::Method(FillMethod::Backward) => FillPolicy::Backward,
+            FillForm::Method(FillMethod::Zero) => FillPolicy::Zero,
+            FillForm::Method(FillMethod::Mean) => FillPolicy::Mean,
+        }
+    }
+}
+
+
+#[cfg(feature = "serde")]
+impl From<FillPolicy> for FillForm {
+    fn from(fill: FillPolicy) -> Self {
+        match fill {
+            FillPolicy::Value(value) => FillForm::Value { value },
+            FillPolicy::Forward => FillForm::Method(FillMethod::Forward),
+            FillPolicy::Backward => FillForm::Method(FillMethod::Backward),
+            FillPolicy::Zero => FillForm::Method(FillMethod::Zero),
+            FillPolicy::Mean => FillForm::Method(FillMethod::Mean),
+        }
     }
 }
 
 
-fn to_i64<'a>(v: &AnyValue<'a>) -> i64 {
+/// Fills the missing values of an indicator output, after offset shifting.
+pub fn fill_missing(series: Series, fill: Option<FillPolicy>) -> CommandResult<Series> {
+    let fill = match fill {
+        Some(fill) => fill,
+        None => return Ok(series)
+    };
+
+    let series = if series.dtype().is_float() {
+        let values: Series = series
+            .cast(&DataType::Float64)?
+            .f64()?
+            .into_iter()
+            .map(|value| value.filter(|v| !v.is_nan()))
+            .collect();
+        values.cast(series.dtype())?.with_name(series.name().clone())
+    } else {
+        series
+    };
+
+    let filled = match fill {
+        FillPolicy::Value(value) => {
+            let value = Series::new(series.name().clone(), [value])
+                .cast(series.dtype())?
+                .new_from_index(0, series.len());
+            series.zip_with(&series.is_not_null(), &value)?
+        },
+        FillPolicy::Forward => series.fill_null(FillNullStrategy::Forward(None))?,
+        FillPolicy::Backward => series.fill_null(FillNullStrategy::Backward(None))?,
+        FillPolicy::Zero => series.fill_null(FillNullStrategy::Zero)?,
+        FillPolicy::Mean => series.fill_null(FillNullStrategy::Mean)?,
+    };
+
+    Ok(filled)
+}
+
+
+/// `fill_missing` for every column of a multi output indicator.
+pub fn fill_missing_frame(df: DataFrame, fill: Option<FillPolicy>) -> CommandResult<DataFrame> {
+    if fill.is_none() {
+        return Ok(df);
+    }
+    let columns = df
+        .get_columns()
+        .iter()
+        .map(|column| {
+            let series = column.as_materialized_series().clone();
+            Ok(fill_missing(series, fill)?.into_column())
+        })
+        .collect::<CommandResult<Vec<Column>>>()?;
+    Ok(DataFrame::new(columns)?)
+}
+
+
+pub fn to_i64(v: &AnyValue<'_>) -> CommandResult<i64> {
     if let AnyValue::Int64(i) = v {
-        *i
+        Ok(*i)
     } else {
-        panic!("not an i64");
+        Err(CommandError::InvalidDtype {
+            name: "value".to_string(),
+            expected: "i64".to_string(),
+            got: v.dtype().to_string(),
+        })
     }
 }
 
 
-fn is_percent(x: Option<f64>) -> bool {
-    match x {
-        Some(val) if val >= 0.0 && val <= 100.0 => true,
-        _ => false,
+pub fn is_percent(x: Option<f64>) -> bool {
+    matches!(x, Some(val) if (0.0..=100.0).contains(&val))
+}
+
+
+/// The common length of `inputs`, or `LengthMismatch` for the first series
+/// that differs from the first one.
+pub fn same_length(inputs: &[&Series]) -> CommandResult<usize> {
+    let expected = inputs.first().map_or(0, |series| series.len());
+    match inputs.iter().find(|series| series.len() != expected) {
+        Some(series) => Err(CommandError::LengthMismatch { expected, got: series.len() }),
+        None => Ok(expected)
     }
 }
 
 
+/// `high - low` with zero ranges as epsilon, like pandas-ta's
+/// `non_zero_range`, named `non_zero_range`.
 pub fn non_zero_range(high: &Series, low: &Series) -> CommandResult<Series> {
-    let diff = match high - low {
-        Ok(diff) => diff,
-        Err(_) => return Err("Failed to calculate difference".into()),
-    };
-    // diff to vector
-    let diff = diff.f64()?.into_iter().collect::<Vec<_>>();
-    // check if any value is zero and add epsilon if true
-    let diff = diff.into_iter().map(|val| {
-        if val == Some(0.0) {
-            Some(val.unwrap() + f64::EPSILON)
-        } else {
-            val
-        }
+    same_length(&[high, low])?;
+    let high = high.cast(&DataType::Float64)?;
+    let low = low.cast(&DataType::Float64)?;
+    let diff: Float64Chunked = arity::binary_elementwise_values(high.f64()?, low.f64()?, |high, low| {
+        non_zero(high - low)
+    });
+    Ok(diff.into_series().with_name("non_zero_range".into()))
+}
+
+
+/// A zero difference becomes epsilon so it can divide.
+pub(crate) fn non_zero(diff: f64) -> f64 {
+    if diff == 0.0 {
+        f64::EPSILON
+    } else {
+        diff
+    }
+}
+
+
+pub fn signed_series(series: &Series, initial: Option<f64>) -> CommandResult<Series> {
+    let series = series.cast(&DataType::Float64)?;
+    let series = series.f64()?;
+    // sign of the one step difference, the first value has no predecessor
+    let mut prev: Option<f64> = None;
+    let signed = series.into_iter().enumerate().map(|(i, val)| {
+        let sign = match (prev, val) {
+            _ if i == 0 => initial,
+            (Some(p), Some(v)) if v > p => Some(1.0),
+            (Some(p), Some(v)) if v < p => Some(-1.0),
+            (Some(_), Some(_)) => Some(0.0),
+            _ => None,
+        };
+        prev = val;
+        sign
     }).collect::<Vec<_>>();
-    // vector to series
-    let diff = Series::new("diff".into(), diff);
-    Ok(diff)
+    Ok(Series::new("sign".into(), signed))
 }
 
 
+/// `f` over each window of `length` values with polars' `rolling_map`, a
+/// window is only evaluated once it is full and free of nulls.
+pub fn rolling_apply<F>(series: &Series, length: usize, f: F) -> CommandResult<Series>
+where
+    F: Fn(&[f64]) -> f64,
+{
+    let series = series.cast(&DataType::Float64)?;
+    if length == 0 {
+        return Ok(Series::full_null(series.name().clone(), series.len(), &DataType::Float64));
+    }
+    let options = RollingOptionsFixedWindow {
+        window_size: length,
+        min_periods: length,
+        ..Default::default()
+    };
+    // the window is a single chunk without nulls, so it is one slice
+    let applied = series.rolling_map(
+        &|window: &Series| {
+            let value = window.f64().ok().and_then(|window| window.cont_slice().ok()).map(&f);
+            Series::new(PlSmallStr::EMPTY, [value])
+        },
+        options
+    )?;
+    Ok(applied)
+}
+
+
+#[cfg(test)]
 mod tests {
     use crate::utils::data_loader::{
         csv_to_dataframe, 
@@ -74,22 +268,47 @@ mod tests {
         assert_eq!(get_drift(None), 1);
     }
 
+    #[test]
+    fn test_get_length() {
+        assert_eq!(get_length(Some(5), 10), 5);
+        assert_eq!(get_length(Some(0), 10), 10);
+        assert_eq!(get_length(None, 10), 10);
+    }
+
     #[test]
     fn test_get_offset() {
         assert_eq!(get_offset(Some(1)), 1);
         assert_eq!(get_offset(Some(0)), 0);
-        assert_eq!(get_offset(Some(-1)), 0);
+        assert_eq!(get_offset(Some(-1)), -1);
         assert_eq!(get_offset(None), 0);
     }
 
+    #[test]
+    fn test_fill_missing() {
+        let series = Series::new("x".into(), [None, Some(1.0), Some(f64::NAN), Some(3.0), None]);
+        let filled = |fill| {
+            let filled = fill_missing(series.clone(), Some(fill)).unwrap();
+            filled.f64().unwrap().into_iter().collect::<Vec<_>>()
+        };
+        assert_eq!(filled(FillPolicy::Value(9.0)), [Some(9.0), Some(1.0), Some(9.0), Some(3.0), Some(9.0)]);
+        assert_eq!(filled(FillPolicy::Forward), [None, Some(1.0), Some(1.0), Some(3.0), Some(3.0)]);
+        assert_eq!(filled(FillPolicy::Backward), [Some(1.0), Some(1.0), Some(3.0), Some(3.0), None]);
+        assert_eq!(filled(FillPolicy::Zero), [Some(0.0), Some(1.0), Some(0.0), Some(3.0), Some(0.0)]);
+        assert_eq!(filled(FillPolicy::Mean), [Some(2.0), Some(1.0), Some(2.0), Some(3.0), Some(2.0)]);
+
+        let series = Series::new("x".into(), [None, Some(100), Some(0)]);
+        let filled = fill_missing(series, Some(FillPolicy::Value(0.0))).unwrap();
+        assert_eq!(filled.i32().unwrap().into_iter().collect::<Vec<_>>(), [Some(0), Some(100), Some(0)]);
+    }
+
     #[test]
     fn test_is_percent() {
-        assert_eq!(is_percent(Some(0.0)), true);
-        assert_eq!(is_percent(Some(100.0)), true);
-        assert_eq!(is_percent(Some(50.0)), true);
-        assert_eq!(is_percent(Some(-1.0)), false);
-        assert_eq!(is_percent(Some(101.0)), false);
-        assert_eq!(is_percent(None), false);
+        assert!(is_percent(Some(0.0)));
+        assert!(is_percent(Some(100.0)));
+        assert!(is_percent(Some(50.0)));
+        assert!(!is_percent(Some(-1.0)));
+        assert!(!is_percent(Some(101.0)));
+        assert!(!is_percent(None));
     }
 
     #[test]
@@ -110,6 +329,38 @@ mod tests {
         let high = df.column("high").unwrap().as_series().unwrap();
         let low = df.column("low").unwrap().as_series().unwrap();
         let diff = non_zero_range(high, low).unwrap();
-        println!("{:?}", diff);
+        assert_eq!(diff.name().as_str(), "non_zero_range");
+        assert_eq!(diff.len(), high.len());
+        let (high, low) = (high.f64().unwrap(), low.f64().unwrap());
+        for (i, value) in diff.f64().unwrap().into_iter().enumerate() {
+            let range = high.get(i).unwrap() - low.get(i).unwrap();
+            let expected = if range == 0.0 { f64::EPSILON } else { range };
+            assert_eq!(value, Some(expected), "row {}", i);
+        }
+
+        let high = Series::new("high".into(), [Some(2.0), Some(1.5), None]);
+        let low = Series::new("low".into(), [Some(1.0), Some(1.5), Some(1.0)]);
+        let diff: Vec<_> = non_zero_range(&high, &low).unwrap().f64().unwrap().into_iter().collect();
+        assert_eq!(diff, vec![Some(1.0), Some(f64::EPSILON), None]);
+    }
+
+    #[test]
+    fn test_signed_series() {
+        let series = Series::new("x".into(), vec![1.0, 2.0, 2.0, 1.0]);
+        let signed = signed_series(&series, Some(1.0)).unwrap();
+        let signed: Vec<_> = signed.f64().unwrap().into_iter().collect();
+        assert_eq!(signed, vec![Some(1.0), Some(1.0), Some(0.0), Some(-1.0)]);
+    }
+
+    #[test]
+    fn test_rolling_apply() {
+        let series = Series::new("x".into(), vec![Some(1.0), Some(2.0), Some(3.0), None, Some(5.0)]);
+        let sums = rolling_apply(&series, 2, |w| w.iter().sum()).unwrap();
+        let sums: Vec<_> = sums.f64().unwrap().into_iter().collect();
+        assert_eq!(sums, vec![None, Some(3.0), Some(5.0), None, None]);
+        // windows longer than the input are never full
+        let sums = rolling_apply(&series, 6, |w| w.iter().sum()).unwrap();
+        assert_eq!(sums.len(), 5);
+        assert_eq!(sums.null_count(), 5);
     }
 }
\ No newline at end of file
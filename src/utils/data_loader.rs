@@ -1,6 +1,7 @@
 use polars::prelude::*;
 use polars::export::chrono::NaiveDateTime;
 use std::path::{self, Path};
+use crate::utils::error::{CommandError, CommandResult};
 
 
 pub fn csv_to_dataframe(path: &str, has_header: bool) -> PolarsResult<DataFrame> {
@@ -17,28 +18,31 @@ pub fn set_column_names(df: &mut DataFrame, names: Vec<&str>) -> Result<(), Pola
 }
 
 
-pub fn combine_date_time(df: &DataFrame) -> Result<DataFrame, PolarsError> {
+/// The named column, or `MissingColumn` if the frame has none.
+fn column<'a>(df: &'a DataFrame, name: &str) -> CommandResult<&'a Column> {
+    df.column(name).map_err(|_| CommandError::MissingColumn(name.to_string()))
+}
+
+
+pub fn combine_date_time(df: &DataFrame) -> CommandResult<DataFrame> {
     let mut df = df.clone();
-    let date = df.column("date").unwrap();
-    let time = df.column("time").unwrap();
+    let date = column(&df, "date")?;
+    let time = column(&df, "time")?;
 
     // Combine date and time columns into one datetime column
     let datetime: Vec<_> = date
         .str()?
         .into_iter()
         .zip(time.str()?)
-        .map(|(date, time)| {
-            let date = match date {
-                Some(date) => date,
-                None => panic!("Failed to get date")
-            };
-            let time = match time {
-                Some(time) => time,
-                None => panic!("Failed to get time")
-            };
-            let datetime = format!("{} {}", date, time);
-            datetime
-        }).collect();
+        .enumerate()
+        .map(|(row, (date, time))| match (date, time) {
+            (Some(date), Some(time)) => Ok(format!("{} {}", date, time)),
+            (date, time) => Err(CommandError::ParseError {
+                row,
+                value: format!("{} {}", date.unwrap_or("null"), time.unwrap_or("null")),
+            }),
+        })
+        .collect::<CommandResult<_>>()?;
 
     // datetime to series
     let datetime_series = Series::new("datetime".into(), datetime);
@@ -50,19 +54,21 @@ pub fn combine_date_time(df: &DataFrame) -> Result<DataFrame, PolarsError> {
 }
 
 
-pub fn convert_to_naive_datetime(df: &DataFrame, datetime_fmt: &str) -> Result<DataFrame, PolarsError> {
+pub fn convert_to_naive_datetime(df: &DataFrame, datetime_fmt: &str) -> CommandResult<DataFrame> {
     let mut df = df.clone();
-    let datetime = df.column("datetime").unwrap();
+    let datetime = column(&df, "datetime")?;
     let datetime: Vec<NaiveDateTime> = datetime.str()?
         .into_iter()
-        .map(|datetime_str| {
-            if let Some(datetime_str) = datetime_str {
-                let naive = NaiveDateTime::parse_from_str(datetime_str, datetime_fmt).unwrap();
-                naive
-            } else {
-                panic!("Failed to parse datetime")
-            }
-        }).collect();
+        .enumerate()
+        .map(|(row, datetime_str)| {
+            datetime_str
+                .and_then(|datetime_str| NaiveDateTime::parse_from_str(datetime_str, datetime_fmt).ok())
+                .ok_or_else(|| CommandError::ParseError {
+                    row,
+                    value: datetime_str.unwrap_or("null").to_string(),
+                })
+        })
+        .collect::<CommandResult<_>>()?;
 
     let datetime_series = Series::new("datetime".into(), datetime);
     df.replace("datetime", datetime_series)?;
@@ -70,6 +76,7 @@ pub fn convert_to_naive_datetime(df: &DataFrame, datetime_fmt: &str) -> Result<D
 }
 
 
+#[cfg(test)]
 mod tests {
     use super::*;
 
@@ -118,4 +125,30 @@ mod tests {
         let df = convert_to_naive_datetime(&df, "%Y.%m.%d %H:%M").unwrap();
         println!("{:?}", df);
     }
+
+    #[test]
+    fn test_parse_errors() {
+        let df = df!(
+            "date" => [Some("2020.01.01"), None],
+            "time" => ["00:00", "00:01"]
+        ).unwrap();
+        assert!(matches!(
+            combine_date_time(&df),
+            Err(CommandError::ParseError { row: 1, .. })
+        ));
+
+        let df = df!("datetime" => ["2020.01.01 00:00", "2020.13.01 00:00"]).unwrap();
+        match convert_to_naive_datetime(&df, "%Y.%m.%d %H:%M") {
+            Err(CommandError::ParseError { row, value }) => {
+                assert_eq!(row, 1);
+                assert_eq!(value, "2020.13.01 00:00");
+            },
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        assert!(matches!(
+            convert_to_naive_datetime(&df!("date" => ["2020.01.01"]).unwrap(), "%Y.%m.%d"),
+            Err(CommandError::MissingColumn(name)) if name == "datetime"
+        ));
+    }
 }
\ No newline at end of file
@@ -1,50 +1,63 @@
 use polars::export::chrono::DateTime;
 use polars::export::chrono::Utc;
 use polars::prelude::*;
+use crate::utils::error::{CommandError, CommandResult};
 
 
-fn is_datetime64_any_dtype(s: &Column) -> bool {
-    match s.dtype() {
-        DataType::Date | DataType::Datetime(..) => true,
-        _ => false,
-    }
+const MILLISECONDS_IN_DAY: i64 = 86_400_000;
+
+pub fn is_datetime64_any_dtype(s: &Column) -> bool {
+    matches!(s.dtype(), DataType::Date | DataType::Datetime(..))
 }
 
 
-pub fn unix_timestamp_to_naive_datetime(timestamp: i64) -> DateTime<Utc> {
-    let naive = DateTime::from_timestamp_millis(timestamp).unwrap();
-    naive
+pub fn unix_timestamp_to_naive_datetime(timestamp: i64) -> CommandResult<DateTime<Utc>> {
+    DateTime::from_timestamp_millis(timestamp).ok_or(CommandError::InvalidTimestamp(timestamp))
 }
 
 
-fn extract_datetime_as_i64<'a>(v: &AnyValue<'a>) -> i64 {
-    if let AnyValue::Datetime(d, _, _) = v {
-        *d
-    } else {
-        panic!("not a datetime");
+/// The milliseconds since the epoch of a date or datetime value.
+pub fn extract_datetime_as_i64(v: &AnyValue<'_>) -> CommandResult<i64> {
+    match v {
+        AnyValue::Date(days) => Ok(*days as i64 * MILLISECONDS_IN_DAY),
+        AnyValue::Datetime(d, unit, _) => Ok(match unit {
+            TimeUnit::Nanoseconds => d.div_euclid(1_000_000),
+            TimeUnit::Microseconds => d.div_euclid(1_000),
+            TimeUnit::Milliseconds => *d,
+        }),
+        _ => Err(CommandError::InvalidDtype {
+            name: "value".to_string(),
+            expected: "date or datetime".to_string(),
+            got: v.dtype().to_string(),
+        }),
     }
 }
 
 
-fn is_datetime_ordered(df: &DataFrame, index_col: &str) -> Result<bool, PolarsError> {
+pub fn is_datetime_ordered(df: &DataFrame, index_col: &str) -> CommandResult<bool> {
     let df = df.clone();
-    let index = df.column(index_col)?;
-    let index_is_datetime = is_datetime64_any_dtype(index);
+    let index = df
+        .column(index_col)
+        .map_err(|_| CommandError::MissingColumn(index_col.to_string()))?;
+    if !is_datetime64_any_dtype(index) {
+        return Ok(false);
+    }
+    if index.is_empty() {
+        return Err(CommandError::InsufficientData { needed: 1, got: 0 });
+    }
 
     let first: AnyValue<'_> = index.get(0)?;
-    let first = extract_datetime_as_i64(&first);
+    let first = extract_datetime_as_i64(&first)?;
     let last = index.get(index.len() - 1)?;
-    let last = extract_datetime_as_i64(&last);
+    let last = extract_datetime_as_i64(&last)?;
 
-    let ordered = match (first, last) {
-        (first, last) => first < last,
-        _ => false,
-    };
+    let ordered = first < last;
 
-    Ok(index_is_datetime && ordered)
+    Ok(ordered)
 }
 
 
+#[cfg(test)]
 mod tests {
     use crate::utils::data_loader::{
         csv_to_dataframe, 
@@ -74,7 +87,42 @@ mod tests {
             &df, 
             "datetime"
         ).unwrap();
-        assert_eq!(is_ordered, true);
+        assert!(is_ordered);
+    }
+
+    #[test]
+    fn test_extract_datetime_as_i64() {
+        let ms = 1_700_000_000_123;
+        let values = [
+            AnyValue::Datetime(ms, TimeUnit::Milliseconds, None),
+            AnyValue::Datetime(ms * 1_000 + 456, TimeUnit::Microseconds, None),
+            AnyValue::Datetime(ms * 1_000_000 + 456_789, TimeUnit::Nanoseconds, None),
+        ];
+        for value in &values {
+            assert_eq!(extract_datetime_as_i64(value).unwrap(), ms);
+        }
+        assert_eq!(extract_datetime_as_i64(&AnyValue::Date(19_675)).unwrap(), 19_675 * 86_400_000);
+        assert!(matches!(
+            extract_datetime_as_i64(&AnyValue::Int64(ms)),
+            Err(CommandError::InvalidDtype { .. })
+        ));
+    }
+
+    #[test]
+    fn test_unix_timestamp_to_naive_datetime() {
+        let datetime = unix_timestamp_to_naive_datetime(86_400_000).unwrap();
+        assert_eq!(datetime.to_rfc3339(), "1970-01-02T00:00:00+00:00");
+        assert!(matches!(
+            unix_timestamp_to_naive_datetime(i64::MAX),
+            Err(CommandError::InvalidTimestamp(i64::MAX))
+        ));
+    }
+
+    #[test]
+    fn test_is_datetime_ordered_date() {
+        let df = df!("date" => [19_675i32, 19_676, 19_677]).unwrap();
+        let df = df.lazy().with_column(col("date").cast(DataType::Date)).collect().unwrap();
+        assert!(is_datetime_ordered(&df, "date").unwrap());
     }
 
     #[test]
@@ -93,8 +141,8 @@ mod tests {
             "%Y.%m.%d %H:%M"
         ).unwrap();
         let is_any_dtype = is_datetime64_any_dtype(
-            &df.column("datetime").unwrap()
+            df.column("datetime").unwrap()
         );
-        assert_eq!(is_any_dtype, true);
+        assert!(is_any_dtype);
     }
 }
\ No newline at end of file
@@ -8,6 +8,42 @@ pub enum CommandError {
     Polars(#[from] PolarsError),
     #[error("DataFrame not found")]
     DataFrameNotFound,
+    #[error("Column '{0}' not found")]
+    MissingColumn(String),
+    #[error("Column '{name}' has dtype {got}, expected {expected}")]
+    InvalidDtype {
+        name: String,
+        expected: String,
+        got: String,
+    },
+    #[error("Series have different lengths, expected {expected} got {got}")]
+    LengthMismatch {
+        expected: usize,
+        got: usize,
+    },
+    #[error("Not enough data, needed {needed} rows got {got}")]
+    InsufficientData {
+        needed: usize,
+        got: usize,
+    },
+    #[error("Invalid {name} {value}, expected {expected}")]
+    InvalidParameter {
+        name: String,
+        value: String,
+        expected: String,
+    },
+    #[error("Column '{name}' has a missing value between valid ones at row {row}")]
+    InteriorNull {
+        name: String,
+        row: usize,
+    },
+    #[error("Timestamp {0} ms is out of range")]
+    InvalidTimestamp(i64),
+    #[error("Failed to parse {value:?} at row {row}")]
+    ParseError {
+        row: usize,
+        value: String,
+    },
     #[error("{0}")]
     Other(String),
 }
@@ -1,5 +1,3 @@
-use polars::prelude::*;
-
 pub fn fibonacci(n: i32, zero: bool, weighted: bool) -> Vec<f64> {
     let n = n.abs().max(2);
 
@@ -13,15 +11,15 @@ pub fn fibonacci(n: i32, zero: bool, weighted: bool) -> Vec<f64> {
     for _ in 0..n-1 {
         let temp = a;
         a = b;
-        b = temp + b;
+        b += temp;
         result.push(a);
     }
 
     if weighted {
         let sum: f64 = result.iter().sum();
         if sum > 0.0 {
-            for i in 0..result.len() {
-                result[i] /= sum;
+            for value in result.iter_mut() {
+                *value /= sum;
             }
         }
     }
@@ -30,11 +28,22 @@ pub fn fibonacci(n: i32, zero: bool, weighted: bool) -> Vec<f64> {
 }
 
 
+/// The mean of `values`, summed in order as polars sums fewer than 128 values.
+/// The batch and streaming EMA seed with it, so both give the same bits.
+pub fn mean(values: impl IntoIterator<Item = f64>) -> Option<f64> {
+    let (sum, count) = values
+        .into_iter()
+        .fold((0.0, 0usize), |(sum, count), value| (sum + value, count + 1));
+    (count > 0).then(|| sum / count as f64)
+}
+
+
 pub fn dot(w: &[f64], x: &[f64]) -> f64 {
     w.iter().zip(x.iter()).map(|(wi, xi)| wi * xi).sum()
 }
 
 
+#[cfg(test)]
 mod tests {
     use super::*;
 
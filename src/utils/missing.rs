@@ -0,0 +1,331 @@
+use crate::utils::validate;
+use crate::utils::error::{CommandError, CommandResult};
+use polars::prelude::*;
+use std::cell::Cell;
+use std::sync::RwLock;
+
+
+/// How the warm-up rows of an indicator, the leading rows before its first
+/// value, are represented.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WarmUpValue {
+    #[default]
+    Null,
+    NaN,
+}
+
+
+/// What an indicator does with missing input values between valid ones.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InteriorNulls {
+    /// Compute over the gaps, the outputs they touch are missing too.
+    #[default]
+    Propagate,
+    /// Compute over the valid rows only, the gaps stay missing in the output.
+    Skip,
+    /// Refuse inputs with gaps.
+    Error,
+}
+
+
+/// The crate-wide missing value policy. NaN inputs count as missing, like in
+/// pandas, so outputs with NaN warm-up rows can be fed to other indicators.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct MissingPolicy {
+    pub warm_up: WarmUpValue,
+    pub interior: InteriorNulls,
+}
+
+
+static POLICY: RwLock<MissingPolicy> = RwLock::new(MissingPolicy {
+    warm_up: WarmUpValue::Null,
+    interior: InteriorNulls::Propagate,
+});
+
+thread_local! {
+    static SCOPED: Cell<Option<MissingPolicy>> = const { Cell::new(None) };
+}
+
+
+/// The policy in effect on this thread.
+pub fn missing_policy() -> MissingPolicy {
+    SCOPED.with(|scoped| scoped.get()).unwrap_or_else(|| {
+        *POLICY.read().unwrap_or_else(|poisoned| poisoned.into_inner())
+    })
+}
+
+
+/// Sets the policy for every thread without a scoped one.
+pub fn set_missing_policy(policy: MissingPolicy) {
+    *POLICY.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = policy;
+}
+
+
+/// Runs `f` with `policy` in effect on the current thread only.
+pub fn with_missing_policy<T>(policy: MissingPolicy, f: impl FnOnce() -> T) -> T {
+    struct Restore(Option<MissingPolicy>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            SCOPED.with(|scoped| scoped.set(self.0));
+        }
+    }
+    let _restore = Restore(SCOPED.with(|scoped| scoped.replace(Some(policy))));
+    f()
+}
+
+
+/// The inputs of an indicator under the missing value policy, as Float64
+/// with NaN turned into nulls.
+pub(crate) struct Prepared {
+    pub(crate) inputs: Vec<Series>,
+    policy: MissingPolicy,
+    /// For `Skip`, the position of each input row among the kept rows.
+    positions: Option<IdxCa>,
+}
+
+
+pub(crate) fn prepare(inputs: &[&Series]) -> CommandResult<Prepared> {
+    let policy = missing_policy();
+    let len = validate::inputs(inputs)?;
+
+    let inputs = inputs
+        .iter()
+        .map(|series| {
+            let values = series.cast(&DataType::Float64)?;
+            if !values.f64()?.into_iter().any(|value| value.is_some_and(f64::is_nan)) {
+                return Ok(values);
+            }
+            let values: Series = values
+                .f64()?
+                .into_iter()
+                .map(|value| value.and_then(present))
+                .collect();
+            Ok(values.with_name(series.name().clone()))
+        })
+        .collect::<CommandResult<Vec<Series>>>()?;
+
+    match policy.interior {
+        InteriorNulls::Propagate => Ok(Prepared { inputs, policy, positions: None }),
+        InteriorNulls::Error => {
+            for series in &inputs {
+                if let Some(row) = first_interior_null(series) {
+                    return Err(CommandError::InteriorNull {
+                        name: series.name().to_string(),
+                        row,
+                    });
+                }
+            }
+            Ok(Prepared { inputs, policy, positions: None })
+        },
+        InteriorNulls::Skip => {
+            if inputs.iter().all(|series| series.null_count() == 0) {
+                return Ok(Prepared { inputs, policy, positions: None });
+            }
+            let valid = inputs
+                .iter()
+                .fold(BooleanChunked::full("valid".into(), true, len), |valid, series| {
+                    valid & series.is_not_null()
+                });
+
+            let mut kept: Vec<IdxSize> = Vec::with_capacity(len);
+            let mut positions: Vec<Option<IdxSize>> = Vec::with_capacity(len);
+            for (row, valid) in valid.into_no_null_iter().enumerate() {
+                if valid {
+                    positions.push(Some(kept.len() as IdxSize));
+                    kept.push(row as IdxSize);
+                } else {
+                    positions.push(None);
+                }
+            }
+
+            let kept = IdxCa::from_vec("kept".into(), kept);
+            let inputs = inputs
+                .iter()
+                .map(|series| series.take(&kept))
+                .collect::<PolarsResult<Vec<Series>>>()?;
+            let positions = IdxCa::from_iter_options("positions".into(), positions.into_iter());
+            Ok(Prepared { inputs, policy, positions: Some(positions) })
+        },
+    }
+}
+
+
+/// A value as the indicators take it, NaN counting as missing.
+pub(crate) fn present(value: f64) -> Option<f64> {
+    (!value.is_nan()).then_some(value)
+}
+
+
+/// The row of the first null after the first valid value and before the last.
+fn first_interior_null(series: &Series) -> Option<usize> {
+    let valid: Vec<bool> = series.is_not_null().into_no_null_iter().collect();
+    let first = valid.iter().position(|valid| *valid)?;
+    let last = valid.iter().rposition(|valid| *valid)?;
+    valid[first..last].iter().position(|valid| !valid).map(|row| first + row)
+}
+
+
+impl Prepared {
+    /// Puts an output computed from the prepared inputs back on the input
+    /// rows and shifts it by `offset` as `shifted` does. Gaps in the inputs
+    /// stay null.
+    pub(crate) fn restore(&self, output: Series, offset: i32) -> CommandResult<Series> {
+        let output = match &self.positions {
+            Some(positions) => output.take(positions)?,
+            None => output,
+        };
+        write_missing(output, offset, self.policy.warm_up)
+    }
+}
+
+
+/// Shifts an output by `offset` and writes the rows without a value as the
+/// missing value policy asks: the warm-up rows, the rows a negative offset
+/// empties at the end and undefined (NaN) results all take the policy's
+/// missing value. For outputs of inputs the policy was already applied to.
+pub(crate) fn shifted(output: Series, offset: i32) -> CommandResult<Series> {
+    write_missing(output, offset, missing_policy().warm_up)
+}
+
+
+fn write_missing(output: Series, offset: i32, warm_up: WarmUpValue) -> CommandResult<Series> {
+    let output = if offset != 0 {
+        output.shift(offset as i64)
+    } else {
+        output
+    };
+
+    if !output.dtype().is_float() {
+        return Ok(output);
+    }
+
+    let values = output.cast(&DataType::Float64)?;
+    let values = values.f64()?;
+    let leading = values
+        .into_iter()
+        .take_while(|value| value.is_none_or(f64::is_nan))
+        .count();
+    let shifted_out = values.len() - (offset.min(0).unsigned_abs() as usize).min(values.len());
+    let missing = match warm_up {
+        WarmUpValue::Null => None,
+        WarmUpValue::NaN => Some(f64::NAN),
+    };
+    let written = |i: usize, value: Option<f64>| {
+        if i < leading || i >= shifted_out || value.is_some_and(f64::is_nan) {
+            missing
+        } else {
+            value
+        }
+    };
+
+    let unchanged = values
+        .into_iter()
+        .enumerate()
+        .all(|(i, value)| written(i, value).map(f64::to_bits) == value.map(f64::to_bits));
+    if unchanged {
+        return Ok(output);
+    }
+
+    let restored: Series = values
+        .into_iter()
+        .enumerate()
+        .map(|(i, value)| written(i, value))
+        .collect();
+    Ok(restored.cast(output.dtype())?.with_name(output.name().clone()))
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn values(series: &Series) -> Vec<Option<f64>> {
+        series.f64().unwrap().into_iter().collect()
+    }
+
+    #[test]
+    fn test_prepare_skip() {
+        let close = Series::new("close".into(), [None, Some(1.0), Some(f64::NAN), Some(3.0)]);
+        let policy = MissingPolicy { interior: InteriorNulls::Skip, ..Default::default() };
+        let prepared = with_missing_policy(policy, || prepare(&[&close])).unwrap();
+        assert_eq!(values(&prepared.inputs[0]), [Some(1.0), Some(3.0)]);
+
+        let output = Series::new("out".into(), [None, Some(2.0)]);
+        let restored = prepared.restore(output, 0).unwrap();
+        assert_eq!(values(&restored), [None, None, None, Some(2.0)]);
+    }
+
+    #[test]
+    fn test_prepare_error() {
+        let policy = MissingPolicy { interior: InteriorNulls::Error, ..Default::default() };
+        let leading = Series::new("close".into(), [None, Some(1.0), Some(2.0)]);
+        assert!(with_missing_policy(policy, || prepare(&[&leading])).is_ok());
+        let interior = Series::new("close".into(), [None, Some(1.0), None, Some(2.0)]);
+        assert!(matches!(
+            with_missing_policy(policy, || prepare(&[&interior])),
+            Err(CommandError::InteriorNull { name, row: 2 }) if name == "close"
+        ));
+    }
+
+    #[test]
+    fn test_prepare_invalid_inputs() {
+        let close = Series::new("close".into(), [1.0, 2.0, 3.0]);
+        let volume = Series::new("volume".into(), [1, 2]);
+        assert!(matches!(
+            prepare(&[&close, &volume]),
+            Err(CommandError::LengthMismatch { expected: 3, got: 2 })
+        ));
+        let close = Series::new("close".into(), ["1.0", "2.0"]);
+        assert!(matches!(prepare(&[&close]), Err(CommandError::InvalidDtype { .. })));
+    }
+
+    #[test]
+    fn test_restore_warm_up() {
+        let close = Series::new("close".into(), [1.0, 2.0, 3.0]);
+        let output = Series::new("out".into(), [Some(f64::NAN), None, Some(2.0)]);
+
+        let prepared = prepare(&[&close]).unwrap();
+        assert_eq!(values(&prepared.restore(output.clone(), 0).unwrap()), [None, None, Some(2.0)]);
+
+        let policy = MissingPolicy { warm_up: WarmUpValue::NaN, ..Default::default() };
+        let prepared = with_missing_policy(policy, || prepare(&[&close])).unwrap();
+        let restored = prepared.restore(output, 0).unwrap();
+        assert!(values(&restored)[..2].iter().all(|value| value.unwrap().is_nan()));
+    }
+
+    #[test]
+    fn test_restore_offset() {
+        let close = Series::new("close".into(), [1.0, 2.0, 3.0, 4.0, 5.0]);
+        let output = Series::new("out".into(), [None, None, Some(2.0), Some(3.0), Some(4.0)]);
+        let policy = MissingPolicy { warm_up: WarmUpValue::NaN, ..Default::default() };
+        let prepared = with_missing_policy(policy, || prepare(&[&close])).unwrap();
+
+        let restored = values(&prepared.restore(output.clone(), 2).unwrap());
+        assert!(restored[..4].iter().all(|value| value.unwrap().is_nan()));
+        assert_eq!(restored[4], Some(2.0));
+
+        let restored = values(&prepared.restore(output, -2).unwrap());
+        assert_eq!(restored[..3], [Some(2.0), Some(3.0), Some(4.0)]);
+        assert!(restored[3..].iter().all(|value| value.unwrap().is_nan()));
+    }
+
+    #[test]
+    fn test_restore_undefined() {
+        let close = Series::new("close".into(), [Some(1.0), None, Some(3.0), Some(4.0)]);
+        let output = Series::new("out".into(), [Some(1.0), None, Some(f64::NAN), Some(4.0)]);
+
+        let prepared = prepare(&[&close]).unwrap();
+        assert_eq!(values(&prepared.restore(output.clone(), 0).unwrap()), [Some(1.0), None, None, Some(4.0)]);
+
+        let policy = MissingPolicy { warm_up: WarmUpValue::NaN, ..Default::default() };
+        let prepared = with_missing_policy(policy, || prepare(&[&close])).unwrap();
+        let restored = values(&prepared.restore(output, 0).unwrap());
+        assert_eq!(restored[1], None);
+        assert!(restored[2].unwrap().is_nan());
+    }
+}
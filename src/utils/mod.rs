@@ -2,4 +2,7 @@ pub mod core;
 pub mod error;
 pub mod data_loader;
 pub mod math;
-mod datetime_utils;
\ No newline at end of file
+pub mod datetime_utils;
+pub mod missing;
+pub mod validate;
+
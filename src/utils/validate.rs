@@ -0,0 +1,197 @@
+use crate::utils::core::same_length;
+use crate::utils::error::{CommandError, CommandResult};
+use polars::prelude::*;
+use std::cell::Cell;
+use std::fmt::Display;
+use std::sync::RwLock;
+
+
+/// How indicators treat invalid parameters and inputs too short for them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Validation {
+    /// Invalid parameters and too short inputs are errors.
+    #[default]
+    Strict,
+    /// Like pandas-ta, invalid parameters are replaced by their defaults and
+    /// too short inputs give an output of warm-up rows only.
+    Lenient,
+}
+
+
+static VALIDATION: RwLock<Validation> = RwLock::new(Validation::Strict);
+
+thread_local! {
+    static SCOPED: Cell<Option<Validation>> = const { Cell::new(None) };
+    static SHORT_INPUTS: Cell<bool> = const { Cell::new(false) };
+}
+
+
+/// The validation mode in effect on this thread.
+pub fn validation() -> Validation {
+    SCOPED.with(|scoped| scoped.get()).unwrap_or_else(|| {
+        *VALIDATION.read().unwrap_or_else(|poisoned| poisoned.into_inner())
+    })
+}
+
+
+/// Sets the validation mode for every thread without a scoped one.
+pub fn set_validation(validation: Validation) {
+    *VALIDATION.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = validation;
+}
+
+
+/// Runs `f` with `validation` in effect on the current thread only.
+pub fn with_validation<T>(validation: Validation, f: impl FnOnce() -> T) -> T {
+    struct Restore(Option<Validation>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            SCOPED.with(|scoped| scoped.set(self.0));
+        }
+    }
+    let _restore = Restore(SCOPED.with(|scoped| scoped.replace(Some(validation))));
+    f()
+}
+
+
+/// Runs `f` with inputs too short for an indicator allowed on the current
+/// thread, as when lenient, while parameters are still validated under the
+/// mode in effect.
+pub(crate) fn with_short_inputs<T>(f: impl FnOnce() -> T) -> T {
+    struct Restore(bool);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            SHORT_INPUTS.with(|short| short.set(self.0));
+        }
+    }
+    let _restore = Restore(SHORT_INPUTS.with(|short| short.replace(true)));
+    f()
+}
+
+
+/// A parameter for which `valid` holds, `default` if unset. `expected`
+/// describes the valid values in the error.
+pub(crate) fn parameter<T: Copy + Display>(
+    name: &str,
+    value: Option<T>,
+    default: T,
+    valid: impl Fn(T) -> bool,
+    expected: &str
+) -> CommandResult<T> {
+    match value {
+        None => Ok(default),
+        Some(value) if valid(value) => Ok(value),
+        Some(_) if validation() == Validation::Lenient => Ok(default),
+        Some(value) => Err(CommandError::InvalidParameter {
+            name: name.to_string(),
+            value: value.to_string(),
+            expected: expected.to_string(),
+        }),
+    }
+}
+
+
+/// A window length or count parameter of at least `min`, `default` if unset.
+pub(crate) fn length(name: &str, value: Option<i32>, min: i32, default: i32) -> CommandResult<i32> {
+    parameter(name, value, default, |value| value >= min, &format!("at least {}", min))
+}
+
+
+/// The value `length` resolves to when lenient, for what is known before an
+/// indicator runs, like its lookback.
+pub(crate) fn resolved(value: Option<i32>, min: i32, default: i32) -> usize {
+    value.filter(|value| *value >= min).unwrap_or(default) as usize
+}
+
+
+/// A positive float parameter, `default` if unset.
+pub(crate) fn positive(name: &str, value: Option<f64>, default: f64) -> CommandResult<f64> {
+    parameter(name, value, default, |value| value > 0.0, "a positive number")
+}
+
+
+/// Checks an input has the `needed` rows for a first value. When lenient, or
+/// within `with_short_inputs`, the indicator runs anyway and its output is
+/// warm-up rows only.
+pub(crate) fn min_rows(got: usize, needed: usize) -> CommandResult<()> {
+    let short_allowed = SHORT_INPUTS.with(|short| short.get());
+    if got < needed && validation() == Validation::Strict && !short_allowed {
+        return Err(CommandError::InsufficientData { needed, got });
+    }
+    Ok(())
+}
+
+
+/// Checks the input series of an indicator have the same length and a
+/// numeric dtype, and returns that length.
+pub(crate) fn inputs(inputs: &[&Series]) -> CommandResult<usize> {
+    let len = same_length(inputs)?;
+    if let Some(series) = inputs.iter().find(|series| {
+        !series.dtype().is_numeric() && !series.dtype().is_null()
+    }) {
+        return Err(CommandError::InvalidDtype {
+            name: series.name().to_string(),
+            expected: "numeric".to_string(),
+            got: series.dtype().to_string(),
+        });
+    }
+    Ok(len)
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_length() {
+        assert_eq!(length("length", None, 1, 10).unwrap(), 10);
+        assert_eq!(length("length", Some(3), 2, 10).unwrap(), 3);
+        assert!(matches!(
+            length("length", Some(1), 2, 10),
+            Err(CommandError::InvalidParameter { .. })
+        ));
+        let lenient = with_validation(Validation::Lenient, || length("length", Some(-1), 1, 10));
+        assert_eq!(lenient.unwrap(), 10);
+    }
+
+    #[test]
+    fn test_positive() {
+        assert_eq!(positive("factor", Some(0.5), 10.0).unwrap(), 0.5);
+        assert!(positive("factor", Some(0.0), 10.0).is_err());
+        let lenient = with_validation(Validation::Lenient, || positive("factor", Some(-1.0), 10.0));
+        assert_eq!(lenient.unwrap(), 10.0);
+    }
+
+    #[test]
+    fn test_min_rows() {
+        assert!(min_rows(10, 10).is_ok());
+        assert!(matches!(
+            min_rows(3, 10),
+            Err(CommandError::InsufficientData { needed: 10, got: 3 })
+        ));
+        assert!(with_validation(Validation::Lenient, || min_rows(3, 10)).is_ok());
+
+        // only the row count is relaxed, parameters stay strict
+        with_short_inputs(|| {
+            assert!(min_rows(3, 10).is_ok());
+            assert!(length("length", Some(0), 1, 10).is_err());
+        });
+        assert!(min_rows(3, 10).is_err());
+    }
+
+    #[test]
+    fn test_inputs() {
+        let close = Series::new("close".into(), [1.0f32, 2.0, 3.0]);
+        let volume = Series::new("volume".into(), [1i64, 2, 3]);
+        assert_eq!(inputs(&[&close, &volume]).unwrap(), 3);
+        let short = Series::new("volume".into(), [1i64, 2]);
+        assert!(matches!(
+            inputs(&[&close, &short]),
+            Err(CommandError::LengthMismatch { expected: 3, got: 2 })
+        ));
+        let text = Series::new("close".into(), ["1.0", "2.0", "3.0"]);
+        assert!(matches!(inputs(&[&text]), Err(CommandError::InvalidDtype { .. })));
+    }
+}
@@ -0,0 +1,192 @@
+use crate::overlap::ema::{ema, EmaOptions};
+use crate::overlap::rma::{rma, RmaOptions};
+use crate::overlap::sma::{sma, SmaOptions};
+use crate::utils::core::{get_offset, shifted_lookback, fill_missing, FillPolicy};
+use crate::utils::missing::{prepare, shifted};
+use crate::utils::error::CommandResult;
+use crate::volatility::true_range::true_range;
+use crate::utils::validate;
+use polars::prelude::*;
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct AtrOptions {
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub length: Option<i32>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub mamode: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub drift: Option<i32>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub offset: Option<i32>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub fill: Option<FillPolicy>,
+    pub percent: bool,
+}
+
+
+impl Default for AtrOptions {
+    fn default() -> Self {
+        AtrOptions {
+            length: Some(14),
+            mamode: Some("rma".to_string()),
+            drift: None,
+            offset: None,
+            fill: None,
+            percent: false,
+        }
+    }
+}
+
+
+impl AtrOptions {
+    /// The output name, e.g. `ATRr_14`, with a `p` suffix for `percent`.
+    pub fn name(&self) -> String {
+        let length = match self.length {
+            Some(length) if length > 0 => length,
+            _ => 14
+        };
+        let prefix = match self.mamode.as_deref() {
+            Some("ema") => "e",
+            Some("sma") => "s",
+            _ => "r"
+        };
+        format!("ATR{}_{}{}", prefix, length, if self.percent { "p" } else { "" })
+    }
+
+    /// Leading bars without `length` true ranges, like TA-Lib's
+    /// `TA_ATR_Lookback`, shifted by the offset.
+    pub fn lookback(&self) -> usize {
+        let length = validate::resolved(self.length, 1, 14);
+        let drift = validate::resolved(self.drift, 1, 1);
+        shifted_lookback(length + drift - 1, self.offset)
+    }
+}
+
+
+/// Average true range: the `mamode` moving average ("rma", "ema" or "sma")
+/// of the true range, as a percentage of close when `percent` is set.
+pub fn atr(
+    high: &Series,
+    low: &Series,
+    close: &Series,
+    options: AtrOptions
+) -> CommandResult<Series> {
+    let prepared = prepare(&[high, low, close])?;
+    let (high, low, close) = (&prepared.inputs[0], &prepared.inputs[1], &prepared.inputs[2]);
+    let length = validate::length("length", options.length, 1, 14)?;
+    let drift = validate::length("drift", options.drift, 1, 1)?;
+    validate::min_rows(close.len(), (length + drift) as usize)?;
+
+    let name = options.name();
+    let offset = get_offset(options.offset);
+
+    // the averages read NaN warm-up rows of the true range as missing
+    let tr = true_range(high, low, close, Some(drift), None, None)?;
+
+    let atr = average(&tr, close, length, &options)?;
+
+    let atr = prepared.restore(atr, offset)?;
+
+    let atr = fill_missing(atr, options.fill)?;
+
+    Ok(atr.with_name(name.into()))
+}
+
+
+/// ATR from `tr`, the true range of the bars with the same drift, for
+/// callers that already have it. The missing value policy was applied when
+/// `tr` was computed, so its gaps are kept as they are.
+pub(crate) fn atr_from_true_range(
+    tr: &Series,
+    close: &Series,
+    options: AtrOptions
+) -> CommandResult<Series> {
+    let length = validate::length("length", options.length, 1, 14)?;
+    let drift = validate::length("drift", options.drift, 1, 1)?;
+    validate::min_rows(tr.len(), (length + drift) as usize)?;
+    let name = options.name();
+    let offset = get_offset(options.offset);
+
+    let atr = average(tr, close, length, &options)?;
+
+    let atr = shifted(atr, offset)?;
+
+    let atr = fill_missing(atr, options.fill)?;
+
+    Ok(atr.with_name(name.into()))
+}
+
+
+/// The `mamode` average of the true range, as a percentage of close for
+/// `percent`.
+fn average(tr: &Series, close: &Series, length: i32, options: &AtrOptions) -> CommandResult<Series> {
+    let atr = match options.mamode.as_deref() {
+        Some("ema") => ema(tr, EmaOptions {
+            length: Some(length),
+            mamode: Some("ema".to_string()),
+            ..Default::default()
+        })?,
+        Some("sma") => sma(tr, SmaOptions {
+            length: Some(length),
+            ..Default::default()
+        })?,
+        _ => rma(tr, RmaOptions {
+            length: Some(length),
+            ..Default::default()
+        })?,
+    };
+
+    if options.percent {
+        let close = close.cast(&DataType::Float64)?;
+        match atr * 100.0 / close {
+            Ok(atr) => Ok(atr),
+            Err(_) => Err("Failed to calculate ATR percent".into())
+        }
+    } else {
+        Ok(atr)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::utils::data_loader::{
+        csv_to_dataframe, 
+        set_column_names, 
+        combine_date_time, 
+        convert_to_naive_datetime
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_atr() {
+        let mut df = csv_to_dataframe(
+            "data/AUDNZD1.csv", 
+            false
+        ).unwrap();
+        let _ = set_column_names(
+            &mut df, 
+            vec!["date", "time", "open", "high", "low", "close", "volume"]
+        );
+        let df = combine_date_time(&df).unwrap();
+        let df = convert_to_naive_datetime(
+            &df, 
+            "%Y.%m.%d %H:%M"
+        ).unwrap();
+        let high = df.column("high").unwrap().as_series().unwrap();
+        let low = df.column("low").unwrap().as_series().unwrap();
+        let close = df.column("close").unwrap().as_series().unwrap();
+
+        let atr = atr(high, low, close, AtrOptions::default()).unwrap();
+        assert_eq!(atr.name().as_str(), "ATRr_14");
+        let atr = atr.f64().unwrap();
+        // the first true range is missing, so 14 valid values end at row 14
+        assert_eq!(atr.get(13), None);
+        assert!(atr.get(14).unwrap() > 0.0);
+    }
+}
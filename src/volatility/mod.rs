@@ -1,2 +1,2 @@
-mod true_range;
-// mod atr;
\ No newline at end of file
+pub mod true_range;
+pub mod atr;
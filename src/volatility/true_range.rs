@@ -1,68 +1,55 @@
-use crate::utils::core::{get_drift, get_offset, non_zero_range};
-// use crate::utils::math::abs;
+use crate::utils::core::{get_offset, non_zero, fill_missing, FillPolicy};
 use polars::prelude::*;
+use crate::utils::missing::prepare;
 use crate::utils::error::CommandResult;
+use crate::utils::validate;
 
 pub fn true_range(
     high: &Series, 
     low: &Series, 
     close: &Series, 
     drift: Option<i32>, 
-    offset: Option<i32>
+    offset: Option<i32>,
+    fill: Option<FillPolicy>
 ) -> CommandResult<Series> {
-    let drift = get_drift(drift);
+    let prepared = prepare(&[high, low, close])?;
+    let (high, low, close) = (&prepared.inputs[0], &prepared.inputs[1], &prepared.inputs[2]);
+    let drift = validate::length("drift", drift, 1, 1)?;
+    validate::min_rows(close.len(), drift as usize + 1)?;
     let offset = get_offset(offset);
-    let shifted_close = close.shift(drift as i64);
+    let high = high.cast(&DataType::Float64)?;
+    let low = low.cast(&DataType::Float64)?;
+    let close = close.cast(&DataType::Float64)?;
 
-    let high_low_range = non_zero_range(high, low)?;
-    let prev_close: &Series = &shifted_close.as_series();
+    // the first drift values have no previous close, they are the warm-up;
+    // the rest pairs each bar with the close drift bars before it, all
+    // slices of the inputs rather than copies. A bar without its high, low
+    // or previous close has no true range, like any output a gap touches
+    let warm_up = (drift as usize).min(close.len());
+    let rows = close.len() - warm_up;
+    let true_range: Float64Chunked = arity::ternary_elementwise(
+        &high.f64()?.slice(warm_up as i64, rows),
+        &low.f64()?.slice(warm_up as i64, rows),
+        &close.f64()?.slice(0, rows),
+        |high: Option<f64>, low: Option<f64>, prev_close: Option<f64>| {
+            let (high, low, prev_close) = (high?, low?, prev_close?);
+            let range = non_zero(high - low).abs();
+            Some(range.max((high - prev_close).abs()).max((prev_close - low).abs()))
+        }
+    );
+    let mut warm_up = Float64Chunked::full_null("true_range".into(), warm_up);
+    warm_up.append(&true_range)?;
+    let true_range = warm_up.into_series();
 
-    let high_minus_prev_close = match high - prev_close {
-        Ok(diff) => diff,
-        Err(_) => return Err("Failed to calculate difference".into())
-    };
-    let prev_close_minus_low = match prev_close - low {
-        Ok(diff) => diff,
-        Err(_) => return Err("Failed to calculate difference".into())
-    };
-
-    let ranges = vec![
-        abs(&high_low_range)?.into_column(),
-        abs(&high_minus_prev_close)?.into_column(),
-        abs(&prev_close_minus_low)?.into_column()
-    ];
-
-    let df : DataFrame = DataFrame::new(ranges)?;
-    let true_range = df
-        .max_horizontal()
-        .unwrap()
-        .unwrap();
+    let true_range = prepared.restore(true_range, offset)?;
 
-    // set the first drift values to NaN
-    let true_range: Series = true_range
-        .f64()
-        .unwrap()
-        .into_iter()
-        .enumerate()
-        .map(|(i, value)| {
-            if i < drift as usize {
-                Some(f64::NAN)
-            } else {
-                value
-            }
-        })
-        .collect();
+    let true_range = fill_missing(true_range, fill)?;
 
-    let true_range = if offset != 0 {
-        true_range.shift(offset as i64)
-    } else {
-        true_range
-    };
-
-    Ok(true_range)
+    Ok(true_range.with_name(format!("TRUERANGE_{}", drift).into()))
 }
 
 
+#[cfg(test)]
 mod tests {
 
     use crate::utils::data_loader::{
@@ -71,12 +58,8 @@ mod tests {
         combine_date_time, 
         convert_to_naive_datetime
     };
-    use polars::prelude::abs;
-    use polars::prelude::*;
-
     use super::true_range;
-
-    // use super::*;
+    use crate::utils::core::FillPolicy;
 
     #[test]
     fn test_true_range() {
@@ -98,12 +81,38 @@ mod tests {
         let close = df.column("close").unwrap().as_series().unwrap();
 
         let result = true_range(
-            &high,
-            &low, 
-            &close, 
+            high,
+            low, 
+            close, 
             Some(1), 
-            Some(0)
+            Some(0),
+            None
         ).unwrap();
         println!("{:?}", result);
+
+        // the NaN warm-up value is missing, like in pandas
+        let filled = true_range(high, low, close, Some(1), None, Some(FillPolicy::Zero)).unwrap();
+        assert_eq!(filled.f64().unwrap().get(0), Some(0.0));
+        assert_eq!(filled.f64().unwrap().get(1), result.f64().unwrap().get(1));
     }
-}
\ No newline at end of file
+
+
+    #[test]
+    fn test_true_range_gaps() {
+        use polars::prelude::*;
+
+        let high = Series::new("high".into(), [2.0, 3.0, 3.0, 5.0, 4.0]);
+        let low = Series::new("low".into(), [1.0, 2.0, 3.0, 4.0, 3.5]);
+        let close = Series::new("close".into(), [Some(1.5), Some(2.5), None, Some(4.5), Some(3.5)]);
+        let result = true_range(&high, &low, &close, Some(2), None, None).unwrap();
+        let result = result.f64().unwrap();
+        assert_eq!(result.len(), 5);
+        assert_eq!(result.get(0), None);
+        assert_eq!(result.get(1), None);
+        // a flat bar gapped up from two bars before
+        assert_eq!(result.get(2), Some(1.5));
+        // without a previous close there is no true range
+        assert_eq!(result.get(4), None);
+        assert_eq!(result.null_count(), 3);
+    }
+}
@@ -0,0 +1,266 @@
+use crate::overlap::ema::{ema, EmaOptions};
+use crate::overlap::sma::{sma, SmaOptions};
+use crate::utils::core::{get_offset, shifted_lookback, fill_missing_frame, FillPolicy};
+use crate::utils::missing::prepare;
+use crate::utils::error::CommandResult;
+use crate::utils::validate;
+use crate::volume::obv::obv;
+use polars::prelude::*;
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct AobvOptions {
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub fast: Option<i32>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub slow: Option<i32>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub max_lookback: Option<i32>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub min_lookback: Option<i32>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub mamode: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub run_length: Option<i32>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub offset: Option<i32>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub fill: Option<FillPolicy>,
+}
+
+
+impl Default for AobvOptions {
+    fn default() -> Self {
+        AobvOptions {
+            fast: Some(4),
+            slow: Some(12),
+            max_lookback: Some(2),
+            min_lookback: Some(2),
+            mamode: Some("ema".to_string()),
+            run_length: Some(2),
+            offset: None,
+            fill: None,
+        }
+    }
+}
+
+
+impl AobvOptions {
+    /// The output columns, e.g. `OBV, OBV_min_2, OBV_max_2, OBVe_4, OBVe_12,
+    /// AOBV_LR_2, AOBV_SR_2`.
+    pub fn names(&self) -> Vec<String> {
+        let mut fast = positive_or(self.fast, 4);
+        let mut slow = positive_or(self.slow, 12);
+        if slow < fast {
+            std::mem::swap(&mut fast, &mut slow);
+        }
+        let prefix = match self.mamode.as_deref() {
+            Some("sma") => "s",
+            _ => "e"
+        };
+        let run_length = positive_or(self.run_length, 2);
+        vec![
+            "OBV".to_string(),
+            format!("OBV_min_{}", positive_or(self.min_lookback, 2)),
+            format!("OBV_max_{}", positive_or(self.max_lookback, 2)),
+            format!("OBV{}_{}", prefix, fast),
+            format!("OBV{}_{}", prefix, slow),
+            format!("AOBV_LR_{}", run_length),
+            format!("AOBV_SR_{}", run_length),
+        ]
+    }
+
+    /// Leading bars before every column has a value: the runs compare the
+    /// slow average `run_length` bars apart. Shifted by the offset.
+    pub fn lookback(&self) -> usize {
+        let fast = validate::resolved(self.fast, 1, 4);
+        let slow = validate::resolved(self.slow, 1, 12);
+        let extremes = validate::resolved(self.max_lookback, 1, 2).max(validate::resolved(self.min_lookback, 1, 2));
+        let runs = fast.max(slow) - 1 + validate::resolved(self.run_length, 1, 2);
+        shifted_lookback(runs.max(extremes - 1), self.offset)
+    }
+}
+
+
+fn positive_or(value: Option<i32>, default: i32) -> i32 {
+    match value {
+        Some(value) if value > 0 => value,
+        _ => default
+    }
+}
+
+
+/// 1 where the series moved in the wanted direction over `length` bars, 0
+/// otherwise (including the warm-up, where the change is unknown).
+fn run_direction(series: &Series, length: usize, up: bool) -> CommandResult<Vec<bool>> {
+    let values: Vec<Option<f64>> = series.f64()?.into_iter().collect();
+    let direction = (0..values.len())
+        .map(|i| {
+            let prev = i.checked_sub(length).and_then(|j| values[j]);
+            match (prev, values[i]) {
+                (Some(prev), Some(curr)) if up => curr > prev,
+                (Some(prev), Some(curr)) => curr < prev,
+                _ => false
+            }
+        })
+        .collect();
+    Ok(direction)
+}
+
+
+pub fn aobv(
+    close: &Series,
+    volume: &Series,
+    options: AobvOptions
+) -> CommandResult<DataFrame> {
+    let prepared = prepare(&[close, volume])?;
+    let (close, volume) = (&prepared.inputs[0], &prepared.inputs[1]);
+    let names = options.names();
+    let mut fast = validate::length("fast", options.fast, 1, 4)?;
+    let mut slow = validate::length("slow", options.slow, 1, 12)?;
+    if slow < fast {
+        std::mem::swap(&mut fast, &mut slow);
+    }
+    validate::min_rows(close.len(), slow as usize)?;
+    let max_lookback = validate::length("max_lookback", options.max_lookback, 1, 2)?;
+    let min_lookback = validate::length("min_lookback", options.min_lookback, 1, 2)?;
+    let run_length = validate::length("run_length", options.run_length, 1, 2)?;
+
+    let mamode = match options.mamode {
+        Some(mamode) if mamode == "sma" || mamode == "ema" => mamode,
+        _ => "ema".to_string()
+    };
+
+    let offset = get_offset(options.offset);
+
+    let obv = obv(close, volume, None, None)?;
+
+    let moving_average = |length: i32| -> CommandResult<Series> {
+        if mamode == "sma" {
+            sma(&obv, SmaOptions {
+                length: Some(length),
+                ..Default::default()
+            })
+        } else {
+            ema(&obv, EmaOptions {
+                length: Some(length),
+                ..Default::default()
+            })
+        }
+    };
+    let maf = moving_average(fast)?;
+    let mas = moving_average(slow)?;
+
+    let obv_min = obv.rolling_min(RollingOptionsFixedWindow {
+        window_size: min_lookback as usize,
+        min_periods: min_lookback as usize,
+        ..Default::default()
+    })?;
+    let obv_max = obv.rolling_max(RollingOptionsFixedWindow {
+        window_size: max_lookback as usize,
+        min_periods: max_lookback as usize,
+        ..Default::default()
+    })?;
+
+    // long: fast average rising while the slow one rises or falls,
+    // short: fast average falling while the slow one rises or falls
+    let length = run_length as usize;
+    let fast_up = run_direction(&maf, length, true)?;
+    let fast_down = run_direction(&maf, length, false)?;
+    let slow_up = run_direction(&mas, length, true)?;
+    let slow_down = run_direction(&mas, length, false)?;
+
+    let long_run: Series = (0..obv.len())
+        .map(|i| (fast_up[i] && (slow_down[i] || slow_up[i])) as i32)
+        .collect();
+    let short_run: Series = (0..obv.len())
+        .map(|i| (fast_down[i] && (slow_up[i] || slow_down[i])) as i32)
+        .collect();
+
+    let columns = [obv, obv_min, obv_max, maf, mas, long_run, short_run];
+
+    let columns = columns
+        .into_iter()
+        .zip(names)
+        .map(|(column, name)| {
+            Ok(prepared.restore(column.with_name(name.into()), offset)?.into_column())
+        })
+        .collect::<CommandResult<Vec<_>>>()?;
+
+    fill_missing_frame(DataFrame::new(columns)?, options.fill)
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::utils::data_loader::{
+        csv_to_dataframe, 
+        set_column_names, 
+        combine_date_time, 
+        convert_to_naive_datetime
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_aobv() {
+        let mut df = csv_to_dataframe(
+            "data/AUDNZD1.csv", 
+            false
+        ).unwrap();
+        let _ = set_column_names(
+            &mut df, 
+            vec!["date", "time", "open", "high", "low", "close", "volume"]
+        );
+        let df = combine_date_time(&df).unwrap();
+        let df = convert_to_naive_datetime(
+            &df, 
+            "%Y.%m.%d %H:%M"
+        ).unwrap();
+
+        let close = df.column("close").unwrap().as_series().unwrap();
+        let volume = df.column("volume").unwrap().as_series().unwrap();
+        let aobv = aobv(
+            close,
+            volume,
+            AobvOptions {
+                ..Default::default()
+            }
+        ).unwrap();
+        assert_eq!(
+            aobv.get_column_names(),
+            vec!["OBV", "OBV_min_2", "OBV_max_2", "OBVe_4", "OBVe_12", "AOBV_LR_2", "AOBV_SR_2"]
+        );
+        assert_eq!(aobv.height(), close.len());
+
+        let column = |name: &str| -> Vec<Option<f64>> {
+            let column = aobv.column(name).unwrap().cast(&DataType::Float64).unwrap();
+            column.f64().unwrap().into_iter().collect()
+        };
+        let approx = |values: Vec<Option<f64>>| -> Vec<Option<f64>> {
+            values.into_iter().map(|v| v.map(|v| (v * 1e6).round() / 1e6)).collect()
+        };
+        // volumes 91, 80, 61, 86, 66, 46 on closes up, down, up, flat, down, up
+        assert_eq!(column("OBV")[..6], [Some(91.0), Some(11.0), Some(72.0), Some(72.0), Some(6.0), Some(52.0)]);
+        assert_eq!(column("OBV_min_2")[..6], [None, Some(11.0), Some(11.0), Some(72.0), Some(6.0), Some(6.0)]);
+        assert_eq!(column("OBV_max_2")[..6], [None, Some(91.0), Some(72.0), Some(72.0), Some(72.0), Some(52.0)]);
+        // seeded with the mean of the first 4 OBV values, then alpha = 0.4
+        assert_eq!(approx(column("OBVe_4")[..6].to_vec()), [None, None, None, Some(61.5), Some(39.3), Some(44.38)]);
+        assert_eq!(column("OBVe_12")[..11], [None; 11]);
+        assert!(column("OBVe_12")[11].is_some());
+
+        // a run needs both averages, so none is flagged before the slow one
+        // has a value `run_length` bars back, and never long and short at once
+        let long_run = column("AOBV_LR_2");
+        let short_run = column("AOBV_SR_2");
+        assert!(long_run[..13].iter().chain(&short_run[..13]).all(|v| *v == Some(0.0)));
+        assert!(long_run.iter().zip(&short_run).all(|(l, s)| {
+            matches!((l, s), (Some(0.0), Some(0.0)) | (Some(1.0), Some(0.0)) | (Some(0.0), Some(1.0)))
+        }));
+        assert!(long_run.contains(&Some(1.0)));
+        assert!(short_run.contains(&Some(1.0)));
+    }
+}
@@ -0,0 +1,5 @@
+pub mod obv;
+pub mod nvi;
+pub mod pvi;
+pub mod aobv;
+pub mod vp;
\ No newline at end of file
@@ -0,0 +1,121 @@
+use crate::utils::core::{get_length, get_offset, fill_missing, FillPolicy};
+use crate::utils::missing::prepare;
+use crate::utils::error::CommandResult;
+use crate::utils::validate;
+use polars::prelude::*;
+
+
+/// Shared kernel of the negative and positive volume index. The index starts
+/// at `initial` and adds the `length` bar rate of change of close on every bar
+/// for which `select` accepts the change in volume.
+pub(crate) fn volume_index(
+    close: &Series,
+    volume: &Series,
+    length: Option<i32>,
+    initial: Option<f64>,
+    offset: Option<i32>,
+    select: fn(f64) -> bool,
+) -> CommandResult<Series> {
+    let prepared = prepare(&[close, volume])?;
+    let (close, volume) = (&prepared.inputs[0], &prepared.inputs[1]);
+    let length = validate::length("length", length, 1, 1)? as usize;
+    let initial = validate::positive("initial", initial, 1000.0)?;
+    let offset = get_offset(offset);
+
+    let close = close.cast(&DataType::Float64)?;
+    let close: Vec<Option<f64>> = close.f64()?.into_iter().collect();
+    let volume = volume.cast(&DataType::Float64)?;
+    let volume: Vec<Option<f64>> = volume.f64()?.into_iter().collect();
+
+    // the first bar is the seed; bars inside the rate of change warm-up and
+    // bars with missing inputs carry the previous value forward
+    let mut index = Vec::with_capacity(close.len());
+    let mut value = initial;
+    for i in 0..close.len() {
+        if i > 0 {
+            let change = match (volume[i - 1], volume[i]) {
+                (Some(prev), Some(curr)) => Some(curr - prev),
+                _ => None
+            };
+            let roc = match (i.checked_sub(length).and_then(|j| close[j]), close[i]) {
+                (Some(prev), Some(curr)) => Some(100.0 * (curr - prev) / prev),
+                _ => None
+            };
+            if let (Some(change), Some(roc)) = (change, roc) {
+                if select(change) {
+                    value += roc;
+                }
+            }
+        }
+        index.push(value);
+    }
+
+    let index = Series::new("index".into(), index);
+
+    let index = prepared.restore(index, offset)?;
+
+    Ok(index)
+}
+
+
+pub fn nvi(
+    close: &Series,
+    volume: &Series,
+    length: Option<i32>,
+    initial: Option<f64>,
+    offset: Option<i32>,
+    fill: Option<FillPolicy>
+) -> CommandResult<Series> {
+    let nvi = volume_index(
+        close,
+        volume,
+        length,
+        initial,
+        offset,
+        |change| change < 0.0
+    )?;
+    let nvi = fill_missing(nvi, fill)?;
+
+    Ok(nvi.with_name(format!("NVI_{}", get_length(length, 1)).into()))
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::utils::data_loader::{
+        csv_to_dataframe, 
+        set_column_names, 
+        combine_date_time, 
+        convert_to_naive_datetime
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_nvi() {
+        let mut df = csv_to_dataframe(
+            "data/AUDNZD1.csv", 
+            false
+        ).unwrap();
+        let _ = set_column_names(
+            &mut df, 
+            vec!["date", "time", "open", "high", "low", "close", "volume"]
+        );
+        let df = combine_date_time(&df).unwrap();
+        let df = convert_to_naive_datetime(
+            &df, 
+            "%Y.%m.%d %H:%M"
+        ).unwrap();
+
+        let close = df.column("close").unwrap().as_series().unwrap();
+        let volume = df.column("volume").unwrap().as_series().unwrap();
+        let nvi = nvi(close, volume, None, None, None, None).unwrap();
+        let nvi = nvi.f64().unwrap();
+        assert_eq!(nvi.len(), close.len());
+        assert_eq!(nvi.get(0), Some(1000.0));
+        // volume fell from 91 to 80 so the close change is added
+        let roc = 100.0 * (1.08069 - 1.08086) / 1.08086;
+        assert!((nvi.get(1).unwrap() - (1000.0 + roc)).abs() < 1e-9);
+    }
+}
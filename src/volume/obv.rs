@@ -0,0 +1,82 @@
+use crate::utils::core::{get_offset, signed_series, fill_missing, FillPolicy};
+use crate::utils::missing::prepare;
+use crate::utils::error::CommandResult;
+use polars::prelude::*;
+
+
+pub fn obv(
+    close: &Series,
+    volume: &Series,
+    offset: Option<i32>,
+    fill: Option<FillPolicy>
+) -> CommandResult<Series> {
+    let prepared = prepare(&[close, volume])?;
+    let (close, volume) = (&prepared.inputs[0], &prepared.inputs[1]);
+    let offset = get_offset(offset);
+    let volume = volume.cast(&DataType::Float64)?;
+
+    // the first bar counts as an up bar so obv starts at the first volume
+    let signed_volume = match signed_series(close, Some(1.0))? * volume {
+        Ok(signed_volume) => signed_volume,
+        Err(_) => return Err("Failed to calculate signed volume".into())
+    };
+
+    // running total, a missing bar keeps the total but stays null itself
+    let mut total = 0.0;
+    let obv: Series = signed_volume
+        .f64()?
+        .into_iter()
+        .map(|value| {
+            value.map(|v| {
+                total += v;
+                total
+            })
+        })
+        .collect();
+
+    let obv = prepared.restore(obv, offset)?;
+
+    let obv = fill_missing(obv, fill)?;
+
+    Ok(obv.with_name("OBV".into()))
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::utils::data_loader::{
+        csv_to_dataframe, 
+        set_column_names, 
+        combine_date_time, 
+        convert_to_naive_datetime
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_obv() {
+        let mut df = csv_to_dataframe(
+            "data/AUDNZD1.csv", 
+            false
+        ).unwrap();
+        let _ = set_column_names(
+            &mut df, 
+            vec!["date", "time", "open", "high", "low", "close", "volume"]
+        );
+        let df = combine_date_time(&df).unwrap();
+        let df = convert_to_naive_datetime(
+            &df, 
+            "%Y.%m.%d %H:%M"
+        ).unwrap();
+
+        let close = df.column("close").unwrap().as_series().unwrap();
+        let volume = df.column("volume").unwrap().as_series().unwrap();
+        let obv = obv(close, volume, None, None).unwrap();
+        let obv = obv.f64().unwrap();
+        // 91 up, 80 down, 61 up
+        assert_eq!(obv.get(0), Some(91.0));
+        assert_eq!(obv.get(1), Some(11.0));
+        assert_eq!(obv.get(2), Some(72.0));
+    }
+}
@@ -0,0 +1,65 @@
+use crate::utils::core::{fill_missing, get_length, FillPolicy};
+use crate::volume::nvi::volume_index;
+use crate::utils::error::CommandResult;
+use polars::prelude::*;
+
+
+pub fn pvi(
+    close: &Series,
+    volume: &Series,
+    length: Option<i32>,
+    initial: Option<f64>,
+    offset: Option<i32>,
+    fill: Option<FillPolicy>
+) -> CommandResult<Series> {
+    let pvi = volume_index(
+        close,
+        volume,
+        length,
+        initial,
+        offset,
+        |change| change > 0.0
+    )?;
+    let pvi = fill_missing(pvi, fill)?;
+
+    Ok(pvi.with_name(format!("PVI_{}", get_length(length, 1)).into()))
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::utils::data_loader::{
+        csv_to_dataframe, 
+        set_column_names, 
+        combine_date_time, 
+        convert_to_naive_datetime
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_pvi() {
+        let mut df = csv_to_dataframe(
+            "data/AUDNZD1.csv", 
+            false
+        ).unwrap();
+        let _ = set_column_names(
+            &mut df, 
+            vec!["date", "time", "open", "high", "low", "close", "volume"]
+        );
+        let df = combine_date_time(&df).unwrap();
+        let df = convert_to_naive_datetime(
+            &df, 
+            "%Y.%m.%d %H:%M"
+        ).unwrap();
+
+        let close = df.column("close").unwrap().as_series().unwrap();
+        let volume = df.column("volume").unwrap().as_series().unwrap();
+        let pvi = pvi(close, volume, None, None, None, None).unwrap();
+        let pvi = pvi.f64().unwrap();
+        assert_eq!(pvi.get(0), Some(1000.0));
+        // volume fell from 91 to 80 so the index is unchanged
+        assert_eq!(pvi.get(1), Some(1000.0));
+    }
+}
@@ -0,0 +1,310 @@
+use crate::utils::core::signed_series;
+use crate::utils::error::{CommandError, CommandResult};
+use crate::utils::missing::prepare;
+use crate::utils::validate;
+use polars::prelude::*;
+
+
+/// How the close prices are grouped into the bins of the profile.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum VpBins {
+    /// `width` bins spanning equal slices of the close price range.
+    EqualWidth,
+    /// `width` bins holding (nearly) the same number of bars.
+    EqualCount,
+}
+
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct VpOptions {
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub width: Option<i32>,
+    pub sort_close: bool,
+    pub bins: VpBins,
+}
+
+
+impl Default for VpOptions {
+    fn default() -> Self {
+        VpOptions {
+            width: Some(10),
+            sort_close: false,
+            bins: VpBins::EqualCount,
+        }
+    }
+}
+
+
+#[derive(Default)]
+struct Bin {
+    low: Option<f64>,
+    high: Option<f64>,
+    sum: f64,
+    count: usize,
+    volume: f64,
+    pos_volume: f64,
+    neg_volume: f64,
+}
+
+
+impl Bin {
+    fn push(&mut self, close: f64, sign: f64, volume: f64) {
+        self.low = Some(self.low.map_or(close, |low| low.min(close)));
+        self.high = Some(self.high.map_or(close, |high| high.max(close)));
+        self.sum += close;
+        self.count += 1;
+        self.volume += volume;
+        if sign > 0.0 {
+            self.pos_volume += volume;
+        } else if sign < 0.0 {
+            self.neg_volume += volume;
+        }
+    }
+
+    fn mean(&self) -> Option<f64> {
+        if self.count > 0 {
+            Some(self.sum / self.count as f64)
+        } else {
+            None
+        }
+    }
+}
+
+
+/// Volume profile: aggregates the whole input into `width` price bins with
+/// the total, positive (close up) and negative (close down) volume of each.
+/// The total is all the volume in the bin. pandas-ta adds the positive and
+/// negative volume instead, which leaves out bars with an unchanged close.
+///
+/// Unlike the bar-aligned indicators the result has one row per bin. With
+/// `VpBins::EqualWidth` the bins are equal slices of the close range and
+/// `low_close`/`high_close` are the bin edges, always in ascending price
+/// order. With `VpBins::EqualCount` the bars are split into equally sized
+/// chunks, in time order or, when `sort_close` is set, after sorting by close,
+/// and `low_close`/`high_close` are the extremes of each chunk. Bars with a
+/// missing (null or NaN) close or volume are left out of the profile.
+pub fn vp(
+    close: &Series,
+    volume: &Series,
+    options: VpOptions
+) -> CommandResult<DataFrame> {
+    let prepared = prepare(&[close, volume])?;
+    let (close, volume) = (&prepared.inputs[0], &prepared.inputs[1]);
+    let width = validate::length("width", options.width, 1, 10)? as usize;
+
+    let signs = signed_series(close, Some(1.0))?;
+
+    // bars with a missing close or volume cannot be placed in the profile,
+    // nor be compared with the next close
+    let mut bars: Vec<(f64, f64, f64)> = close
+        .f64()?
+        .into_iter()
+        .zip(signs.f64()?)
+        .zip(volume.f64()?)
+        .filter_map(|((close, sign), volume)| {
+            Some((close?, sign.unwrap_or(0.0), volume?))
+        })
+        .collect();
+
+    if bars.is_empty() {
+        return Err(CommandError::InsufficientData { needed: 1, got: 0 });
+    }
+
+    let bins = match options.bins {
+        VpBins::EqualWidth => {
+            let (min, max) = bars.iter().fold(
+                (f64::INFINITY, f64::NEG_INFINITY),
+                |(min, max), (close, _, _)| (min.min(*close), max.max(*close))
+            );
+            let step = (max - min) / width as f64;
+            let mut bins: Vec<Bin> = (0..width).map(|_| Bin::default()).collect();
+            for (close, sign, volume) in bars {
+                let i = if step > 0.0 {
+                    (((close - min) / step) as usize).min(width - 1)
+                } else {
+                    0
+                };
+                bins[i].push(close, sign, volume);
+            }
+            // report the bin edges rather than the observed extremes
+            for (i, bin) in bins.iter_mut().enumerate() {
+                bin.low = Some(min + step * i as f64);
+                bin.high = Some(if i == width - 1 { max } else { min + step * (i + 1) as f64 });
+            }
+            bins
+        },
+        VpBins::EqualCount => {
+            if options.sort_close {
+                bars.sort_by(|a, b| a.0.total_cmp(&b.0));
+            }
+            // split like numpy's array_split: the first len % width chunks
+            // take one extra bar
+            let width = width.min(bars.len());
+            let size = bars.len() / width;
+            let extra = bars.len() % width;
+            let mut bins = Vec::with_capacity(width);
+            let mut start = 0;
+            for i in 0..width {
+                let end = start + size + usize::from(i < extra);
+                let mut bin = Bin::default();
+                for (close, sign, volume) in &bars[start..end] {
+                    bin.push(*close, *sign, *volume);
+                }
+                bins.push(bin);
+                start = end;
+            }
+            bins
+        }
+    };
+
+    let df = df! {
+        "bin" => (0..bins.len() as u32).collect::<Vec<_>>(),
+        "low_close" => bins.iter().map(|bin| bin.low).collect::<Vec<_>>(),
+        "mean_close" => bins.iter().map(|bin| bin.mean()).collect::<Vec<_>>(),
+        "high_close" => bins.iter().map(|bin| bin.high).collect::<Vec<_>>(),
+        "pos_volume" => bins.iter().map(|bin| bin.pos_volume).collect::<Vec<_>>(),
+        "neg_volume" => bins.iter().map(|bin| bin.neg_volume).collect::<Vec<_>>(),
+        "total_volume" => bins.iter().map(|bin| bin.volume).collect::<Vec<_>>(),
+    }?;
+
+    Ok(df)
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::utils::data_loader::{
+        csv_to_dataframe, 
+        set_column_names, 
+        combine_date_time, 
+        convert_to_naive_datetime
+    };
+
+    use crate::utils::missing::{with_missing_policy, InteriorNulls, MissingPolicy};
+
+    use super::*;
+
+    #[test]
+    fn test_vp() {
+        let mut df = csv_to_dataframe(
+            "data/AUDNZD1.csv", 
+            false
+        ).unwrap();
+        let _ = set_column_names(
+            &mut df, 
+            vec!["date", "time", "open", "high", "low", "close", "volume"]
+        );
+        let df = combine_date_time(&df).unwrap();
+        let df = convert_to_naive_datetime(
+            &df, 
+            "%Y.%m.%d %H:%M"
+        ).unwrap();
+
+        let close = df.column("close").unwrap().as_series().unwrap();
+        let volume = df.column("volume").unwrap().as_series().unwrap();
+        let profile = vp(
+            close,
+            volume,
+            VpOptions {
+                ..Default::default()
+            }
+        ).unwrap();
+        assert_eq!(profile.height(), 10);
+
+        let column = |name: &str| -> Vec<f64> {
+            profile.column(name).unwrap().f64().unwrap().into_iter().map(|v| v.unwrap()).collect()
+        };
+        let volume_sum = volume.cast(&DataType::Float64).unwrap().f64().unwrap().sum().unwrap();
+        assert_eq!(column("total_volume").iter().sum::<f64>(), volume_sum);
+        // the 4th bar has an unchanged close, so its volume is in the total only
+        let (pos, neg, total) = (column("pos_volume"), column("neg_volume"), column("total_volume"));
+        for i in 0..10 {
+            assert!(pos[i] + neg[i] <= total[i]);
+        }
+        assert!(pos[0] + neg[0] < total[0]);
+        // equal-count chunks in time order: the first bin holds the first bar
+        let low = column("low_close");
+        let high = column("high_close");
+        let mean = column("mean_close");
+        for i in 0..10 {
+            assert!(low[i] <= mean[i] && mean[i] <= high[i]);
+        }
+        assert!(low[0] <= 1.08086 && 1.08086 <= high[0]);
+    }
+
+    #[test]
+    fn test_vp_flat_volume() {
+        let close = Series::new("close".into(), vec![1.0, 1.0, 1.0]);
+        let volume = Series::new("volume".into(), vec![1.0, 2.0, 3.0]);
+
+        let profile = vp(&close, &volume, VpOptions {
+            width: Some(1),
+            ..Default::default()
+        }).unwrap();
+        let column = |name: &str| profile.column(name).unwrap().f64().unwrap().get(0);
+        // only the first bar is counted as up, the flat bars count in the total
+        assert_eq!(column("pos_volume"), Some(1.0));
+        assert_eq!(column("neg_volume"), Some(0.0));
+        assert_eq!(column("total_volume"), Some(6.0));
+    }
+
+    #[test]
+    fn test_vp_bins() {
+        let close = Series::new("close".into(), vec![1.0, 2.0, 3.0, 4.0, 10.0]);
+        let volume = Series::new("volume".into(), vec![5.0, 1.0, 1.0, 1.0, 2.0]);
+
+        let profile = vp(&close, &volume, VpOptions {
+            width: Some(2),
+            bins: VpBins::EqualWidth,
+            ..Default::default()
+        }).unwrap();
+        let total: Vec<_> = profile.column("total_volume").unwrap().f64().unwrap().into_iter().collect();
+        assert_eq!(total, vec![Some(8.0), Some(2.0)]);
+        let high: Vec<_> = profile.column("high_close").unwrap().f64().unwrap().into_iter().collect();
+        assert_eq!(high, vec![Some(5.5), Some(10.0)]);
+
+        let profile = vp(&close, &volume, VpOptions {
+            width: Some(2),
+            bins: VpBins::EqualCount,
+            ..Default::default()
+        }).unwrap();
+        let total: Vec<_> = profile.column("total_volume").unwrap().f64().unwrap().into_iter().collect();
+        assert_eq!(total, vec![Some(7.0), Some(3.0)]);
+    }
+
+    #[test]
+    fn test_vp_missing() {
+        let close = Series::new("close".into(), vec![1.0, 2.0, f64::NAN, 3.0, 2.5]);
+        let volume = Series::new("volume".into(), vec![Some(1.0), Some(2.0), Some(4.0), Some(8.0), None]);
+
+        let profile = vp(&close, &volume, VpOptions {
+            width: Some(1),
+            ..Default::default()
+        }).unwrap();
+        let column = |name: &str| profile.column(name).unwrap().f64().unwrap().get(0);
+        // the NaN bar and the one without volume are dropped, the bar after
+        // the NaN has no change to sign so it counts in the total only
+        assert_eq!(column("total_volume"), Some(11.0));
+        assert_eq!(column("pos_volume"), Some(3.0));
+        assert_eq!(column("neg_volume"), Some(0.0));
+        assert_eq!(column("low_close"), Some(1.0));
+        assert_eq!(column("high_close"), Some(3.0));
+        assert_eq!(column("mean_close"), Some(2.0));
+
+        let policy = MissingPolicy { interior: InteriorNulls::Error, ..Default::default() };
+        assert!(with_missing_policy(policy, || vp(&close, &volume, VpOptions::default())).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_vp_options_serde() {
+        let options: VpOptions = serde_json::from_str(r#"{"bins": "EqualWidth"}"#).unwrap();
+        assert_eq!(options, VpOptions { bins: VpBins::EqualWidth, ..Default::default() });
+        let saved = serde_json::to_string(&options).unwrap();
+        assert_eq!(serde_json::from_str::<VpOptions>(&saved).unwrap(), options);
+    }
+}
@@ -0,0 +1,484 @@
+"""Writes the golden-value fixtures checked by tests/golden.rs.
+
+    python3 tests/fixtures/generate.py [--rows 500] [--reference] [--only FILE ...]
+
+The fixtures hold reference outputs for the first rows of data/AUDNZD1.csv:
+pandas_ta.csv with pandas-ta's defaults and talib.csv with TA-Lib's.
+talib_cdl.csv lists the non-zero signals of every TA-Lib candlestick pattern
+on the first --cdl-rows rows. --only writes the given fixtures and leaves
+the others, e.g. --only talib_cdl.csv where TA-Lib but not pandas-ta is
+installed.
+
+pandas-ta and TA-Lib compute the fixtures when they are importable. With
+--reference, a missing library is replaced by the standard library
+implementations below, transcribed from the pandas-ta and TA-Lib sources
+(pandas' ewm, rolling and diff semantics, TA-Lib's seeding). Those are
+ports, not library output, and only as faithful as the transcription.
+backend.txt records which one wrote each fixture, with the exact library
+versions when the libraries did. There is no port of the
+TA-Lib candlestick patterns, talib_cdl.csv needs TA-Lib itself.
+"""
+
+import sys
+
+import argparse
+import csv
+import math
+import os
+
+NAN = float("nan")
+HERE = os.path.dirname(os.path.abspath(__file__))
+DATA = os.path.join(HERE, "..", "..", "data", "AUDNZD1.csv")
+BACKENDS = os.path.join(HERE, "backend.txt")
+FIXTURES = ["pandas_ta.csv", "talib.csv", "talib_cdl.csv"]
+
+
+def isnan(x):
+    return x != x
+
+
+# -- pandas primitives -------------------------------------------------------
+
+def shift(xs, n):
+    return [NAN] * n + xs[:len(xs) - n]
+
+
+def rolling(xs, n, f):
+    out = []
+    for t in range(len(xs)):
+        window = xs[t + 1 - n:t + 1] if t + 1 >= n else None
+        out.append(NAN if window is None or any(map(isnan, window)) else f(window))
+    return out
+
+
+def mean(w):
+    return sum(w) / len(w)
+
+
+def var(w, ddof=1):
+    m = mean(w)
+    return sum((x - m) ** 2 for x in w) / (len(w) - ddof)
+
+
+def ewm(xs, alpha, adjust, min_periods=0):
+    """pandas' ewm(...).mean() with ignore_na=False."""
+    out, weighted, weights, old_wt, seen = [], NAN, 0.0, 1.0, 0
+    for x in xs:
+        if not isnan(x):
+            seen += 1
+        if isnan(weighted):
+            if not isnan(x):
+                weighted, old_wt = x, 1.0
+        elif adjust:
+            old_wt *= 1 - alpha
+            if not isnan(x):
+                new_wt = 1.0
+                weighted = (old_wt * weighted + new_wt * x) / (old_wt + new_wt)
+                old_wt += new_wt
+        else:
+            old_wt *= 1 - alpha
+            if not isnan(x):
+                weighted = (old_wt * weighted + alpha * x) / (old_wt + alpha)
+                old_wt = 1.0
+        out.append(weighted if seen >= max(min_periods, 1) else NAN)
+    return out
+
+
+def non_zero_range(xs, ys):
+    """pandas-ta's non_zero_range: epsilon is added to every difference
+    when any of them is zero."""
+    diff = [x - y for x, y in zip(xs, ys)]
+    return [d + sys.float_info.epsilon for d in diff] if 0.0 in diff else diff
+
+
+def sign_series(xs, initial):
+    out = [initial]
+    for prev, curr in zip(xs, xs[1:]):
+        d = curr - prev
+        out.append(1.0 if d > 0 else -1.0 if d < 0 else 0.0)
+    return out
+
+
+# -- pandas-ta ---------------------------------------------------------------
+
+def pta_sma(xs, n):
+    return rolling(xs, n, mean)
+
+
+def pta_ema(xs, n):
+    # sma=True seed, then ewm(span=n, adjust=False)
+    valid = [x for x in xs[:n] if not isnan(x)]
+    seeded = [NAN] * (n - 1) + [sum(valid) / len(valid)] + xs[n:]
+    return ewm(seeded, 2 / (n + 1), adjust=False)
+
+
+def pta_rma(xs, n):
+    return ewm(xs, 1 / n, adjust=True, min_periods=n)
+
+
+def pta_dema(xs, n):
+    ema1 = pta_ema(xs, n)
+    ema2 = pta_ema(ema1, n)
+    return [2 * a - b for a, b in zip(ema1, ema2)]
+
+
+def pta_true_range(h, l, c, drift=1):
+    prev = shift(c, drift)
+    out = []
+    for i, (hi, lo, pc) in enumerate(zip(h, l, prev)):
+        hl = hi - lo
+        hl = hl + 2.220446049250313e-16 if hl == 0 else hl
+        ranges = [abs(v) for v in (hl, hi - pc, pc - lo) if not isnan(v)]
+        out.append(NAN if i < drift else max(ranges))
+    return out
+
+
+def pta_rsi(xs, n, drift=1):
+    diff = [a - b for a, b in zip(xs, shift(xs, drift))]
+    pos = [max(d, 0.0) if not isnan(d) else d for d in diff]
+    neg = [min(d, 0.0) if not isnan(d) else d for d in diff]
+    pos_avg, neg_avg = pta_rma(pos, n), pta_rma(neg, n)
+    return [100 * p / (p + abs(q)) for p, q in zip(pos_avg, neg_avg)]
+
+
+def pta_linreg(xs, n, output):
+    """pandas-ta's linreg with x = 1..n: the default output is
+    m * (n - 1) + b, the fit one bar before the end of the window, tsf is
+    m * n + b and the intercept b itself, the fit at x = 0."""
+    x_sum = 0.5 * n * (n + 1)
+    x2_sum = x_sum * (2 * n + 1) / 3
+    divisor = n * x2_sum - x_sum * x_sum
+
+    def fit(w):
+        y_sum = sum(w)
+        xy_sum = sum((k + 1) * y for k, y in enumerate(w))
+        m = (n * xy_sum - x_sum * y_sum) / divisor
+        b = (y_sum * x2_sum - x_sum * xy_sum) / divisor
+        return output(m, b)
+    return rolling(xs, n, fit)
+
+
+def pta_skew(w):
+    n, m = len(w), mean(w)
+    m2 = sum((x - m) ** 2 for x in w) / n
+    m3 = sum((x - m) ** 3 for x in w) / n
+    return math.sqrt(n * (n - 1)) / (n - 2) * m3 / m2 ** 1.5
+
+
+def pta_kurt(w):
+    n, m = len(w), mean(w)
+    s2 = sum((x - m) ** 2 for x in w)
+    s4 = sum((x - m) ** 4 for x in w)
+    v = s2 / (n - 1)
+    return (n * (n + 1) * s4 / ((n - 1) * (n - 2) * (n - 3) * v * v)
+            - 3 * (n - 1) ** 2 / ((n - 2) * (n - 3)))
+
+
+def pta_median(w):
+    s = sorted(w)
+    k = len(s) // 2
+    return s[k] if len(s) % 2 else 0.5 * (s[k - 1] + s[k])
+
+
+def pta_entropy(xs, n, base=2.0):
+    sums = rolling(xs, n, sum)
+    p = [x / s for x, s in zip(xs, sums)]
+    terms = [-q * math.log(q) / math.log(base) for q in p]
+    return rolling(terms, n, sum)
+
+
+def pta_cdl_doji(o, h, l, c, length=10, factor=10.0, scalar=100):
+    body = [abs(x) for x in non_zero_range(c, o)]
+    hl_range_avg = pta_sma([abs(x) for x in non_zero_range(h, l)], length)
+    # a missing average compares False
+    return [float(scalar) if b < 0.01 * factor * a else 0.0 for b, a in zip(body, hl_range_avg)]
+
+
+def pta_cdl_inside(o, h, l, c):
+    out = [0.0]
+    for i in range(1, len(c)):
+        inside = h[i] - h[i - 1] < 0 and l[i] - l[i - 1] > 0
+        color = 1 if c[i] >= o[i] else -1
+        out.append(float(int(inside) * color))
+    return out
+
+
+def pta_volume_index(c, v, select, length=1, initial=1000.0):
+    prev = shift(c, length)
+    roc = [100 * (a - b) / b for a, b in zip(c, prev)]
+    signs = sign_series(v, 1.0)
+    out, total = [], 0.0
+    for i, (r, s) in enumerate(zip(roc, signs)):
+        step = initial if i == 0 else (r if select(s) and not isnan(r) else 0.0)
+        total += step
+        out.append(total)
+    return out
+
+
+def pandas_ta_fixture(o, h, l, c, v):
+    zs_mean = pta_sma(c, 30)
+    zs_std = [math.sqrt(x) for x in rolling(c, 30, var)]
+    return {
+        "SMA_10": pta_sma(c, 10),
+        "EMA_10": pta_ema(c, 10),
+        "RMA_10": pta_rma(c, 10),
+        "DEMA_10": pta_dema(c, 10),
+        "TRUERANGE_1": pta_true_range(h, l, c),
+        "ATRr_14": pta_rma(pta_true_range(h, l, c), 14),
+        "RSI_14": pta_rsi(c, 14),
+        "LR_14": pta_linreg(c, 14, lambda m, b: m * 13 + b),
+        "LRm_14": pta_linreg(c, 14, lambda m, b: m),
+        "LRb_14": pta_linreg(c, 14, lambda m, b: b),
+        "LRtsf_14": pta_linreg(c, 14, lambda m, b: m * 14 + b),
+        "VAR_30": rolling(c, 30, var),
+        "STDEV_30": zs_std,
+        "ZS_30": [(x - m) / s for x, m, s in zip(c, zs_mean, zs_std)],
+        "SKEW_30": rolling(c, 30, pta_skew),
+        "KURT_30": rolling(c, 30, pta_kurt),
+        "MAD_30": rolling(c, 30, lambda w: mean([abs(x - mean(w)) for x in w])),
+        "MEDIAN_30": rolling(c, 30, pta_median),
+        "ENTP_10": pta_entropy(c, 10),
+        "LOGRET_1": [math.log(a / b) for a, b in zip(c, shift(c, 1))],
+        "PCTRET_1": [a / b - 1 for a, b in zip(c, shift(c, 1))],
+        "OBV": [sum(s * x for s, x in zip(sign_series(c, 1.0)[:i + 1], v)) for i in range(len(c))],
+        "NVI_1": pta_volume_index(c, v, lambda s: s < 0),
+        "PVI_1": pta_volume_index(c, v, lambda s: s > 0),
+        "CDL_DOJI_10_0.1": pta_cdl_doji(o, h, l, c),
+        "CDL_INSIDE": pta_cdl_inside(o, h, l, c),
+    }
+
+
+def pandas_ta_library_fixture(o, h, l, c, v):
+    import pandas as pd
+    import pandas_ta as ta
+    df = pd.DataFrame({"open": o, "high": h, "low": l, "close": c, "volume": v})
+    out = {
+        "SMA_10": ta.sma(df.close, 10, talib=False),
+        "EMA_10": ta.ema(df.close, 10, talib=False),
+        "RMA_10": ta.rma(df.close, 10),
+        "DEMA_10": ta.dema(df.close, 10, talib=False),
+        "TRUERANGE_1": ta.true_range(df.high, df.low, df.close, talib=False),
+        "ATRr_14": ta.atr(df.high, df.low, df.close, 14, talib=False),
+        "RSI_14": ta.rsi(df.close, 14, talib=False),
+        "LR_14": ta.linreg(df.close, 14, talib=False),
+        "LRm_14": ta.linreg(df.close, 14, slope=True, talib=False),
+        "LRb_14": ta.linreg(df.close, 14, intercept=True, talib=False),
+        "LRtsf_14": ta.linreg(df.close, 14, tsf=True, talib=False),
+        "VAR_30": ta.variance(df.close, 30, talib=False),
+        "STDEV_30": ta.stdev(df.close, 30, talib=False),
+        "ZS_30": ta.zscore(df.close, 30),
+        "SKEW_30": ta.skew(df.close, 30),
+        "KURT_30": ta.kurtosis(df.close, 30),
+        "MAD_30": ta.mad(df.close, 30),
+        "MEDIAN_30": ta.median(df.close, 30),
+        "ENTP_10": ta.entropy(df.close, 10),
+        "LOGRET_1": ta.log_return(df.close, 1),
+        "PCTRET_1": ta.percent_return(df.close, 1),
+        "OBV": ta.obv(df.close, df.volume, talib=False),
+        "NVI_1": ta.nvi(df.close, df.volume),
+        "PVI_1": ta.pvi(df.close, df.volume),
+        "CDL_DOJI_10_0.1": ta.cdl_doji(df.open, df.high, df.low, df.close),
+        "CDL_INSIDE": ta.cdl_inside(df.open, df.high, df.low, df.close),
+    }
+    return {name: [float(x) for x in series] for name, series in out.items()}
+
+
+# -- TA-Lib ------------------------------------------------------------------
+
+def tal_ema(xs, n):
+    k = 2 / (n + 1)
+    out = [NAN] * (n - 1) + [mean(xs[:n])]
+    for x in xs[n:]:
+        out.append((x - out[-1]) * k + out[-1])
+    return out
+
+
+def tal_linreg(xs, n, output):
+    sum_x = n * (n - 1) * 0.5
+    sum_x_sqr = n * (n - 1) * (2 * n - 1) / 6
+    divisor = sum_x * sum_x - n * sum_x_sqr
+
+    def fit(w):
+        sum_xy = sum(i * w[n - 1 - i] for i in range(n))
+        sum_y = sum(w)
+        m = (n * sum_xy - sum_x * sum_y) / divisor
+        b = (sum_y - m * sum_x) / n
+        return output(m, b)
+    return rolling(xs, n, fit)
+
+
+def tal_wilder(xs, n, first):
+    """Wilder smoothing seeded with the mean of xs[first:first + n]."""
+    out = [NAN] * (first + n - 1) + [mean(xs[first:first + n])]
+    for x in xs[first + n:]:
+        out.append((out[-1] * (n - 1) + x) / n)
+    return out
+
+
+def tal_trange(h, l, c):
+    out = [NAN]
+    for i in range(1, len(c)):
+        out.append(max(h[i] - l[i], abs(h[i] - c[i - 1]), abs(l[i] - c[i - 1])))
+    return out
+
+
+def tal_rsi(xs, n):
+    gains = [NAN] + [max(b - a, 0.0) for a, b in zip(xs, xs[1:])]
+    losses = [NAN] + [max(a - b, 0.0) for a, b in zip(xs, xs[1:])]
+    gain, loss = tal_wilder(gains, n, 1), tal_wilder(losses, n, 1)
+    return [NAN if isnan(g) else 100 * g / (g + q) for g, q in zip(gain, loss)]
+
+
+def talib_fixture(o, h, l, c, v):
+    obv, total = [], v[0]
+    for i in range(len(c)):
+        if i > 0:
+            total += v[i] if c[i] > c[i - 1] else -v[i] if c[i] < c[i - 1] else 0.0
+        obv.append(total)
+    return {
+        "SMA_10": pta_sma(c, 10),
+        "EMA_10": tal_ema(c, 10),
+        "TRANGE": tal_trange(h, l, c),
+        "ATR_14": tal_wilder(tal_trange(h, l, c), 14, 1),
+        "RSI_14": tal_rsi(c, 14),
+        "LINEARREG_14": tal_linreg(c, 14, lambda m, b: b + m * 13),
+        "LINEARREG_SLOPE_14": tal_linreg(c, 14, lambda m, b: m),
+        "LINEARREG_INTERCEPT_14": tal_linreg(c, 14, lambda m, b: b),
+        "LINEARREG_ANGLE_14": tal_linreg(c, 14, lambda m, b: math.atan(m) * 180 / math.pi),
+        "TSF_14": tal_linreg(c, 14, lambda m, b: b + m * 14),
+        "STDDEV_30": rolling(c, 30, lambda w: math.sqrt(var(w, 0))),
+        "VAR_30": rolling(c, 30, lambda w: var(w, 0)),
+        "OBV": obv,
+    }
+
+
+def talib_library_fixture(o, h, l, c, v):
+    import numpy as np
+    import talib
+    c, h, l, v = (np.array(x) for x in (c, h, l, v))
+    out = {
+        "SMA_10": talib.SMA(c, 10),
+        "EMA_10": talib.EMA(c, 10),
+        "TRANGE": talib.TRANGE(h, l, c),
+        "ATR_14": talib.ATR(h, l, c, 14),
+        "RSI_14": talib.RSI(c, 14),
+        "LINEARREG_14": talib.LINEARREG(c, 14),
+        "LINEARREG_SLOPE_14": talib.LINEARREG_SLOPE(c, 14),
+        "LINEARREG_INTERCEPT_14": talib.LINEARREG_INTERCEPT(c, 14),
+        "LINEARREG_ANGLE_14": talib.LINEARREG_ANGLE(c, 14),
+        "TSF_14": talib.TSF(c, 14),
+        "STDDEV_30": talib.STDDEV(c, 30),
+        "VAR_30": talib.VAR(c, 30),
+        "OBV": talib.OBV(c, v),
+    }
+    return {name: [float(x) for x in series] for name, series in out.items()}
+
+
+def talib_cdl_library_fixture(o, h, l, c):
+    """The non-zero signals of every TA-Lib candlestick pattern, with TA-Lib's
+    default settings and penetrations, as (row, pattern, value) triples."""
+    import numpy as np
+    import talib
+    o, h, l, c = (np.array(x) for x in (o, h, l, c))
+    signals = []
+    for function in talib.get_function_groups()["Pattern Recognition"]:
+        values = getattr(talib, function)(o, h, l, c)
+        name = "CDL_" + function[len("CDL"):]
+        signals += [(row, name, int(value)) for row, value in enumerate(values) if value != 0]
+    return sorted(signals)
+
+
+def pandas_ta_versions():
+    import pandas
+    import pandas_ta
+    return f"pandas-ta {pandas_ta.version}, pandas {pandas.__version__}"
+
+
+def talib_versions():
+    import numpy
+    import talib
+    ta_version = talib.__ta_version__
+    if isinstance(ta_version, bytes):
+        ta_version = ta_version.decode()
+    return f"TA-Lib {ta_version}, ta-lib python {talib.__version__}, numpy {numpy.__version__}"
+
+
+# -- output ------------------------------------------------------------------
+
+def write(path, columns):
+    names = list(columns)
+    with open(path, "w", newline="") as f:
+        writer = csv.writer(f, lineterminator="\n")
+        writer.writerow(names)
+        for row in zip(*(columns[name] for name in names)):
+            writer.writerow(["" if isnan(x) else repr(x) for x in row])
+
+
+def write_signals(path, signals):
+    with open(path, "w", newline="") as f:
+        writer = csv.writer(f, lineterminator="\n")
+        writer.writerow(["row", "pattern", "value"])
+        writer.writerows(signals)
+
+
+def read_backends():
+    """What wrote each fixture so far, by file."""
+    if not os.path.exists(BACKENDS):
+        return {}
+    with open(BACKENDS) as f:
+        return dict(line.rstrip("\n").split(": ", 1) for line in f if line.strip())
+
+
+def read(rows):
+    with open(DATA) as f:
+        rows = [r for _, r in zip(range(rows), csv.reader(f))]
+    return ([float(r[k]) for r in rows] for k in range(2, 7))
+
+
+def main():
+    parser = argparse.ArgumentParser()
+    parser.add_argument("--rows", type=int, default=500)
+    parser.add_argument("--cdl-rows", type=int, default=20000)
+    parser.add_argument("--reference", action="store_true",
+                        help="use the ports below for a library that is not installed")
+    parser.add_argument("--only", nargs="+", choices=FIXTURES,
+                        help="the fixtures to write, the others are left as they are")
+    args = parser.parse_args()
+    only = args.only or FIXTURES
+
+    o, h, l, c, v = read(args.rows)
+
+    def compute(file, library, name, port, versions):
+        if file not in only:
+            return
+        try:
+            columns = library(o, h, l, c, v)
+            backends[file] = f"{name} output, {versions()}"
+        except ImportError:
+            if not args.reference:
+                parser.error(f"{name} is not installed, pass --reference to write {file} with the port")
+            columns = port(o, h, l, c, v)
+            backends[file] = f"generate.py port of {name}, not {name} output"
+        write(os.path.join(HERE, file), columns)
+
+    backends = read_backends()
+    compute("pandas_ta.csv", pandas_ta_library_fixture, "pandas-ta", pandas_ta_fixture, pandas_ta_versions)
+    compute("talib.csv", talib_library_fixture, "TA-Lib", talib_fixture, talib_versions)
+
+    if "talib_cdl.csv" in only:
+        try:
+            o, h, l, c, _ = read(args.cdl_rows)
+            signals = talib_cdl_library_fixture(o, h, l, c)
+            write_signals(os.path.join(HERE, "talib_cdl.csv"), signals)
+            backends["talib_cdl.csv"] = f"TA-Lib output, {talib_versions()}"
+        except ImportError:
+            if args.only:
+                parser.error("TA-Lib is not installed, talib_cdl.csv needs it")
+            print("TA-Lib is not installed, talib_cdl.csv is not written")
+
+    with open(BACKENDS, "w") as f:
+        f.writelines(f"{file}: {backends[file]}\n" for file in FIXTURES if file in backends)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,162 @@
+//! Property-based checks of indicator invariants on random bars.
+
+use polars::prelude::*;
+use polars_ta::momentum::rsi::{rsi, RsiOptions};
+use polars_ta::overlap::dema::{dema, DemaOptions};
+use polars_ta::overlap::ema::{ema, EmaOptions};
+use polars_ta::overlap::sma::{sma, SmaOptions};
+use polars_ta::volatility::atr::{atr, AtrOptions};
+use polars_ta::volatility::true_range::true_range;
+use proptest::prelude::*;
+
+
+/// Random OHLC bars with `low <= open, close <= high`.
+#[derive(Debug, Clone)]
+struct Bars {
+    open: Vec<f64>,
+    high: Vec<f64>,
+    low: Vec<f64>,
+    close: Vec<f64>,
+}
+
+
+impl Bars {
+    fn series(&self) -> [Series; 4] {
+        [
+            Series::new("open".into(), &self.open),
+            Series::new("high".into(), &self.high),
+            Series::new("low".into(), &self.low),
+            Series::new("close".into(), &self.close),
+        ]
+    }
+}
+
+
+/// A random walk of `len` bars, each with its own wicks. Ranges of zero are
+/// allowed since `true_range` treats them specially.
+fn bars(len: impl Into<prop::collection::SizeRange>) -> impl Strategy<Value = Bars> {
+    let bar = (-0.01f64..0.01, -0.01f64..0.01, 0.0f64..0.005, 0.0f64..0.005, 0u8..8);
+    prop::collection::vec(bar, len).prop_map(|steps| {
+        let mut bars = Bars { open: vec![], high: vec![], low: vec![], close: vec![] };
+        let mut price = 1.0;
+        for (gap, change, up, down, flat) in steps {
+            let open = price * (1.0 + gap);
+            let close = open * (1.0 + change);
+            let (up, down) = if flat == 0 { (0.0, 0.0) } else { (up, down) };
+            bars.open.push(open);
+            bars.high.push(open.max(close) * (1.0 + up));
+            bars.low.push(open.min(close) * (1.0 - down));
+            bars.close.push(if flat == 0 { open } else { close });
+            price = close;
+        }
+        bars
+    })
+}
+
+
+fn values(series: &Series) -> Vec<Option<f64>> {
+    series.f64().unwrap().into_iter().collect()
+}
+
+
+proptest! {
+    #[test]
+    fn generated_bars_are_valid(bars in bars(1..100)) {
+        for i in 0..bars.close.len() {
+            prop_assert!(bars.low[i] <= bars.open[i].min(bars.close[i]));
+            prop_assert!(bars.high[i] >= bars.open[i].max(bars.close[i]));
+        }
+    }
+
+    #[test]
+    fn true_range_covers_the_bar(bars in bars(2..200), drift in 1i32..5) {
+        let [_, high, low, close] = bars.series();
+        prop_assume!(close.len() > drift as usize);
+        let tr = true_range(&high, &low, &close, Some(drift), None, None).unwrap();
+        for (i, value) in values(&tr).into_iter().enumerate() {
+            match value {
+                None => prop_assert!(i < drift as usize, "missing true range at {}", i),
+                Some(value) => prop_assert!(
+                    value >= bars.high[i] - bars.low[i],
+                    "true range {} below the range {} at {}", value, bars.high[i] - bars.low[i], i
+                ),
+            }
+        }
+    }
+
+    #[test]
+    fn atr_is_positive(bars in bars(30..200), length in 1i32..15) {
+        let [_, high, low, close] = bars.series();
+        let options = AtrOptions { length: Some(length), ..Default::default() };
+        let atr = atr(&high, &low, &close, options).unwrap();
+        prop_assert!(values(&atr).into_iter().flatten().all(|value| value > 0.0));
+    }
+
+    #[test]
+    fn ema_of_a_constant_is_the_constant(
+        value in 0.01f64..1e6,
+        len in 20usize..200,
+        length in 1i32..20,
+        adjust in any::<bool>(),
+        sma_seed in any::<bool>()
+    ) {
+        let close = Series::new("close".into(), vec![value; len]);
+        let mamode = if sma_seed { "sma" } else { "ema" };
+        let ema = ema(&close, EmaOptions {
+            length: Some(length),
+            adjust,
+            mamode: Some(mamode.to_string()),
+            ..Default::default()
+        }).unwrap();
+        prop_assert_eq!(ema.len(), len);
+        for result in values(&ema).into_iter().flatten() {
+            prop_assert!((result - value).abs() <= 1e-12 * value, "{} != {}", result, value);
+        }
+    }
+
+    #[test]
+    fn dema_of_length_one_is_the_input(bars in bars(1..200)) {
+        let [_, _, _, close] = bars.series();
+        let dema = dema(&close, DemaOptions { length: Some(1), ..Default::default() }).unwrap();
+        for (result, value) in values(&dema).into_iter().zip(&bars.close) {
+            let result = result.unwrap();
+            prop_assert!((result - value).abs() <= 1e-12 * value, "{} != {}", result, value);
+        }
+    }
+
+    #[test]
+    fn offset_shifts_without_changing_the_length(bars in bars(30..100), offset in -10i32..10) {
+        let [_, high, low, close] = bars.series();
+        let shifted = [
+            sma(&close, SmaOptions { offset: Some(offset), ..Default::default() }).unwrap(),
+            ema(&close, EmaOptions { offset: Some(offset), ..Default::default() }).unwrap(),
+            dema(&close, DemaOptions { offset: Some(offset), ..Default::default() }).unwrap(),
+            true_range(&high, &low, &close, None, Some(offset), None).unwrap(),
+        ];
+        let unshifted = [
+            sma(&close, SmaOptions::default()).unwrap(),
+            ema(&close, EmaOptions::default()).unwrap(),
+            dema(&close, DemaOptions::default()).unwrap(),
+            true_range(&high, &low, &close, None, None, None).unwrap(),
+        ];
+        for (shifted, unshifted) in shifted.iter().zip(&unshifted) {
+            prop_assert_eq!(shifted.len(), close.len());
+            prop_assert!(shifted.equals_missing(&unshifted.shift(offset as i64)));
+        }
+    }
+
+    #[test]
+    fn rsi_stays_between_0_and_100(bars in bars(20..200), length in 1i32..15, drift in 1i32..3) {
+        let [_, _, _, close] = bars.series();
+        let rsi = rsi(&close, RsiOptions {
+            length: Some(length),
+            drift: Some(drift),
+            ..Default::default()
+        }).unwrap();
+        for value in values(&rsi).into_iter().flatten() {
+            // without any move in the average, gains over moves is 0 / 0,
+            // which is written as missing rather than NaN
+            prop_assert!((0.0..=100.0).contains(&value), "RSI {} out of range", value);
+        }
+    }
+}